@@ -0,0 +1,453 @@
+//! Verification result cache for repeated lookups of the same digest.
+//!
+//! Admission paths verify the same image for every replica; [`verify_signature_cached`]
+//! wraps [`crate::verify_signature_with_signers`] with an in-memory LRU and an
+//! optional SQLite store shared between processes. Entries are keyed by digest and
+//! the trust policy they were checked against, including the cluster and program
+//! the signature was read from (the store is shared across profiles), expire after a positive or negative
+//! TTL, and are dropped when a matching program event is observed (see
+//! [`VerificationCache::apply_event`]).
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use solana_sdk::pubkey::Pubkey;
+use tracing::debug;
+
+use crate::events::SkelzEvent;
+use crate::{format_timestamp, parse_validity_duration, xdg_config_home, SkelzConfig, SkelzProgram, VerifyOptions};
+
+const DEFAULT_CAPACITY: usize = 1024;
+const DEFAULT_POSITIVE_TTL: Duration = Duration::from_secs(600);
+const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(30);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS verdicts (
+    digest TEXT NOT NULL,
+    policy TEXT NOT NULL,
+    allowed INTEGER NOT NULL,
+    detail TEXT NOT NULL,
+    cached_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    PRIMARY KEY (digest, policy)
+);
+";
+
+/// Cache sizing and lifetimes, from the `cache_*` config keys
+#[derive(Debug, Clone)]
+pub struct CacheSettings {
+    /// Maximum number of in-memory entries
+    pub capacity: usize,
+    /// Lifetime of successful verifications (also capped by the signature expiry)
+    pub positive_ttl: Duration,
+    /// Lifetime of failed verifications
+    pub negative_ttl: Duration,
+    /// Optional SQLite store shared across processes
+    pub path: Option<PathBuf>,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            positive_ttl: DEFAULT_POSITIVE_TTL,
+            negative_ttl: DEFAULT_NEGATIVE_TTL,
+            path: None,
+        }
+    }
+}
+
+impl CacheSettings {
+    /// Read `cache_capacity`, `cache_positive_ttl`, `cache_negative_ttl` and `cache_path`
+    pub fn from_config(cfg: &SkelzConfig) -> Result<Self> {
+        let ttl = |value: &Option<String>, default: Duration| -> Result<Duration> {
+            match value {
+                Some(value) => Ok(parse_validity_duration(value)?.to_std()?),
+                None => Ok(default),
+            }
+        };
+        Ok(Self {
+            capacity: cfg.cache_capacity.unwrap_or(DEFAULT_CAPACITY).max(1),
+            positive_ttl: ttl(&cfg.cache_positive_ttl, DEFAULT_POSITIVE_TTL)
+                .context("invalid cache_positive_ttl")?,
+            negative_ttl: ttl(&cfg.cache_negative_ttl, DEFAULT_NEGATIVE_TTL)
+                .context("invalid cache_negative_ttl")?,
+            path: cfg.cache_path.clone(),
+        })
+    }
+}
+
+/// Default on-disk store used by the CLI: `$XDG_CONFIG_HOME/skelz/verify-cache.sqlite`
+pub fn default_cache_path() -> PathBuf {
+    xdg_config_home().join("skelz").join("verify-cache.sqlite")
+}
+
+/// A digest and the trust policy it was verified against
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub digest: String,
+    pub policy: String,
+}
+
+impl CacheKey {
+    pub fn new(digest: &str, policy: &str) -> Self {
+        Self { digest: digest.to_string(), policy: policy.to_string() }
+    }
+
+    /// Key for a verification against a set of trusted signers on `cluster` and `program_id`
+    pub fn for_signers(
+        cluster: &str,
+        program_id: &Pubkey,
+        digest: &str,
+        trusted_signers: &[Pubkey],
+        options: &VerifyOptions,
+    ) -> Self {
+        let mut signers: Vec<String> = trusted_signers.iter().map(|k| k.to_string()).collect();
+        signers.sort();
+        signers.dedup();
+        let policy = format!(
+            "cluster={};program={};signers:{};allow_expired={}",
+            cluster,
+            program_id,
+            signers.join(","),
+            options.allow_expired
+        );
+        Self::new(digest, &policy)
+    }
+}
+
+/// A cached verification outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedVerdict {
+    pub allowed: bool,
+    /// Signer on success, error message on failure
+    pub detail: String,
+    pub cached_at: i64,
+    pub expires_at: i64,
+}
+
+/// In-memory LRU of verification results with an optional SQLite store
+pub struct VerificationCache {
+    settings: CacheSettings,
+    entries: HashMap<CacheKey, CachedVerdict>,
+    // Least recently used first
+    order: VecDeque<CacheKey>,
+    store: Option<Connection>,
+}
+
+impl VerificationCache {
+    pub fn new(settings: CacheSettings) -> Result<Self> {
+        let store = match settings.path.as_deref() {
+            Some(path) => Some(open_store(path)?),
+            None => None,
+        };
+        Ok(Self { settings, entries: HashMap::new(), order: VecDeque::new(), store })
+    }
+
+    pub fn settings(&self) -> &CacheSettings {
+        &self.settings
+    }
+
+    /// Number of entries held in memory
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn touch(&mut self, key: &CacheKey) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(key.clone());
+    }
+
+    fn forget(&mut self, key: &CacheKey) {
+        self.entries.remove(key);
+        self.order.retain(|k| k != key);
+    }
+
+    fn remember(&mut self, key: CacheKey, verdict: CachedVerdict) {
+        self.touch(&key);
+        self.entries.insert(key, verdict);
+        while self.entries.len() > self.settings.capacity {
+            match self.order.pop_front() {
+                Some(evicted) => {
+                    self.entries.remove(&evicted);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Look up a live verdict, checking memory first and then the store
+    pub fn get(&mut self, key: &CacheKey, now: i64) -> Result<Option<CachedVerdict>> {
+        let verdict = match self.entries.get(key).cloned() {
+            Some(verdict) => Some(verdict),
+            None => self.load(key)?,
+        };
+        match verdict {
+            Some(verdict) if verdict.expires_at > now => {
+                self.remember(key.clone(), verdict.clone());
+                Ok(Some(verdict))
+            }
+            Some(_) => {
+                self.remove(key)?;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Record an outcome; `valid_until` caps the lifetime of positive entries
+    pub fn insert(
+        &mut self,
+        key: CacheKey,
+        allowed: bool,
+        detail: &str,
+        now: i64,
+        valid_until: Option<i64>,
+    ) -> Result<CachedVerdict> {
+        let ttl = if allowed { self.settings.positive_ttl } else { self.settings.negative_ttl };
+        let mut expires_at = now.saturating_add(ttl.as_secs() as i64);
+        if allowed {
+            if let Some(valid_until) = valid_until {
+                expires_at = expires_at.min(valid_until);
+            }
+        }
+        let verdict = CachedVerdict { allowed, detail: detail.to_string(), cached_at: now, expires_at };
+        if let Some(store) = &self.store {
+            store.execute(
+                "INSERT OR REPLACE INTO verdicts (digest, policy, allowed, detail, cached_at, expires_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![key.digest, key.policy, allowed, detail, now, expires_at],
+            )?;
+        }
+        self.remember(key, verdict.clone());
+        Ok(verdict)
+    }
+
+    fn load(&self, key: &CacheKey) -> Result<Option<CachedVerdict>> {
+        let Some(store) = &self.store else { return Ok(None) };
+        Ok(store
+            .query_row(
+                "SELECT allowed, detail, cached_at, expires_at FROM verdicts WHERE digest = ?1 AND policy = ?2",
+                params![key.digest, key.policy],
+                |row| {
+                    Ok(CachedVerdict {
+                        allowed: row.get(0)?,
+                        detail: row.get(1)?,
+                        cached_at: row.get(2)?,
+                        expires_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    pub fn remove(&mut self, key: &CacheKey) -> Result<()> {
+        self.forget(key);
+        if let Some(store) = &self.store {
+            store.execute(
+                "DELETE FROM verdicts WHERE digest = ?1 AND policy = ?2",
+                params![key.digest, key.policy],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Drop every entry for a digest, whatever policy it was checked against
+    pub fn invalidate_digest(&mut self, digest: &str) -> Result<()> {
+        let stale: Vec<CacheKey> = self.entries.keys().filter(|k| k.digest == digest).cloned().collect();
+        for key in &stale {
+            self.forget(key);
+        }
+        if let Some(store) = &self.store {
+            store.execute("DELETE FROM verdicts WHERE digest = ?1", params![digest])?;
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) -> Result<()> {
+        self.entries.clear();
+        self.order.clear();
+        if let Some(store) = &self.store {
+            store.execute("DELETE FROM verdicts", [])?;
+        }
+        Ok(())
+    }
+
+    /// Invalidate entries affected by a program event
+    ///
+    /// Revocations and new signatures drop the digest; a key rotation can change the
+    /// outcome for any digest, so it clears the cache.
+    pub fn apply_event(&mut self, event: &SkelzEvent) -> Result<()> {
+        match event {
+            SkelzEvent::SignatureRevoked { digest, .. } | SkelzEvent::SignatureWritten { digest, .. } => {
+                debug!(%digest, "invalidating cached verifications");
+                self.invalidate_digest(digest)
+            }
+            // Rotations and multisig changes (co-signers) can change the outcome for any digest
+            SkelzEvent::KeyRotated { .. } | SkelzEvent::ProposalExecuted { .. } => self.clear(),
+            // Policy and keyless verifications are not cached
+            SkelzEvent::PolicyChanged { .. } | SkelzEvent::IdentityBound { .. } => Ok(()),
+        }
+    }
+}
+
+fn open_store(path: &Path) -> Result<Connection> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("create cache dir {}", parent.display()))?;
+    }
+    let conn = Connection::open(path).with_context(|| format!("open cache {}", path.display()))?;
+    conn.execute_batch(SCHEMA).context("initialize cache schema")?;
+    Ok(conn)
+}
+
+/// RPC and transport failures say nothing about the image and are never cached
+fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<solana_client::client_error::ClientError>()
+            || cause.is::<anchor_client::ClientError>()
+    })
+}
+
+/// [`crate::verify_signature_with_signers`] behind a [`VerificationCache`]
+///
+/// `cluster` names the cluster `program` reads from; verdicts are never shared across clusters.
+pub fn verify_signature_cached(
+    cache: &mut VerificationCache,
+    program: &SkelzProgram,
+    cluster: &str,
+    digest: &str,
+    trusted_signers: &[Pubkey],
+    options: &VerifyOptions,
+) -> Result<()> {
+    let key = CacheKey::for_signers(cluster, &program.id(), digest, trusted_signers, options);
+    let now = chrono::Utc::now().timestamp();
+
+    if let Some(verdict) = cache.get(&key, now)? {
+        if !verdict.allowed {
+            return Err(anyhow!("{} (cached result from {})", verdict.detail, format_timestamp(verdict.cached_at)));
+        }
+        println!("✅ Signature verification successful (cached)!");
+        println!("   - Digest: {}", digest);
+        println!("   - Signer: {}", verdict.detail);
+        println!("   - Cached at: {}", format_timestamp(verdict.cached_at));
+        println!("   - Cache expires: {}", format_timestamp(verdict.expires_at));
+        return Ok(());
+    }
+
+    match crate::verify_signature_with_signers(program, digest, trusted_signers, options) {
+        Ok(signature) => {
+            // An accepted expired signature is not bounded by its expiry
+            let valid_until = if options.allow_expired { None } else { signature.not_after };
+            cache.insert(key, true, &signature.signer.to_string(), now, valid_until)?;
+            Ok(())
+        }
+        Err(e) if is_transient(&e) => Err(e),
+        Err(e) => {
+            cache.insert(key, false, &e.to_string(), now, None)?;
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(capacity: usize) -> CacheSettings {
+        CacheSettings {
+            capacity,
+            positive_ttl: Duration::from_secs(60),
+            negative_ttl: Duration::from_secs(5),
+            path: None,
+        }
+    }
+
+    #[test]
+    fn expires_entries_by_outcome() {
+        let mut cache = VerificationCache::new(settings(8)).unwrap();
+        let good = CacheKey::new("sha256:good", "signers:a");
+        let bad = CacheKey::new("sha256:bad", "signers:a");
+        cache.insert(good.clone(), true, "a", 100, None).unwrap();
+        cache.insert(bad.clone(), false, "Signer mismatch", 100, None).unwrap();
+
+        assert!(cache.get(&good, 150).unwrap().is_some());
+        assert!(cache.get(&bad, 104).unwrap().is_some());
+        assert!(cache.get(&bad, 105).unwrap().is_none());
+        assert!(cache.get(&good, 160).unwrap().is_none());
+
+        // Signature expiry caps positive entries
+        let verdict = cache.insert(good.clone(), true, "a", 100, Some(120)).unwrap();
+        assert_eq!(verdict.expires_at, 120);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = VerificationCache::new(settings(2)).unwrap();
+        let (a, b, c) = (CacheKey::new("a", "p"), CacheKey::new("b", "p"), CacheKey::new("c", "p"));
+        cache.insert(a.clone(), true, "", 0, None).unwrap();
+        cache.insert(b.clone(), true, "", 0, None).unwrap();
+        assert!(cache.get(&a, 1).unwrap().is_some());
+        cache.insert(c.clone(), true, "", 0, None).unwrap();
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&b, 1).unwrap().is_none());
+        assert!(cache.get(&a, 1).unwrap().is_some());
+    }
+
+    #[test]
+    fn revocation_event_invalidates_disk_store() {
+        let path = std::env::temp_dir().join(format!("skelz-cache-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let disk = CacheSettings { path: Some(path.clone()), ..settings(8) };
+        let signer = Pubkey::new_unique();
+        let key = CacheKey::for_signers("devnet", &crate::skelz::ID, "sha256:abc", &[signer], &VerifyOptions::default());
+
+        let mut writer = VerificationCache::new(disk.clone()).unwrap();
+        writer.insert(key.clone(), true, &signer.to_string(), 0, None).unwrap();
+
+        // A second process sees the stored verdict until the revocation is applied
+        let mut reader = VerificationCache::new(disk.clone()).unwrap();
+        assert!(reader.get(&key, 1).unwrap().unwrap().allowed);
+        writer
+            .apply_event(&SkelzEvent::SignatureRevoked {
+                digest: "sha256:abc".to_string(),
+                signer: signer.to_string(),
+                revoked_at: 1,
+            })
+            .unwrap();
+        assert!(VerificationCache::new(disk).unwrap().get(&key, 1).unwrap().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn signer_keys_ignore_order() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let options = VerifyOptions::default();
+        let key = |cluster: &str, program: &Pubkey, signers: &[Pubkey], options: &VerifyOptions| {
+            CacheKey::for_signers(cluster, program, "d", signers, options)
+        };
+        let id = crate::skelz::ID;
+        assert_eq!(key("devnet", &id, &[a, b], &options), key("devnet", &id, &[b, a], &options));
+        assert_ne!(key("devnet", &id, &[a], &options), key("devnet", &id, &[a], &VerifyOptions { allow_expired: true }));
+    }
+
+    #[test]
+    fn keys_are_scoped_to_cluster_and_program() {
+        let signer = Pubkey::new_unique();
+        let options = VerifyOptions::default();
+        let devnet = CacheKey::for_signers("devnet", &crate::skelz::ID, "d", &[signer], &options);
+        assert_ne!(devnet, CacheKey::for_signers("mainnet-beta", &crate::skelz::ID, "d", &[signer], &options));
+        assert_ne!(devnet, CacheKey::for_signers("devnet", &Pubkey::new_unique(), "d", &[signer], &options));
+    }
+}
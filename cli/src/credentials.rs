@@ -0,0 +1,467 @@
+//! Registry credentials kept out of `config.toml`.
+//!
+//! Two sources are read besides the `GHCR_USER`/`GHCR_TOKEN` environment variables:
+//! - the skelz credential store, a file encrypted with AES-256-GCM under a key derived
+//!   from a passphrase (PBKDF2-HMAC-SHA256), written with mode 0600;
+//! - docker's `config.json`: `credHelpers`/`credsStore` entries are resolved with the
+//!   `docker-credential-<name> get` protocol, `auths` entries are decoded in place.
+//!   Docker Hub (`docker.io`) is looked up under docker's `https://index.docker.io/v1/` key.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::xdg_config_home;
+
+/// Environment variable holding the credential store passphrase (else it is prompted for)
+pub const PASSPHRASE_ENV: &str = "SKELZ_CREDENTIALS_PASSPHRASE";
+
+const STORE_VERSION: u32 = 1;
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Iteration counts above this are refused rather than hanging `open`
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const SALT_LEN: usize = 16;
+const AAD: &[u8] = b"skelz-credentials-v1";
+
+/// A username and password or token for one registry
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credential {
+    pub username: String,
+    pub secret: String,
+}
+
+impl std::fmt::Debug for Credential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credential").field("username", &self.username).field("secret", &"<redacted>").finish()
+    }
+}
+
+/// Default location of the credential store: `$XDG_CONFIG_HOME/skelz/credentials.enc`
+pub fn credential_store_path() -> PathBuf {
+    xdg_config_home().join("skelz").join("credentials.enc")
+}
+
+/// Passphrase from [`PASSPHRASE_ENV`], else read from the terminal
+pub fn store_passphrase() -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        if !passphrase.is_empty() {
+            return Ok(passphrase);
+        }
+    }
+    rpassword::prompt_password("Credential store passphrase: ").context("read credential store passphrase")
+}
+
+/// Registry host of a server address: `https://ghcr.io/v2/` and `ghcr.io` both give `ghcr.io`
+pub fn registry_host(server: &str) -> String {
+    let server = server.trim();
+    let server = server.split_once("://").map_or(server, |(_, rest)| rest);
+    server.split('/').next().unwrap_or_default().to_ascii_lowercase()
+}
+
+/// On-disk layout of the encrypted store
+#[derive(Serialize, Deserialize)]
+struct SealedStore {
+    version: u32,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Credentials by registry host, encrypted at rest
+pub struct CredentialStore {
+    path: PathBuf,
+    passphrase: String,
+    iterations: u32,
+    entries: BTreeMap<String, Credential>,
+}
+
+impl CredentialStore {
+    /// Decrypt the store at `path`; a missing file is an empty store
+    pub fn open(path: &Path, passphrase: &str) -> Result<Self> {
+        let mut store = CredentialStore {
+            path: path.to_path_buf(),
+            passphrase: passphrase.to_string(),
+            iterations: PBKDF2_ITERATIONS,
+            entries: BTreeMap::new(),
+        };
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(store),
+            Err(e) => return Err(e).with_context(|| format!("read credential store {}", path.display())),
+        };
+        warn_if_shared(path);
+        let sealed: SealedStore =
+            serde_json::from_str(&text).with_context(|| format!("credential store {} is malformed", path.display()))?;
+        if sealed.version != STORE_VERSION {
+            anyhow::bail!("credential store {} has unsupported version {}", path.display(), sealed.version);
+        }
+        let decode = |field: &str, value: &str| {
+            base64::engine::general_purpose::STANDARD
+                .decode(value)
+                .with_context(|| format!("credential store {}: {} is not base64", path.display(), field))
+        };
+        let salt = decode("salt", &sealed.salt)?;
+        let nonce = decode("nonce", &sealed.nonce)?;
+        let mut buffer = decode("ciphertext", &sealed.ciphertext)?;
+        if sealed.iterations > MAX_PBKDF2_ITERATIONS {
+            anyhow::bail!(
+                "credential store {} uses {} PBKDF2 iterations, more than the supported {}",
+                path.display(),
+                sealed.iterations,
+                MAX_PBKDF2_ITERATIONS
+            );
+        }
+        let key = derive_key(passphrase, &salt, sealed.iterations)?;
+        let nonce = Nonce::try_assume_unique_for_key(&nonce).map_err(|_| anyhow!("credential store nonce has the wrong length"))?;
+        let plaintext = key
+            .open_in_place(nonce, Aad::from(AAD), &mut buffer)
+            .map_err(|_| anyhow!("cannot decrypt credential store {}: wrong passphrase or corrupted file", path.display()))?;
+        store.entries = serde_json::from_slice(plaintext).context("decrypted credential store is malformed")?;
+        // A weaker count is only used to decrypt; the next save upgrades it
+        store.iterations = sealed.iterations.max(PBKDF2_ITERATIONS);
+        Ok(store)
+    }
+
+    /// Open the default store with [`store_passphrase`]; None when it does not exist
+    pub fn open_default() -> Result<Option<Self>> {
+        let path = credential_store_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(Self::open(&path, &store_passphrase()?)?))
+    }
+
+    pub fn get(&self, registry: &str) -> Option<&Credential> {
+        self.entries.get(&registry_host(registry))
+    }
+
+    pub fn set(&mut self, registry: &str, credential: Credential) {
+        self.entries.insert(registry_host(registry), credential);
+    }
+
+    /// Whether an entry was removed
+    pub fn remove(&mut self, registry: &str) -> bool {
+        self.entries.remove(&registry_host(registry)).is_some()
+    }
+
+    /// Registry hosts and their usernames
+    pub fn list(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(host, credential)| (host.as_str(), credential.username.as_str()))
+    }
+
+    /// Encrypt with a fresh salt and nonce and write the file with mode 0600
+    pub fn save(&self) -> Result<()> {
+        let rng = SystemRandom::new();
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut salt).map_err(|_| anyhow!("random salt"))?;
+        rng.fill(&mut nonce).map_err(|_| anyhow!("random nonce"))?;
+        let key = derive_key(&self.passphrase, &salt, self.iterations)?;
+        let mut buffer = serde_json::to_vec(&self.entries)?;
+        key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(AAD), &mut buffer)
+            .map_err(|_| anyhow!("encrypt credential store"))?;
+        let engine = base64::engine::general_purpose::STANDARD;
+        let sealed = SealedStore {
+            version: STORE_VERSION,
+            iterations: self.iterations,
+            salt: engine.encode(salt),
+            nonce: engine.encode(nonce),
+            ciphertext: engine.encode(&buffer),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = self.path.with_extension("enc.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&temp).with_context(|| format!("create {}", temp.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(serde_json::to_string_pretty(&sealed)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, &self.path).with_context(|| format!("write credential store {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(|| anyhow!("credential store iterations must be positive"))?;
+    let mut key = [0u8; 32];
+    ring::pbkdf2::derive(ring::pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+    let key = UnboundKey::new(&AES_256_GCM, &key).map_err(|_| anyhow!("credential store key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn warn_if_shared(path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = fs::metadata(path) {
+            let mode = metadata.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                warn!("credential store {} is accessible to other users (mode {:o}); run chmod 600 {}", path.display(), mode, path.display());
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// Key docker's config uses for Docker Hub, in `auths` and towards credential helpers
+const DOCKER_HUB_SERVER: &str = "https://index.docker.io/v1/";
+
+/// Registry host of a docker config key, with Docker Hub's aliases folded into `docker.io`
+fn docker_host(server: &str) -> String {
+    match registry_host(server).as_str() {
+        "index.docker.io" | "registry-1.docker.io" => "docker.io".to_string(),
+        host => host.to_string(),
+    }
+}
+
+/// Server address docker passes to credential helpers for `host`
+fn docker_server(host: &str) -> &str {
+    if host == "docker.io" {
+        DOCKER_HUB_SERVER
+    } else {
+        host
+    }
+}
+
+/// Location of docker's client config: `$DOCKER_CONFIG/config.json`, else `~/.docker/config.json`
+pub fn docker_config_path() -> PathBuf {
+    match std::env::var("DOCKER_CONFIG") {
+        Ok(dir) if !dir.trim().is_empty() => PathBuf::from(dir.trim()).join("config.json"),
+        _ => dirs_next::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".docker").join("config.json"),
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DockerConfig {
+    #[serde(default)]
+    auths: BTreeMap<String, DockerAuth>,
+    #[serde(default)]
+    cred_helpers: BTreeMap<String, String>,
+    #[serde(default)]
+    creds_store: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct DockerAuth {
+    #[serde(default)]
+    auth: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+}
+
+/// Credentials docker would use for `registry`, from the config at `path`
+///
+/// A registry-specific `credHelpers` entry wins, then `credsStore`, then the inline
+/// `auths` entry. A missing config file gives None.
+pub fn docker_credentials(path: &Path, registry: &str) -> Result<Option<Credential>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("read docker config {}", path.display())),
+    };
+    let config: DockerConfig =
+        serde_json::from_str(&text).with_context(|| format!("docker config {} is malformed", path.display()))?;
+    let host = docker_host(registry);
+
+    let helper = config
+        .cred_helpers
+        .iter()
+        .find(|(server, _)| docker_host(server) == host)
+        .map(|(_, helper)| helper)
+        .or(config.creds_store.as_ref());
+    if let Some(helper) = helper {
+        let program = format!("docker-credential-{}", helper);
+        if let Some(credential) = helper_get(Path::new(&program), docker_server(&host))? {
+            return Ok(Some(credential));
+        }
+    }
+
+    let Some(auth) = config.auths.iter().find(|(server, _)| docker_host(server) == host).map(|(_, auth)| auth) else {
+        return Ok(None);
+    };
+    if let (Some(username), Some(secret)) = (&auth.username, &auth.password) {
+        return Ok(Some(Credential { username: username.clone(), secret: secret.clone() }));
+    }
+    match auth.auth.as_deref().filter(|auth| !auth.is_empty()) {
+        Some(encoded) => {
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .with_context(|| format!("docker config auth for {} is not base64", host))?;
+            let decoded = String::from_utf8(decoded).with_context(|| format!("docker config auth for {} is not UTF-8", host))?;
+            let (username, secret) =
+                decoded.split_once(':').ok_or_else(|| anyhow!("docker config auth for {} is not user:password", host))?;
+            Ok(Some(Credential { username: username.to_string(), secret: secret.to_string() }))
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HelperCredential {
+    username: String,
+    secret: String,
+}
+
+/// Run `<program> get` with the server on stdin; None when the helper has no entry for it
+pub fn helper_get(program: &Path, server: &str) -> Result<Option<Credential>> {
+    let mut child = Command::new(program)
+        .arg("get")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("run credential helper {}", program.display()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(server.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        if stdout.contains("credentials not found") {
+            debug!(helper = %program.display(), server, "no credentials in helper");
+            return Ok(None);
+        }
+        anyhow::bail!(
+            "credential helper {} failed ({}): {}",
+            program.display(),
+            output.status,
+            format!("{}{}", stdout, String::from_utf8_lossy(&output.stderr)).trim()
+        );
+    }
+    let credential: HelperCredential = serde_json::from_str(&stdout)
+        .with_context(|| format!("credential helper {} returned malformed output", program.display()))?;
+    Ok(Some(Credential { username: credential.username, secret: credential.secret }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("skelz-credentials-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn store_round_trips_encrypted_with_private_permissions() {
+        let dir = temp_path("store");
+        let path = dir.join("credentials.enc");
+        let mut store = CredentialStore::open(&path, "correct horse").unwrap();
+        store.iterations = 1_000;
+        store.set("https://ghcr.io/v2/", Credential { username: "octocat".into(), secret: "ghp_secret".into() });
+        store.save().unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("ghp_secret") && !text.contains("octocat"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        let mut reopened = CredentialStore::open(&path, "correct horse").unwrap();
+        assert_eq!(reopened.get("ghcr.io").unwrap().secret, "ghp_secret");
+        // Decrypted with the stored count, saved with at least the default
+        assert_eq!(reopened.iterations, PBKDF2_ITERATIONS);
+        reopened.iterations = 1_000;
+        assert_eq!(reopened.list().collect::<Vec<_>>(), vec![("ghcr.io", "octocat")]);
+        let err = CredentialStore::open(&path, "wrong").err().unwrap();
+        assert!(err.to_string().contains("wrong passphrase"), "{}", err);
+
+        assert!(reopened.remove("ghcr.io"));
+        reopened.save().unwrap();
+        assert!(CredentialStore::open(&path, "correct horse").unwrap().get("ghcr.io").is_none());
+
+        let mut sealed: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        sealed["iterations"] = u32::MAX.into();
+        fs::write(&path, sealed.to_string()).unwrap();
+        let err = CredentialStore::open(&path, "correct horse").err().unwrap();
+        assert!(err.to_string().contains("PBKDF2 iterations"), "{}", err);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_docker_auths_and_credential_helpers() {
+        let dir = temp_path("docker");
+        let config = dir.join("config.json");
+        let auth = base64::engine::general_purpose::STANDARD.encode("octocat:ghp_inline");
+        fs::write(&config, format!(r#"{{"auths": {{"https://ghcr.io": {{"auth": "{}"}}, "quay.io": {{}}}}}}"#, auth)).unwrap();
+        let credential = docker_credentials(&config, "ghcr.io").unwrap().unwrap();
+        assert_eq!(credential, Credential { username: "octocat".into(), secret: "ghp_inline".into() });
+        assert!(docker_credentials(&config, "quay.io").unwrap().is_none());
+        assert!(docker_credentials(&dir.join("missing.json"), "ghcr.io").unwrap().is_none());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let helper = dir.join("docker-credential-test");
+            fs::write(
+                &helper,
+                "#!/bin/sh\nread server\nif [ \"$server\" = ghcr.io ]; then\n  echo '{\"ServerURL\":\"ghcr.io\",\"Username\":\"bot\",\"Secret\":\"ghp_helper\"}'\nelse\n  echo 'credentials not found in native keychain'; exit 1\nfi\n",
+            )
+            .unwrap();
+            fs::set_permissions(&helper, fs::Permissions::from_mode(0o700)).unwrap();
+            let credential = helper_get(&helper, "ghcr.io").unwrap().unwrap();
+            assert_eq!(credential, Credential { username: "bot".into(), secret: "ghp_helper".into() });
+            assert!(helper_get(&helper, "quay.io").unwrap().is_none());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn finds_docker_hub_under_its_index_key() {
+        let dir = temp_path("hub");
+        let config = dir.join("config.json");
+        let auth = base64::engine::general_purpose::STANDARD.encode("moby:dckr_inline");
+        fs::write(&config, format!(r#"{{"auths": {{"https://index.docker.io/v1/": {{"auth": "{}"}}}}}}"#, auth)).unwrap();
+        let credential = docker_credentials(&config, "docker.io").unwrap().unwrap();
+        assert_eq!(credential, Credential { username: "moby".into(), secret: "dckr_inline".into() });
+        assert!(docker_credentials(&config, "ghcr.io").unwrap().is_none());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            // The helper is asked for docker's Hub server address, not the bare host
+            let helper = dir.join("docker-credential-hubtest");
+            fs::write(
+                &helper,
+                "#!/bin/sh\nread server\nif [ \"$server\" = https://index.docker.io/v1/ ]; then\n  echo '{\"Username\":\"moby\",\"Secret\":\"dckr_helper\"}'\nelse\n  echo 'credentials not found in native keychain'; exit 1\nfi\n",
+            )
+            .unwrap();
+            fs::set_permissions(&helper, fs::Permissions::from_mode(0o700)).unwrap();
+            assert_eq!(docker_server("docker.io"), DOCKER_HUB_SERVER);
+            assert_eq!(helper_get(&helper, docker_server("docker.io")).unwrap().unwrap().secret, "dckr_helper");
+            assert!(helper_get(&helper, "docker.io").unwrap().is_none());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
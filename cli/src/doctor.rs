@@ -0,0 +1,241 @@
+//! `skelz doctor`: a checklist of what signing and verification depend on.
+//!
+//! Checks run in order and later checks that need an earlier result (a valid
+//! configuration, a reachable RPC endpoint) are skipped when it is missing.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+use crate::rpc::RpcPool;
+use crate::signer::{SignerSource, SkelzSigner};
+use crate::{
+    check_keypair_file, default_config_file_path, resolve_config, resolve_ghcr_credentials, ConfigOverrides,
+    SkelzConfig, SIGNATURE_ACCOUNT_SPACE,
+};
+
+/// Token endpoint used to check GHCR credentials
+pub const GHCR_TOKEN_URL: &str = "https://ghcr.io/token?service=ghcr.io";
+
+// Base fee per transaction signature, in lamports
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Works for some commands only (e.g. verification without funds)
+    Warn,
+    Fail,
+    /// Not run because an earlier check failed
+    Skip,
+}
+
+/// One line of the checklist
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl DoctorCheck {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name, status, detail: detail.into() }
+    }
+}
+
+impl fmt::Display for DoctorCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = match self.status {
+            CheckStatus::Pass => "✅",
+            CheckStatus::Warn => "⚠️ ",
+            CheckStatus::Fail => "❌",
+            CheckStatus::Skip => "⏭️ ",
+        };
+        write!(f, "{} {}: {}", mark, self.name, self.detail)
+    }
+}
+
+/// Run every check with the configuration `overrides` resolve to
+pub fn run_doctor(overrides: &ConfigOverrides) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+    let (cfg, profile) = match resolve_config(overrides) {
+        Ok(resolved) => (resolved.config, resolved.profile),
+        Err(e) => {
+            checks.push(DoctorCheck::new("config", CheckStatus::Fail, format!("{:#}", e)));
+            for name in ["keypair", "rpc", "program", "balance", "registry"] {
+                checks.push(DoctorCheck::new(name, CheckStatus::Skip, "needs a valid configuration"));
+            }
+            return checks;
+        }
+    };
+    let path = default_config_file_path();
+    let mut detail = if path.exists() {
+        format!("{} is valid", path.display())
+    } else {
+        format!("no config file at {}, using defaults", path.display())
+    };
+    if let Some(name) = &profile {
+        detail.push_str(&format!(" (profile {})", name));
+    }
+    checks.push(DoctorCheck::new("config", CheckStatus::Pass, detail));
+
+    let signer = check_keypair(&cfg);
+    let signer_key = signer.as_ref().ok().copied().flatten();
+    checks.push(match signer {
+        Ok(Some(key)) => DoctorCheck::new("keypair", CheckStatus::Pass, format!("{} ({})", key, cfg.keypair_path.display())),
+        Ok(None) => DoctorCheck::new("keypair", CheckStatus::Warn, "prompt:// signer not loaded (it would ask for the seed phrase)"),
+        Err(e) => DoctorCheck::new("keypair", CheckStatus::Fail, format!("{:#}", e)),
+    });
+
+    match check_rpc(&cfg) {
+        Ok((pool, version)) => {
+            checks.push(DoctorCheck::new("rpc", CheckStatus::Pass, format!("{} ({})", pool.url(), version)));
+            checks.push(check_program(&pool, &cfg));
+            checks.push(check_balance(&pool, &cfg, signer_key));
+        }
+        Err(e) => {
+            checks.push(DoctorCheck::new("rpc", CheckStatus::Fail, format!("{}: {}", cfg.rpc_url, e)));
+            checks.push(DoctorCheck::new("program", CheckStatus::Skip, "needs a reachable RPC endpoint"));
+            checks.push(DoctorCheck::new("balance", CheckStatus::Skip, "needs a reachable RPC endpoint"));
+        }
+    }
+
+    checks.push(match resolve_ghcr_credentials(&cfg) {
+        Ok((user, token)) => check_registry(GHCR_TOKEN_URL, &user, &token),
+        Err(_) => DoctorCheck::new(
+            "registry",
+            CheckStatus::Warn,
+            "no GHCR credentials (set GHCR_USER/GHCR_TOKEN, run `skelz registry store` or `docker login ghcr.io`; needed to sign and verify)",
+        ),
+    });
+    checks
+}
+
+/// Public key of the configured signer; None when loading it would prompt
+fn check_keypair(cfg: &SkelzConfig) -> Result<Option<Pubkey>> {
+    let location = cfg.keypair_path.to_string_lossy();
+    match SignerSource::parse(&location)? {
+        SignerSource::Prompt { .. } => return Ok(None),
+        SignerSource::File(path) => check_keypair_file(&path)?,
+        SignerSource::Env(_) | SignerSource::Remote(_) => {}
+    }
+    Ok(Some(SkelzSigner::load(&location)?.pubkey()))
+}
+
+fn check_rpc(cfg: &SkelzConfig) -> Result<(RpcPool, String)> {
+    let pool = RpcPool::from_config(cfg)?;
+    pool.select_healthy()?;
+    let (version, slot) = pool.call("version", |rpc| Ok((rpc.get_version()?, rpc.get_slot()?)))?;
+    Ok((pool, format!("solana-core {}, slot {}", version.solana_core, slot)))
+}
+
+fn check_program(pool: &RpcPool, cfg: &SkelzConfig) -> DoctorCheck {
+    let program_id = match cfg.program_id() {
+        Ok(program_id) => program_id,
+        Err(e) => return DoctorCheck::new("program", CheckStatus::Fail, format!("{:#}", e)),
+    };
+    let account = pool.call("program account", |rpc| {
+        Ok(rpc.get_account_with_commitment(&program_id, CommitmentConfig::confirmed())?.value)
+    });
+    match account {
+        Ok(Some(account)) if account.executable => {
+            DoctorCheck::new("program", CheckStatus::Pass, format!("{} is deployed on {}", program_id, cfg.cluster))
+        }
+        Ok(Some(_)) => DoctorCheck::new("program", CheckStatus::Fail, format!("{} on {} is not a program", program_id, cfg.cluster)),
+        Ok(None) => DoctorCheck::new("program", CheckStatus::Fail, format!("{} is not deployed on {}", program_id, cfg.cluster)),
+        Err(e) => DoctorCheck::new("program", CheckStatus::Fail, format!("{:#}", e)),
+    }
+}
+
+/// Whether the account paying for signatures can afford one
+fn check_balance(pool: &RpcPool, cfg: &SkelzConfig, signer: Option<Pubkey>) -> DoctorCheck {
+    if let Some(relay) = &cfg.relay_url {
+        return DoctorCheck::new("balance", CheckStatus::Pass, format!("fees and rent are paid by the relay at {}", relay));
+    }
+    let payer = match cfg.fee_payer.as_deref() {
+        Some(location) => Pubkey::from_str(location)
+            .or_else(|_| SkelzSigner::load(location).map(|s| s.pubkey()))
+            .map(Some),
+        None => Ok(signer),
+    };
+    let payer = match payer {
+        Ok(Some(payer)) => payer,
+        Ok(None) => return DoctorCheck::new("balance", CheckStatus::Skip, "needs the signer's public key"),
+        Err(e) => return DoctorCheck::new("balance", CheckStatus::Fail, format!("fee payer: {:#}", e)),
+    };
+    let amounts = pool.call("balance", |rpc| {
+        Ok((rpc.get_balance(&payer)?, rpc.get_minimum_balance_for_rent_exemption(SIGNATURE_ACCOUNT_SPACE)?))
+    });
+    let (balance, rent) = match amounts {
+        Ok(amounts) => amounts,
+        Err(e) => return DoctorCheck::new("balance", CheckStatus::Fail, format!("{:#}", e)),
+    };
+    // A fee payer distinct from the signer adds a transaction signature
+    let cost = rent + LAMPORTS_PER_SIGNATURE * if cfg.fee_payer.is_some() { 2 } else { 1 };
+    let detail = format!(
+        "{} has {:.6} SOL; a signature costs about {:.6} SOL (rent {:.6} SOL)",
+        payer,
+        balance as f64 / LAMPORTS_PER_SOL,
+        cost as f64 / LAMPORTS_PER_SOL,
+        rent as f64 / LAMPORTS_PER_SOL
+    );
+    if balance >= cost {
+        DoctorCheck::new("balance", CheckStatus::Pass, format!("{}, enough for {}", detail, balance / cost))
+    } else {
+        DoctorCheck::new("balance", CheckStatus::Warn, format!("{}; fund it to sign", detail))
+    }
+}
+
+/// Exchange GHCR credentials for a registry token at `token_url`
+pub fn check_registry(token_url: &str, user: &str, token: &str) -> DoctorCheck {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| anyhow!(e))
+        .and_then(|client| Ok(client.get(token_url).basic_auth(user, Some(token)).send()?));
+    match response {
+        Ok(response) if response.status().is_success() => {
+            DoctorCheck::new("registry", CheckStatus::Pass, format!("ghcr.io accepted the credentials of {}", user))
+        }
+        Ok(response) => DoctorCheck::new(
+            "registry",
+            CheckStatus::Fail,
+            format!("ghcr.io rejected the credentials of {} (HTTP {})", user, response.status().as_u16()),
+        ),
+        Err(e) => DoctorCheck::new("registry", CheckStatus::Fail, format!("{}: {:#}", token_url, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn serve_status(status: u16) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/token?service=ghcr.io", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            crate::http::handle_json(&mut stream, |_, _, _| (status, serde_json::json!({ "token": "t" }))).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn registry_check_reports_rejected_credentials() {
+        let check = check_registry(&serve_status(200), "octocat", "ghp_valid");
+        assert_eq!(check.status, CheckStatus::Pass);
+
+        let check = check_registry(&serve_status(401), "octocat", "ghp_revoked");
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert_eq!(check.to_string(), "❌ registry: ghcr.io rejected the credentials of octocat (HTTP 401)");
+    }
+}
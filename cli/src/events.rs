@@ -0,0 +1,523 @@
+//! Skelz program events and the signature transparency feed.
+//!
+//! The program emits Anchor events (`emit!`) for signatures, revocations, key
+//! rotations and policy changes. They appear in transaction logs as
+//! `Program data: <base64>` lines, which this module decodes. [`watch_events`]
+//! streams them either from a websocket `logsSubscribe` or by polling
+//! `getSignaturesForAddress` on the program.
+
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use serde::Serialize;
+use solana_client::pubsub_client::PubsubClient;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{
+    RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature as TxSignature;
+use solana_transaction_status::UiTransactionEncoding;
+use tracing::{debug, warn};
+
+use crate::skelz::events;
+use crate::rpc::RpcPool;
+use crate::SkelzConfig;
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+// Largest page getSignaturesForAddress returns
+const POLL_PAGE_SIZE: usize = 1000;
+
+/// A decoded skelz program event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event")]
+pub enum SkelzEvent {
+    SignatureWritten {
+        digest: String,
+        signer: String,
+        slot: u64,
+        not_after: Option<i64>,
+    },
+    SignatureRevoked {
+        digest: String,
+        signer: String,
+        revoked_at: i64,
+    },
+    KeyRotated {
+        old_key: String,
+        new_key: String,
+        effective_slot: u64,
+    },
+    PolicyChanged {
+        policy: String,
+        owner: String,
+        name: String,
+        repository: String,
+        version: u32,
+        frozen: bool,
+    },
+    IdentityBound {
+        signer: String,
+        attester: String,
+        issuer: String,
+        subject: String,
+        workflow_ref: String,
+    },
+    ProposalExecuted {
+        multisig: String,
+        signer: String,
+        index: u64,
+        co_signers: Vec<String>,
+    },
+}
+
+impl SkelzEvent {
+    /// Key that authorized the event (signer, retiring key or policy owner)
+    pub fn actor(&self) -> &str {
+        match self {
+            SkelzEvent::SignatureWritten { signer, .. } => signer,
+            SkelzEvent::SignatureRevoked { signer, .. } => signer,
+            SkelzEvent::KeyRotated { old_key, .. } => old_key,
+            SkelzEvent::PolicyChanged { owner, .. } => owner,
+            SkelzEvent::IdentityBound { attester, .. } => attester,
+            SkelzEvent::ProposalExecuted { signer, .. } => signer,
+        }
+    }
+}
+
+/// An event with its transaction context, printed as one JSON line by `skelz watch`
+#[derive(Debug, Clone, Serialize)]
+pub struct EventRecord {
+    /// Transaction signature
+    pub signature: String,
+    /// Slot of the transaction
+    pub tx_slot: u64,
+    #[serde(flatten)]
+    pub event: SkelzEvent,
+    /// Set when the actor is not one of the expected keys
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub alert: bool,
+}
+
+/// Options for [`watch_events`]
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Keys expected to act on the program; events by other keys are flagged as alerts
+    pub expected_signers: Vec<Pubkey>,
+    /// Subscribe over websocket instead of polling
+    pub websocket: bool,
+    /// Delay between polls
+    pub poll_interval: Duration,
+    /// Number of past transactions to replay before following new ones (polling only)
+    pub backfill: usize,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            expected_signers: Vec::new(),
+            websocket: false,
+            poll_interval: Duration::from_secs(5),
+            backfill: 0,
+        }
+    }
+}
+
+fn decode_as<T: Discriminator + AnchorDeserialize>(data: &[u8]) -> Option<T> {
+    let payload = data.strip_prefix(&T::DISCRIMINATOR[..])?;
+    T::deserialize(&mut &payload[..]).ok()
+}
+
+/// Decode the raw event data (discriminator + Borsh payload) of a skelz event
+pub fn decode_event_data(data: &[u8]) -> Option<SkelzEvent> {
+    if let Some(e) = decode_as::<events::SignatureWritten>(data) {
+        return Some(SkelzEvent::SignatureWritten {
+            digest: e.digest,
+            signer: e.signer.to_string(),
+            slot: e.slot,
+            not_after: e.not_after,
+        });
+    }
+    if let Some(e) = decode_as::<events::SignatureRevoked>(data) {
+        return Some(SkelzEvent::SignatureRevoked {
+            digest: e.digest,
+            signer: e.signer.to_string(),
+            revoked_at: e.revoked_at,
+        });
+    }
+    if let Some(e) = decode_as::<events::KeyRotated>(data) {
+        return Some(SkelzEvent::KeyRotated {
+            old_key: e.old_key.to_string(),
+            new_key: e.new_key.to_string(),
+            effective_slot: e.effective_slot,
+        });
+    }
+    if let Some(e) = decode_as::<events::PolicyChanged>(data) {
+        return Some(SkelzEvent::PolicyChanged {
+            policy: e.policy.to_string(),
+            owner: e.owner.to_string(),
+            name: e.name,
+            repository: e.repository,
+            version: e.version,
+            frozen: e.frozen,
+        });
+    }
+    if let Some(e) = decode_as::<events::IdentityBound>(data) {
+        return Some(SkelzEvent::IdentityBound {
+            signer: e.signer.to_string(),
+            attester: e.attester.to_string(),
+            issuer: e.issuer,
+            subject: e.subject,
+            workflow_ref: e.workflow_ref,
+        });
+    }
+    if let Some(e) = decode_as::<events::ProposalExecuted>(data) {
+        return Some(SkelzEvent::ProposalExecuted {
+            multisig: e.multisig.to_string(),
+            signer: e.signer.to_string(),
+            index: e.index,
+            co_signers: e.co_signers.iter().map(|k| k.to_string()).collect(),
+        });
+    }
+    None
+}
+
+/// Decode the skelz events emitted by `program_id` in a transaction's log messages
+///
+/// `Program data:` lines are only decoded while `program_id` is the innermost running
+/// program, following the `Program <id> invoke [n]` / `success` / `failed` lines the
+/// runtime writes. Other programs in the transaction can log arbitrary data, including
+/// bytes that decode as skelz events.
+pub fn decode_program_logs(program_id: &Pubkey, logs: &[String]) -> Vec<SkelzEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            let decoded = base64::engine::general_purpose::STANDARD.decode(data.trim()).ok();
+            events.extend(decoded.and_then(|data| decode_event_data(&data)));
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("Program ") else { continue };
+        let mut words = rest.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some(program), Some("invoke")) => stack.push(program),
+            (Some(program), Some("success")) | (Some(program), Some("failed:")) if stack.last() == Some(&program) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Derive the websocket endpoint matching an RPC URL (8899 maps to 8900 like the Solana CLI)
+pub fn websocket_url(rpc_url: &str) -> String {
+    let ws = if let Some(rest) = rpc_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = rpc_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        rpc_url.to_string()
+    };
+    ws.replacen(":8899", ":8900", 1)
+}
+
+fn to_records(
+    program_id: &Pubkey,
+    signature: &str,
+    slot: u64,
+    logs: &[String],
+    expected_signers: &[Pubkey],
+) -> Vec<EventRecord> {
+    decode_program_logs(program_id, logs)
+        .into_iter()
+        .map(|event| {
+            let alert = !expected_signers.is_empty()
+                && !expected_signers
+                    .iter()
+                    .any(|k| k.to_string() == event.actor());
+            EventRecord {
+                signature: signature.to_string(),
+                tx_slot: slot,
+                event,
+                alert,
+            }
+        })
+        .collect()
+}
+
+/// Stream skelz program events to `sink` until it fails or the connection drops
+pub fn watch_events<F>(cfg: &SkelzConfig, options: &WatchOptions, mut sink: F) -> Result<()>
+where
+    F: FnMut(&EventRecord) -> Result<()>,
+{
+    if options.websocket {
+        watch_websocket(cfg, options, &mut sink)
+    } else {
+        watch_polling(cfg, options, &mut sink)
+    }
+}
+
+fn watch_websocket<F>(cfg: &SkelzConfig, options: &WatchOptions, sink: &mut F) -> Result<()>
+where
+    F: FnMut(&EventRecord) -> Result<()>,
+{
+    let url = websocket_url(&RpcPool::from_config(cfg)?.select_healthy()?);
+    let program_id = cfg.program_id()?;
+    debug!(%url, "subscribing to skelz program logs");
+    let (_subscription, receiver) = PubsubClient::logs_subscribe(
+        &url,
+        RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+        RpcTransactionLogsConfig {
+            commitment: Some(CommitmentConfig::confirmed()),
+        },
+    )
+    .map_err(|e| anyhow!("logs subscription to {}: {}", url, e))?;
+
+    loop {
+        let response = receiver
+            .recv()
+            .map_err(|e| anyhow!("websocket subscription closed: {}", e))?;
+        if response.value.err.is_some() {
+            continue;
+        }
+        for record in to_records(
+            &program_id,
+            &response.value.signature,
+            response.context.slot,
+            &response.value.logs,
+            &options.expected_signers,
+        ) {
+            sink(&record)?;
+        }
+    }
+}
+
+/// Fetch the log messages and block time of a confirmed transaction
+pub(crate) fn fetch_transaction_logs(
+    rpc: &RpcClient,
+    signature: &TxSignature,
+) -> Result<(Vec<String>, Option<i64>)> {
+    let transaction = rpc
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .with_context(|| format!("getTransaction {}", signature))?;
+    let logs = transaction
+        .transaction
+        .meta
+        .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+        .unwrap_or_default();
+    Ok((logs, transaction.block_time))
+}
+
+fn watch_polling<F>(cfg: &SkelzConfig, options: &WatchOptions, sink: &mut F) -> Result<()>
+where
+    F: FnMut(&EventRecord) -> Result<()>,
+{
+    let pool = RpcPool::from_config(cfg)?;
+    pool.select_healthy()?;
+    let program_id = cfg.program_id()?;
+    let query = |before: Option<TxSignature>, until: Option<TxSignature>, limit: usize| {
+        pool.call("getSignaturesForAddress", |rpc| {
+            rpc.get_signatures_for_address_with_config(
+                &program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(limit),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .context("getSignaturesForAddress on the skelz program")
+        })
+    };
+
+    // Start from the most recent transaction, replaying `backfill` of them first
+    let mut pending = query(None, None, options.backfill.max(1))?;
+    if options.backfill == 0 {
+        pending.truncate(1);
+    }
+    let mut last_seen = pending
+        .first()
+        .map(|s| TxSignature::from_str(&s.signature))
+        .transpose()?;
+    if options.backfill == 0 {
+        pending.clear();
+    }
+
+    loop {
+        // RPC returns newest first; emit in chronological order
+        for entry in pending.iter().rev().filter(|s| s.err.is_none()) {
+            let signature = TxSignature::from_str(&entry.signature)?;
+            let logs = match pool.call("getTransaction", |rpc| fetch_transaction_logs(rpc, &signature)) {
+                Ok((logs, _)) => logs,
+                Err(e) => {
+                    warn!(%signature, "skipping transaction: {}", e);
+                    continue;
+                }
+            };
+            for record in to_records(
+                &program_id,
+                &entry.signature,
+                entry.slot,
+                &logs,
+                &options.expected_signers,
+            ) {
+                sink(&record)?;
+            }
+        }
+        if let Some(newest) = pending.first() {
+            last_seen = Some(TxSignature::from_str(&newest.signature)?);
+        }
+
+        thread::sleep(options.poll_interval);
+        // Pages come newest first; keep paging back to `last_seen` so bursts are not dropped
+        pending = Vec::new();
+        let mut before = None;
+        loop {
+            let page = query(before, last_seen, POLL_PAGE_SIZE)?;
+            let full = page.len() == POLL_PAGE_SIZE;
+            before = page.last().map(|s| TxSignature::from_str(&s.signature)).transpose()?;
+            pending.extend(page);
+            if !full {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    fn log_line(event: &impl Event) -> String {
+        format!(
+            "{}{}",
+            PROGRAM_DATA_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(event.data())
+        )
+    }
+
+    #[test]
+    fn decodes_events_from_logs() {
+        let signer = Pubkey::new_unique();
+        let (old_key, new_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let logs = vec![
+            "Program 4uw8DwTRdUMwGmbNrK5GZ5kgdVtco4aUaTGDnEUBrYKt invoke [1]".to_string(),
+            log_line(&events::SignatureWritten {
+                digest: "sha256:abc".to_string(),
+                signer,
+                slot: 42,
+                not_after: Some(1_900_000_000),
+            }),
+            log_line(&events::KeyRotated {
+                old_key,
+                new_key,
+                effective_slot: 99,
+            }),
+            "Program data: bm90IGFuIGV2ZW50".to_string(),
+        ];
+
+        let decoded = decode_program_logs(&crate::skelz::ID, &logs);
+        assert_eq!(
+            decoded,
+            vec![
+                SkelzEvent::SignatureWritten {
+                    digest: "sha256:abc".to_string(),
+                    signer: signer.to_string(),
+                    slot: 42,
+                    not_after: Some(1_900_000_000),
+                },
+                SkelzEvent::KeyRotated {
+                    old_key: old_key.to_string(),
+                    new_key: new_key.to_string(),
+                    effective_slot: 99,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_event_data_logged_by_other_programs() {
+        let skelz = crate::skelz::ID;
+        let spoofer = Pubkey::new_unique();
+        let forged = log_line(&events::SignatureWritten {
+            digest: "sha256:never-signed".to_string(),
+            signer: Pubkey::new_unique(),
+            slot: 1,
+            not_after: None,
+        });
+        let genuine = events::KeyRotated { old_key: Pubkey::new_unique(), new_key: Pubkey::new_unique(), effective_slot: 7 };
+        let logs = vec![
+            format!("Program {} invoke [1]", spoofer),
+            forged.clone(),
+            format!("Program {} invoke [2]", skelz),
+            log_line(&genuine),
+            format!("Program {} success", skelz),
+            forged.clone(),
+            format!("Program {} success", spoofer),
+            format!("Program {} invoke [1]", skelz),
+            format!("Program {} invoke [2]", spoofer),
+            forged,
+            format!("Program {} failed: custom program error: 0x1", spoofer),
+            format!("Program {} success", skelz),
+        ];
+        let decoded = decode_program_logs(&skelz, &logs);
+        assert_eq!(decoded.len(), 1);
+        assert!(matches!(&decoded[0], SkelzEvent::KeyRotated { effective_slot: 7, .. }));
+    }
+
+    #[test]
+    fn flags_unexpected_actors() {
+        let (trusted, intruder) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let logs = vec![
+            format!("Program {} invoke [1]", crate::skelz::ID),
+            log_line(&events::SignatureRevoked {
+                digest: "sha256:abc".to_string(),
+                signer: trusted,
+                revoked_at: 1,
+            }),
+            log_line(&events::SignatureWritten {
+                digest: "sha256:def".to_string(),
+                signer: intruder,
+                slot: 7,
+                not_after: None,
+            }),
+        ];
+        let records = to_records(&crate::skelz::ID, "5sig", 7, &logs, &[trusted]);
+        assert!(!records[0].alert);
+        assert!(records[1].alert);
+
+        let line = serde_json::to_string(&records[1]).unwrap();
+        assert!(line.contains(r#""event":"SignatureWritten""#));
+        assert!(line.contains(r#""alert":true"#));
+    }
+
+    #[test]
+    fn websocket_url_from_rpc_url() {
+        assert_eq!(
+            websocket_url("https://api.devnet.solana.com"),
+            "wss://api.devnet.solana.com"
+        );
+        assert_eq!(
+            websocket_url("http://127.0.0.1:8899"),
+            "ws://127.0.0.1:8900"
+        );
+    }
+}
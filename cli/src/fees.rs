@@ -0,0 +1,196 @@
+//! Priority fees and compute budget for skelz transactions.
+//!
+//! Without compute budget instructions a transaction pays no priority fee and
+//! reserves the default 200k compute units, so it loses out during congestion.
+//! [`compute_budget_instructions`] prices the transaction (fixed or from
+//! `getRecentPrioritizationFees`) and sizes its compute-unit limit by simulation.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use tracing::info;
+
+/// Upper bound on compute units per transaction
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+// Headroom added to the simulated consumption, in percent
+const COMPUTE_UNIT_MARGIN_PERCENT: u64 = 20;
+// Floor for the estimated limit, covering the compute budget instructions themselves
+const MIN_COMPUTE_UNIT_LIMIT: u32 = 1_000;
+// Percentile of recent prioritization fees used by `auto`
+const AUTO_FEE_PERCENTILE: usize = 75;
+
+/// Priority fee in micro-lamports per compute unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFee {
+    /// Fixed price
+    MicroLamports(u64),
+    /// Derived from recent prioritization fees paid for the written accounts
+    Auto,
+}
+
+impl FromStr for PriorityFee {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("auto") {
+            return Ok(PriorityFee::Auto);
+        }
+        value
+            .parse()
+            .map(PriorityFee::MicroLamports)
+            .map_err(|_| anyhow!("invalid priority fee '{}': expected micro-lamports or 'auto'", value))
+    }
+}
+
+impl fmt::Display for PriorityFee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriorityFee::MicroLamports(price) => write!(f, "{}", price),
+            PriorityFee::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Pick the `AUTO_FEE_PERCENTILE` percentile of recent fees (0 when there are none)
+pub fn fee_percentile(mut fees: Vec<u64>) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let rank = (fees.len() * AUTO_FEE_PERCENTILE).div_ceil(100).max(1);
+    fees[rank - 1]
+}
+
+/// Recent prioritization fee for transactions writing `accounts`
+pub fn estimate_priority_fee(rpc: &RpcClient, accounts: &[Pubkey]) -> Result<u64> {
+    let recent = rpc
+        .get_recent_prioritization_fees(accounts)
+        .context("getRecentPrioritizationFees")?;
+    Ok(fee_percentile(recent.into_iter().map(|f| f.prioritization_fee).collect()))
+}
+
+/// Compute-unit limit from simulated consumption plus headroom
+pub fn compute_unit_limit_for(units_consumed: u64) -> u32 {
+    let limit = units_consumed.saturating_mul(100 + COMPUTE_UNIT_MARGIN_PERCENT) / 100;
+    (limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32).max(MIN_COMPUTE_UNIT_LIMIT)
+}
+
+/// Simulate `instructions` and size a compute-unit limit for them
+pub fn estimate_compute_unit_limit(rpc: &RpcClient, instructions: &[Instruction], payer: &Pubkey) -> Result<u32> {
+    // Simulate with the maximum limit so the estimate is not capped by the default
+    let mut simulated = vec![ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT)];
+    simulated.extend_from_slice(instructions);
+    let transaction = Transaction::new_with_payer(&simulated, Some(payer));
+    let result = rpc
+        .simulate_transaction_with_config(
+            &transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .context("simulate transaction")?
+        .value;
+    if let Some(err) = result.err {
+        let logs = result.logs.unwrap_or_default().join("\n  ");
+        anyhow::bail!("transaction simulation failed: {}\n  {}", err, logs);
+    }
+    let consumed = result
+        .units_consumed
+        .ok_or_else(|| anyhow!("simulation did not report compute units consumed"))?;
+    Ok(compute_unit_limit_for(consumed))
+}
+
+/// Compute-unit price for `instructions`, resolving `auto` against their writable accounts
+pub fn resolve_priority_fee(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    priority_fee: Option<PriorityFee>,
+) -> Result<Option<u64>> {
+    match priority_fee {
+        None => Ok(None),
+        Some(PriorityFee::MicroLamports(price)) => Ok(Some(price)),
+        Some(PriorityFee::Auto) => {
+            let writable: Vec<Pubkey> = instructions
+                .iter()
+                .flat_map(|ix| ix.accounts.iter().filter(|a| a.is_writable).map(|a| a.pubkey))
+                .collect();
+            Ok(Some(estimate_priority_fee(rpc, &writable)?))
+        }
+    }
+}
+
+/// Lamports paid in priority fees for `limit` compute units at `price` micro-lamports each
+pub fn priority_fee_lamports(limit: u32, price: u64) -> u64 {
+    (limit as u128 * price as u128).div_ceil(1_000_000) as u64
+}
+
+/// Compute budget instructions to prepend to `instructions`
+///
+/// `compute_unit_limit` overrides the simulated estimate. Without a priority fee
+/// only the compute-unit limit is set.
+pub fn compute_budget_instructions(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    priority_fee: Option<PriorityFee>,
+    compute_unit_limit: Option<u32>,
+) -> Result<Vec<Instruction>> {
+    let limit = match compute_unit_limit {
+        Some(limit) => limit.min(MAX_COMPUTE_UNIT_LIMIT),
+        None => estimate_compute_unit_limit(rpc, instructions, payer)?,
+    };
+    let mut budget = vec![ComputeBudgetInstruction::set_compute_unit_limit(limit)];
+
+    let price = resolve_priority_fee(rpc, instructions, priority_fee)?;
+    if let Some(price) = price {
+        budget.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+    info!(compute_unit_limit = limit, priority_fee = ?price, "compute budget");
+    Ok(budget)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_priority_fee() {
+        assert_eq!("auto".parse::<PriorityFee>().unwrap(), PriorityFee::Auto);
+        assert_eq!("AUTO".parse::<PriorityFee>().unwrap(), PriorityFee::Auto);
+        assert_eq!("5000".parse::<PriorityFee>().unwrap(), PriorityFee::MicroLamports(5000));
+        assert!("fast".parse::<PriorityFee>().is_err());
+        assert!("-1".parse::<PriorityFee>().is_err());
+    }
+
+    #[test]
+    fn picks_fee_percentile() {
+        assert_eq!(fee_percentile(vec![]), 0);
+        assert_eq!(fee_percentile(vec![7]), 7);
+        assert_eq!(fee_percentile(vec![0, 0, 100, 10]), 10);
+        assert_eq!(fee_percentile((1..=100).collect()), 75);
+    }
+
+    #[test]
+    fn converts_priority_fee_to_lamports() {
+        assert_eq!(priority_fee_lamports(200_000, 0), 0);
+        assert_eq!(priority_fee_lamports(200_000, 5), 1);
+        assert_eq!(priority_fee_lamports(200_000, 10_000), 2_000);
+    }
+
+    #[test]
+    fn sizes_compute_unit_limit() {
+        assert_eq!(compute_unit_limit_for(10_000), 12_000);
+        assert_eq!(compute_unit_limit_for(0), MIN_COMPUTE_UNIT_LIMIT);
+        assert_eq!(compute_unit_limit_for(2_000_000), MAX_COMPUTE_UNIT_LIMIT);
+    }
+}
@@ -0,0 +1,76 @@
+//! Minimal HTTP/1.1 plumbing for the services `skelz` runs (relay, attester).
+//!
+//! Requests are served one at a time with `Connection: close`; bodies and
+//! responses are JSON.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+
+// Largest request body accepted (a transaction is at most 1232 bytes, a token a few KiB)
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Read one request, pass it to `route` and write its JSON response
+pub(crate) fn handle_json<F>(stream: &mut TcpStream, route: F) -> Result<()>
+where
+    F: FnOnce(&str, &str, &[u8]) -> (u16, serde_json::Value),
+{
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let (status, body) = match read_request(stream) {
+        Ok((method, path, body)) => route(&method, &path, &body),
+        Err(e) => (400, serde_json::json!({ "error": e.to_string() })),
+    };
+    let body = body.to_string();
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        _ => "Bad Gateway",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+/// Read one HTTP/1.1 request: method, path and body
+pub(crate) fn read_request(stream: &TcpStream) -> Result<(String, String, Vec<u8>)> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or_else(|| anyhow!("empty request"))?.to_string();
+    let path = parts.next().ok_or_else(|| anyhow!("request line has no path"))?.to_string();
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().context("invalid Content-Length")?;
+            }
+        }
+    }
+    if length > MAX_BODY_BYTES {
+        anyhow::bail!("request body of {} bytes is too large", length);
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok((method, path, body))
+}
@@ -0,0 +1,722 @@
+//! Local SQLite index of skelz program state.
+//!
+//! `skelz index sync` backfills signature, rotation and multisig accounts with
+//! `getProgramAccounts` on first run, then follows program transactions
+//! incrementally from the last processed one, applying the events they emit.
+//! Verification and listing queries can then be answered locally, together with
+//! how stale the index is.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use anchor_lang::Discriminator;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature as TxSignature;
+use tracing::{info, warn};
+
+use crate::events::{decode_program_logs, fetch_transaction_logs, SkelzEvent};
+use crate::rpc::RpcPool;
+use crate::multisig::{co_signers_of, CoSigner};
+use crate::skelz::accounts::{Multisig, Rotation, Signature};
+use crate::{
+    decode_signature_account, expiry_status, follow_rotation_chain, format_timestamp, signature_pda, xdg_config_home,
+    ExpiryStatus, SkelzConfig, VerifyOptions,
+};
+
+// getSignaturesForAddress page size (RPC maximum)
+pub(crate) const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS signatures (
+    digest TEXT PRIMARY KEY,
+    pda TEXT NOT NULL,
+    signer TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    not_after INTEGER,
+    revoked_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS signatures_by_signer ON signatures (signer);
+CREATE TABLE IF NOT EXISTS rotations (
+    old_key TEXT PRIMARY KEY,
+    new_key TEXT NOT NULL,
+    effective_slot INTEGER NOT NULL,
+    created_slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS co_signers (
+    signer TEXT NOT NULL,
+    co_signer TEXT NOT NULL,
+    added_slot INTEGER NOT NULL,
+    PRIMARY KEY (signer, co_signer)
+);
+CREATE TABLE IF NOT EXISTS sync_state (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    cluster TEXT NOT NULL,
+    program_id TEXT NOT NULL,
+    last_slot INTEGER,
+    last_signature TEXT,
+    synced_at INTEGER
+);
+";
+
+/// A signature account as stored in the index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedSignature {
+    pub digest: String,
+    pub pda: Pubkey,
+    pub signer: Pubkey,
+    pub slot: u64,
+    pub created_at: i64,
+    pub not_after: Option<i64>,
+    pub revoked_at: Option<i64>,
+}
+
+/// Sync cursor and row counts of an index
+#[derive(Debug, Clone, Default)]
+pub struct IndexStatus {
+    /// Cluster the index was built from
+    pub cluster: Option<String>,
+    /// Slot of the last processed program transaction
+    pub last_slot: Option<u64>,
+    /// Last processed program transaction
+    pub last_signature: Option<String>,
+    /// Unix timestamp of the last successful sync
+    pub synced_at: Option<i64>,
+    pub signatures: u64,
+    pub rotations: u64,
+}
+
+impl IndexStatus {
+    /// Seconds elapsed since the last sync, if the index was ever synced
+    pub fn age(&self, now: i64) -> Option<i64> {
+        self.synced_at.map(|synced_at| (now - synced_at).max(0))
+    }
+
+    /// One-line staleness summary printed alongside answers served from the index
+    pub fn describe(&self, now: i64) -> String {
+        match (self.synced_at, self.last_slot) {
+            (Some(synced_at), last_slot) => format!(
+                "synced {}s ago ({}), last program slot {}",
+                self.age(now).unwrap_or_default(),
+                format_timestamp(synced_at),
+                last_slot.map(|s| s.to_string()).unwrap_or_else(|| "none".to_string())
+            ),
+            (None, _) => "never synced".to_string(),
+        }
+    }
+}
+
+/// Summary of a `sync` run
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    /// Accounts loaded by the initial getProgramAccounts backfill
+    pub accounts: usize,
+    /// Program transactions processed
+    pub transactions: usize,
+    /// Events applied to the index
+    pub events: usize,
+    /// Slot of the newest processed transaction
+    pub last_slot: Option<u64>,
+}
+
+/// Default index location: `$XDG_CONFIG_HOME/skelz/index.sqlite`
+pub fn default_index_path() -> PathBuf {
+    xdg_config_home().join("skelz").join("index.sqlite")
+}
+
+/// SQLite-backed cache of skelz signatures and rotations
+pub struct SignatureIndex {
+    conn: Connection,
+}
+
+fn pubkey_column(row: &Row, index: usize) -> rusqlite::Result<Pubkey> {
+    let value: String = row.get(index)?;
+    Pubkey::from_str(&value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn signature_row(row: &Row) -> rusqlite::Result<IndexedSignature> {
+    Ok(IndexedSignature {
+        digest: row.get(0)?,
+        pda: pubkey_column(row, 1)?,
+        signer: pubkey_column(row, 2)?,
+        slot: row.get::<_, i64>(3)? as u64,
+        created_at: row.get(4)?,
+        not_after: row.get(5)?,
+        revoked_at: row.get(6)?,
+    })
+}
+
+const SIGNATURE_COLUMNS: &str = "digest, pda, signer, slot, created_at, not_after, revoked_at";
+
+impl SignatureIndex {
+    /// Open (or create) the index at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create index dir {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("open index {}", path.display()))?;
+        Self::with_connection(conn)
+    }
+
+    /// In-memory index, mostly useful for tests
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA).context("initialize index schema")?;
+        Ok(Self { conn })
+    }
+
+    /// Bind the index to a cluster and program; an index is never shared between them
+    pub fn bind(&self, cluster: &str, program_id: &Pubkey) -> Result<()> {
+        let bound: Option<(String, String)> = self
+            .conn
+            .query_row("SELECT cluster, program_id FROM sync_state WHERE id = 0", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?;
+        match bound {
+            None => {
+                self.conn.execute(
+                    "INSERT INTO sync_state (id, cluster, program_id) VALUES (0, ?1, ?2)",
+                    params![cluster, program_id.to_string()],
+                )?;
+            }
+            Some((bound_cluster, bound_program)) => {
+                if bound_cluster != cluster || bound_program != program_id.to_string() {
+                    anyhow::bail!(
+                        "index was built for cluster {} / program {}, not {} / {}; use another --index path",
+                        bound_cluster,
+                        bound_program,
+                        cluster,
+                        program_id
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> Result<IndexStatus> {
+        let mut status = self
+            .conn
+            .query_row(
+                "SELECT cluster, last_slot, last_signature, synced_at FROM sync_state WHERE id = 0",
+                [],
+                |row| {
+                    Ok(IndexStatus {
+                        cluster: row.get(0)?,
+                        last_slot: row.get::<_, Option<i64>>(1)?.map(|s| s as u64),
+                        last_signature: row.get(2)?,
+                        synced_at: row.get(3)?,
+                        ..IndexStatus::default()
+                    })
+                },
+            )
+            .optional()?
+            .unwrap_or_default();
+        status.signatures =
+            self.conn.query_row("SELECT COUNT(*) FROM signatures", [], |row| row.get::<_, i64>(0))? as u64;
+        status.rotations =
+            self.conn.query_row("SELECT COUNT(*) FROM rotations", [], |row| row.get::<_, i64>(0))? as u64;
+        Ok(status)
+    }
+
+    fn set_cursor(&self, last_slot: Option<u64>, last_signature: Option<&str>, synced_at: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE sync_state SET last_slot = COALESCE(?1, last_slot), \
+             last_signature = COALESCE(?2, last_signature), synced_at = ?3 WHERE id = 0",
+            params![last_slot.map(|s| s as i64), last_signature, synced_at],
+        )?;
+        Ok(())
+    }
+
+    pub fn upsert_signature(&self, signature: &IndexedSignature) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO signatures (digest, pda, signer, slot, created_at, not_after, revoked_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) \
+             ON CONFLICT (digest) DO UPDATE SET pda = ?2, signer = ?3, slot = ?4, \
+             created_at = ?5, not_after = ?6, revoked_at = ?7",
+            params![
+                signature.digest,
+                signature.pda.to_string(),
+                signature.signer.to_string(),
+                signature.slot as i64,
+                signature.created_at,
+                signature.not_after,
+                signature.revoked_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn upsert_rotation(&self, old_key: &Pubkey, new_key: &Pubkey, effective_slot: u64, created_slot: u64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO rotations (old_key, new_key, effective_slot, created_slot) VALUES (?1, ?2, ?3, ?4) \
+             ON CONFLICT (old_key) DO UPDATE SET new_key = ?2, effective_slot = ?3, created_slot = ?4",
+            params![old_key.to_string(), new_key.to_string(), effective_slot as i64, created_slot as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the co-signers of the multisig governing `signer`, as read from its account
+    pub fn replace_co_signers(&self, signer: &Pubkey, co_signers: &[CoSigner]) -> Result<()> {
+        self.conn.execute("DELETE FROM co_signers WHERE signer = ?1", params![signer.to_string()])?;
+        for co_signer in co_signers {
+            self.conn.execute(
+                "INSERT INTO co_signers (signer, co_signer, added_slot) VALUES (?1, ?2, ?3)",
+                params![signer.to_string(), co_signer.key.to_string(), co_signer.added_slot as i64],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Record the co-signer keys of `signer` after a proposal executed at `slot`
+    ///
+    /// Keys already indexed keep the slot they were added at; keys no longer listed are dropped.
+    pub fn update_co_signers(&self, signer: &Pubkey, keys: &[Pubkey], slot: u64) -> Result<()> {
+        let known = self.co_signers(signer)?;
+        let co_signers: Vec<CoSigner> = keys
+            .iter()
+            .map(|key| CoSigner {
+                key: *key,
+                added_slot: known.iter().find(|c| c.key == *key).map_or(slot, |c| c.added_slot),
+            })
+            .collect();
+        self.replace_co_signers(signer, &co_signers)
+    }
+
+    /// Co-signers of the multisig governing `signer` (empty without one)
+    pub fn co_signers(&self, signer: &Pubkey) -> Result<Vec<CoSigner>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT co_signer, added_slot FROM co_signers WHERE signer = ?1 ORDER BY added_slot, co_signer")?;
+        let rows = statement.query_map(params![signer.to_string()], |row| {
+            Ok(CoSigner { key: pubkey_column(row, 0)?, added_slot: row.get::<_, i64>(1)? as u64 })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Apply a decoded program event emitted at `slot` (`block_time` stands in for `created_at`)
+    pub fn apply_event(&self, program_id: &Pubkey, event: &SkelzEvent, slot: u64, block_time: i64) -> Result<()> {
+        match event {
+            SkelzEvent::SignatureWritten { digest, signer, slot: signed_slot, not_after } => {
+                self.upsert_signature(&IndexedSignature {
+                    digest: digest.clone(),
+                    pda: signature_pda(program_id, digest),
+                    signer: Pubkey::from_str(signer)?,
+                    slot: *signed_slot,
+                    created_at: block_time,
+                    not_after: *not_after,
+                    revoked_at: None,
+                })
+            }
+            SkelzEvent::SignatureRevoked { digest, revoked_at, .. } => {
+                let updated = self.conn.execute(
+                    "UPDATE signatures SET revoked_at = ?2 WHERE digest = ?1",
+                    params![digest, revoked_at],
+                )?;
+                if updated == 0 {
+                    warn!(%digest, "revocation for a signature missing from the index");
+                }
+                Ok(())
+            }
+            SkelzEvent::KeyRotated { old_key, new_key, effective_slot } => self.upsert_rotation(
+                &Pubkey::from_str(old_key)?,
+                &Pubkey::from_str(new_key)?,
+                *effective_slot,
+                slot,
+            ),
+            SkelzEvent::ProposalExecuted { signer, co_signers, .. } => {
+                let keys = co_signers.iter().map(|key| Pubkey::from_str(key)).collect::<Result<Vec<_>, _>>()?;
+                self.update_co_signers(&Pubkey::from_str(signer)?, &keys, slot)
+            }
+            SkelzEvent::PolicyChanged { .. } | SkelzEvent::IdentityBound { .. } => Ok(()),
+        }
+    }
+
+    /// Apply the events `program_id` emitted in a transaction's logs; returns how many
+    ///
+    /// Data logged by other programs in the transaction is ignored (see [`decode_program_logs`]).
+    pub fn apply_transaction_logs(&self, program_id: &Pubkey, logs: &[String], slot: u64, block_time: i64) -> Result<usize> {
+        let events = decode_program_logs(program_id, logs);
+        for event in &events {
+            self.apply_event(program_id, event, slot, block_time)?;
+        }
+        Ok(events.len())
+    }
+
+    pub fn signature(&self, digest: &str) -> Result<Option<IndexedSignature>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {} FROM signatures WHERE digest = ?1", SIGNATURE_COLUMNS),
+                params![digest],
+                signature_row,
+            )
+            .optional()?)
+    }
+
+    /// Signatures recorded by `signer`, newest first
+    pub fn signatures_by_signer(&self, signer: &Pubkey) -> Result<Vec<IndexedSignature>> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {} FROM signatures WHERE signer = ?1 ORDER BY slot DESC",
+            SIGNATURE_COLUMNS
+        ))?;
+        let rows = statement.query_map(params![signer.to_string()], signature_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Successor of `key` and the slot from which the rotation is effective
+    pub fn rotation(&self, key: &Pubkey) -> Result<Option<(Pubkey, u64)>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT new_key, effective_slot FROM rotations WHERE old_key = ?1",
+                params![key.to_string()],
+                |row| Ok((pubkey_column(row, 0)?, row.get::<_, i64>(1)? as u64)),
+            )
+            .optional()?)
+    }
+}
+
+/// Bring the index up to date with the cluster
+///
+/// The first run loads every signature and rotation account; later runs only
+/// replay program transactions newer than the last processed one.
+pub fn sync_index(cfg: &SkelzConfig, index: &SignatureIndex) -> Result<SyncReport> {
+    let program_id = cfg.program_id()?;
+    index.bind(&cfg.cluster, &program_id)?;
+    let status = index.status()?;
+    let pool = RpcPool::from_config(cfg)?;
+    pool.select_healthy()?;
+    let mut report = SyncReport::default();
+
+    let until = status
+        .last_signature
+        .as_deref()
+        .map(TxSignature::from_str)
+        .transpose()
+        .context("invalid cursor in index")?;
+
+    // Pages come newest first; collect everything after the cursor
+    let mut pending = Vec::new();
+    let mut before = None;
+    loop {
+        let page = pool.call("getSignaturesForAddress", |rpc| {
+            rpc.get_signatures_for_address_with_config(
+                &program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURE_PAGE_SIZE),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .context("getSignaturesForAddress on the skelz program")
+        })?;
+        let full = page.len() == SIGNATURE_PAGE_SIZE;
+        before = page.last().map(|s| TxSignature::from_str(&s.signature)).transpose()?;
+        pending.extend(page);
+        // Without a cursor the account snapshot below covers history; only the newest is needed
+        if until.is_none() || !full {
+            break;
+        }
+    }
+
+    let newest = pending.first().map(|s| (s.slot, s.signature.clone()));
+    if until.is_none() {
+        report.accounts = backfill_accounts(cfg, index)?;
+    } else {
+        let now = chrono::Utc::now().timestamp();
+        for entry in pending.iter().rev().filter(|s| s.err.is_none()) {
+            let signature = TxSignature::from_str(&entry.signature)?;
+            let (logs, block_time) =
+                pool.call("getTransaction", |rpc| fetch_transaction_logs(rpc, &signature))?;
+            report.events += index.apply_transaction_logs(&program_id, &logs, entry.slot, block_time.unwrap_or(now))?;
+            report.transactions += 1;
+        }
+    }
+
+    report.last_slot = newest.as_ref().map(|(slot, _)| *slot).or(status.last_slot);
+    index.set_cursor(
+        newest.as_ref().map(|(slot, _)| *slot),
+        newest.as_ref().map(|(_, signature)| signature.as_str()),
+        chrono::Utc::now().timestamp(),
+    )?;
+    info!(?report, "index synced");
+    Ok(report)
+}
+
+fn backfill_accounts(cfg: &SkelzConfig, index: &SignatureIndex) -> Result<usize> {
+    let program = crate::load_skelz_program(cfg)?;
+    let mut loaded = 0;
+    // Fetched raw so that legacy-layout accounts decode too (see decode_signature_account)
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &Signature::DISCRIMINATOR))]),
+        account_config: RpcAccountInfoConfig { encoding: Some(UiAccountEncoding::Base64), ..Default::default() },
+        ..Default::default()
+    };
+    for (pda, account) in program.rpc().get_program_accounts_with_config(&program.id(), config)? {
+        let signature = match decode_signature_account(&account.data) {
+            Ok(signature) => signature,
+            Err(e) => {
+                warn!(%pda, "skipping signature account: {:#}", e);
+                continue;
+            }
+        };
+        index.upsert_signature(&IndexedSignature {
+            digest: signature.digest,
+            pda,
+            signer: signature.signer,
+            slot: signature.slot,
+            created_at: signature.created_at,
+            not_after: signature.not_after,
+            revoked_at: signature.revoked_at,
+        })?;
+        loaded += 1;
+    }
+    for account in program.accounts_lazy::<Rotation>(vec![])? {
+        let Ok((_, rotation)) = account else { continue };
+        index.upsert_rotation(&rotation.old_key, &rotation.new_key, rotation.effective_slot, rotation.created_slot)?;
+        loaded += 1;
+    }
+    for account in program.accounts_lazy::<Multisig>(vec![])? {
+        let Ok((_, multisig)) = account else { continue };
+        index.replace_co_signers(&multisig.signer, &multisig.co_signers)?;
+        loaded += 1;
+    }
+    Ok(loaded)
+}
+
+/// Verify a digest against trusted signers using only the index
+///
+/// Applies the same checks as [`crate::verify_signature_with_signers`]: revocation,
+/// multisig co-signers, rotation chains and expiry (against local time). Returns
+/// the indexed record and the rotation path to the trusted key.
+pub fn verify_signature_from_index(
+    index: &SignatureIndex,
+    digest: &str,
+    trusted_signers: &[Pubkey],
+    options: &VerifyOptions,
+    now: i64,
+) -> Result<(IndexedSignature, Vec<Pubkey>, ExpiryStatus)> {
+    let signature = index
+        .signature(digest)?
+        .ok_or_else(|| anyhow!("Signature for {} not found in the index (run `skelz index sync`)", digest))?;
+
+    if let Some(revoked_at) = signature.revoked_at {
+        anyhow::bail!("Signature was revoked by its signer at {}", format_timestamp(revoked_at));
+    }
+
+    let co_signers = co_signers_of(trusted_signers, signature.slot, |key| index.co_signers(key))?;
+    let mut trusted = trusted_signers.to_vec();
+    trusted.extend(co_signers.iter().map(|(co_signer, _)| *co_signer));
+    let chain = follow_rotation_chain(signature.signer, signature.slot, &trusted, |key| index.rotation(key))?;
+
+    let expiry = expiry_status(signature.not_after, now);
+    if let ExpiryStatus::Expired { not_after } = expiry {
+        if !options.allow_expired {
+            anyhow::bail!(
+                "Signature expired at {} (use --allow-expired to accept it anyway)",
+                format_timestamp(not_after)
+            );
+        }
+    }
+    Ok((signature, chain, expiry))
+}
+
+/// Complete verification of an image answered from the local index
+pub fn verify_image_signature_from_index(
+    image_reference: &str,
+    expected_signer: &str,
+    config: &SkelzConfig,
+    index_path: &Path,
+    options: &VerifyOptions,
+) -> Result<()> {
+    let digest = crate::extract_digest_from_reference(image_reference)?;
+    let expected_pubkey = Pubkey::from_str(expected_signer)
+        .context("Invalid expected signer public key format")?;
+
+    let index = SignatureIndex::open(index_path)?;
+    index.bind(&config.cluster, &config.program_id()?)?;
+    let now = chrono::Utc::now().timestamp();
+    let status = index.status()?;
+
+    let (signature, chain, expiry) =
+        verify_signature_from_index(&index, &digest, &[expected_pubkey], options, now)?;
+
+    println!("✅ Signature verification successful (from index)!");
+    println!("   - Digest: {}", signature.digest);
+    println!("   - Signer: {}", signature.signer);
+    println!("   - Slot: {}", signature.slot);
+    println!("   - Signed at: {}", format_timestamp(signature.created_at));
+    match expiry {
+        ExpiryStatus::NoExpiry => println!("   - Expiry: none"),
+        ExpiryStatus::Valid { not_after } => {
+            println!("   - Expiry: valid until {}", format_timestamp(not_after))
+        }
+        ExpiryStatus::Expired { not_after } => {
+            println!("   - Expiry: ⚠️ EXPIRED since {} (allowed by override)", format_timestamp(not_after))
+        }
+    }
+    println!("   - PDA: {}", signature.pda);
+    if chain.len() > 1 {
+        let path: Vec<String> = chain.iter().map(|k| k.to_string()).collect();
+        println!("   - Trusted via rotation: {}", path.join(" -> "));
+    }
+    println!("   - Index: {}", status.describe(now));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(digest: &str, signer: &Pubkey, slot: u64) -> SkelzEvent {
+        SkelzEvent::SignatureWritten {
+            digest: digest.to_string(),
+            signer: signer.to_string(),
+            slot,
+            not_after: None,
+        }
+    }
+
+    #[test]
+    fn applies_events_and_answers_queries() {
+        let index = SignatureIndex::open_in_memory().unwrap();
+        let program_id = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        index.bind("localnet", &program_id).unwrap();
+
+        index.apply_event(&program_id, &written("sha256:aaa", &signer, 10), 10, 1_000).unwrap();
+        index.apply_event(&program_id, &written("sha256:bbb", &signer, 20), 20, 2_000).unwrap();
+        index
+            .apply_event(
+                &program_id,
+                &SkelzEvent::SignatureRevoked {
+                    digest: "sha256:aaa".to_string(),
+                    signer: signer.to_string(),
+                    revoked_at: 3_000,
+                },
+                30,
+                3_000,
+            )
+            .unwrap();
+
+        let listed = index.signatures_by_signer(&signer).unwrap();
+        assert_eq!(listed.iter().map(|s| s.digest.as_str()).collect::<Vec<_>>(), ["sha256:bbb", "sha256:aaa"]);
+        assert_eq!(listed[1].revoked_at, Some(3_000));
+        assert_eq!(listed[0].pda, signature_pda(&program_id, "sha256:bbb"));
+        assert_eq!(listed[0].created_at, 2_000);
+
+        let status = index.status().unwrap();
+        assert_eq!((status.signatures, status.rotations), (2, 0));
+        assert_eq!(status.describe(0), "never synced");
+    }
+
+    #[test]
+    fn verifies_through_indexed_rotations() {
+        let index = SignatureIndex::open_in_memory().unwrap();
+        let program_id = Pubkey::new_unique();
+        let (old_key, new_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        index.apply_event(&program_id, &written("sha256:aaa", &old_key, 10), 10, 1_000).unwrap();
+        index
+            .apply_event(
+                &program_id,
+                &SkelzEvent::KeyRotated {
+                    old_key: old_key.to_string(),
+                    new_key: new_key.to_string(),
+                    effective_slot: 50,
+                },
+                40,
+                1_500,
+            )
+            .unwrap();
+
+        let (_, chain, _) =
+            verify_signature_from_index(&index, "sha256:aaa", &[new_key], &VerifyOptions::default(), 2_000)
+                .unwrap();
+        assert_eq!(chain, vec![old_key, new_key]);
+
+        let missing =
+            verify_signature_from_index(&index, "sha256:ccc", &[new_key], &VerifyOptions::default(), 2_000);
+        assert!(missing.unwrap_err().to_string().contains("not found in the index"));
+    }
+
+    #[test]
+    fn verifies_through_indexed_co_signers() {
+        let index = SignatureIndex::open_in_memory().unwrap();
+        let program_id = Pubkey::new_unique();
+        let (release, co_signer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let executed = |co_signers: Vec<String>| SkelzEvent::ProposalExecuted {
+            multisig: Pubkey::new_unique().to_string(),
+            signer: release.to_string(),
+            index: 0,
+            co_signers,
+        };
+
+        index.apply_event(&program_id, &written("sha256:early", &co_signer, 40), 40, 1_000).unwrap();
+        index.apply_event(&program_id, &executed(vec![co_signer.to_string()]), 50, 1_100).unwrap();
+        index.apply_event(&program_id, &written("sha256:late", &co_signer, 60), 60, 1_200).unwrap();
+        // A later proposal keeps the slot the co-signer was added at
+        index.apply_event(&program_id, &executed(vec![co_signer.to_string()]), 70, 1_300).unwrap();
+        let indexed = index.co_signers(&release).unwrap();
+        assert_eq!(indexed.iter().map(|c| (c.key, c.added_slot)).collect::<Vec<_>>(), [(co_signer, 50)]);
+
+        let options = VerifyOptions::default();
+        let (signature, _, _) = verify_signature_from_index(&index, "sha256:late", &[release], &options, 2_000).unwrap();
+        assert_eq!(signature.signer, co_signer);
+        assert!(verify_signature_from_index(&index, "sha256:early", &[release], &options, 2_000).is_err());
+
+        index.apply_event(&program_id, &executed(Vec::new()), 80, 1_400).unwrap();
+        assert!(verify_signature_from_index(&index, "sha256:late", &[release], &options, 2_000).is_err());
+    }
+
+    #[test]
+    fn ignores_signatures_logged_by_other_programs() {
+        use anchor_lang::Event;
+        use base64::Engine as _;
+
+        let index = SignatureIndex::open_in_memory().unwrap();
+        let (program_id, spoofer, trusted) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let data = crate::skelz::events::SignatureWritten {
+            digest: "sha256:forged".to_string(),
+            signer: trusted,
+            slot: 10,
+            not_after: None,
+        }
+        .data();
+        let forged = format!("Program data: {}", base64::engine::general_purpose::STANDARD.encode(data));
+        // The transaction lists the skelz program as an account but never invokes it
+        let logs = vec![format!("Program {} invoke [1]", spoofer), forged.clone(), format!("Program {} success", spoofer)];
+        assert_eq!(index.apply_transaction_logs(&program_id, &logs, 10, 1_000).unwrap(), 0);
+        let verdict = verify_signature_from_index(&index, "sha256:forged", &[trusted], &VerifyOptions::default(), 2_000);
+        assert!(verdict.is_err());
+
+        let logs = vec![format!("Program {} invoke [1]", program_id), forged, format!("Program {} success", program_id)];
+        assert_eq!(index.apply_transaction_logs(&program_id, &logs, 10, 1_000).unwrap(), 1);
+    }
+
+    #[test]
+    fn refuses_index_from_another_cluster() {
+        let index = SignatureIndex::open_in_memory().unwrap();
+        let program_id = Pubkey::new_unique();
+        index.bind("devnet", &program_id).unwrap();
+        index.bind("devnet", &program_id).unwrap();
+        assert!(index.bind("mainnet-beta", &program_id).is_err());
+    }
+}
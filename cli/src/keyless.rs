@@ -0,0 +1,728 @@
+//! Keyless signing: ephemeral keys bound on-chain to an OIDC identity.
+//!
+//! `skelz sign --keyless` generates a throwaway keypair and builds one
+//! transaction holding `bind_identity` (issuer, subject and workflow ref of the
+//! caller's ID token) and `write_signature`, both for that key. An attester
+//! (`skelz attester`, run by whoever verifiers trust) checks the ID token
+//! against the issuer's keys and that the transaction records exactly the
+//! token's claims, then co-signs as attester and fee payer and submits. The
+//! ephemeral key is discarded afterwards.
+//!
+//! `skelz verify --oidc-issuer --identity-regexp` accepts a signature when its
+//! key has an identity binding from a trusted attester with that issuer and an
+//! identity matching the expression (see [`crate::oidc::identity_for`]).
+//!
+//! Attester API (JSON):
+//! - `GET /v1/info` → `{"attester", "program_id", "issuer", "audience"}`
+//! - `POST /v1/attest` `{"token", "transaction": "<base64 bincode>"}` →
+//!   `{"signature", "signer", "digest", "issuer", "identity"}`; errors are
+//!   `{"error"}` with status 400 (rejected), 401 (invalid token), 403 (identity
+//!   not allowed) or 502 (submit failed)
+
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::time::Duration;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature as TxSignature, Signer};
+use solana_sdk::transaction::Transaction;
+use tracing::{info, warn};
+
+use crate::oidc::{self, OidcClaims, OidcVerifier};
+use crate::rpc::RpcPool;
+use crate::skelz::accounts::IdentityBinding;
+use crate::skelz::client::{accounts, args};
+use crate::{
+    extract_digest_from_reference, fees, fetch_optional_account, fetch_signature, http, relay, signature_pda,
+    skelz_program_with_payer, write_signature_instructions, SignOptions, SkelzConfig, SkelzError, SkelzProgram,
+    SkelzSigner, VerifyOptions,
+};
+
+/// Derive the identity binding PDA of an (ephemeral) signing key
+pub fn identity_binding_pda(program_id: &Pubkey, signer: &Pubkey) -> Pubkey {
+    let (pda, _bump) = Pubkey::find_program_address(&[b"identity", signer.as_ref()], program_id);
+    pda
+}
+
+/// Fetch the identity binding of a signing key, if it has one
+pub fn fetch_identity_binding(program: &SkelzProgram, signer: &Pubkey) -> Result<Option<IdentityBinding>> {
+    fetch_optional_account(program, &identity_binding_pda(&program.id(), signer))
+}
+
+/// What the attester is willing to vouch for and pay
+#[derive(Debug, Clone)]
+pub struct AttesterPolicy {
+    /// Identities allowed to sign (default: any identity of the issuer)
+    pub identity_regexp: Option<Regex>,
+    /// Highest compute-unit price accepted, in micro-lamports
+    pub max_priority_fee: u64,
+    /// Highest compute-unit limit accepted
+    pub max_compute_unit_limit: u32,
+}
+
+impl Default for AttesterPolicy {
+    fn default() -> Self {
+        AttesterPolicy { identity_regexp: None, max_priority_fee: 100_000, max_compute_unit_limit: 200_000 }
+    }
+}
+
+/// Why a binding was not attested
+#[derive(Debug, thiserror::Error)]
+pub enum AttestError {
+    #[error("{0}")]
+    Rejected(String),
+    #[error("invalid ID token: {0}")]
+    Unauthorized(String),
+    #[error("identity {0} is not allowed by this attester")]
+    NotAllowed(String),
+    #[error("submit failed: {0}")]
+    Submit(String),
+}
+
+impl AttestError {
+    fn status(&self) -> u16 {
+        match self {
+            AttestError::Rejected(_) => 400,
+            AttestError::Unauthorized(_) => 401,
+            AttestError::NotAllowed(_) => 403,
+            AttestError::Submit(_) => 502,
+        }
+    }
+}
+
+fn rejected(message: impl Into<String>) -> AttestError {
+    AttestError::Rejected(message.into())
+}
+
+/// A transaction that passed [`validate_keyless_transaction`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedKeyless {
+    /// The ephemeral signing key
+    pub signer: Pubkey,
+    pub digest: String,
+}
+
+/// Check that `transaction` only binds `claims` to one ephemeral key and records its signature
+///
+/// `attester` must be the fee payer, attester and rent payer. Every other
+/// signature must already be present and valid.
+pub fn validate_keyless_transaction(
+    transaction: &Transaction,
+    program_id: &Pubkey,
+    attester: &Pubkey,
+    claims: &OidcClaims,
+    policy: &AttesterPolicy,
+) -> Result<ValidatedKeyless, AttestError> {
+    let budget = (policy.max_compute_unit_limit, policy.max_priority_fee);
+    let instructions = relay::sponsored_instructions(transaction, program_id, attester, "attester", budget)
+        .map_err(AttestError::Rejected)?;
+
+    let (mut bound, mut write) = (None, None);
+    for instruction in instructions {
+        let key = |position| instruction.account(position).map_err(AttestError::Rejected);
+        if let Some(data) = instruction.data.strip_prefix(&args::BindIdentity::DISCRIMINATOR[..]) {
+            let decoded = args::BindIdentity::deserialize(&mut &data[..])
+                .map_err(|e| rejected(format!("bind_identity arguments do not decode: {}", e)))?;
+            let (signer, attester_key, payer, binding) = (key(0)?, key(1)?, key(2)?, key(3)?);
+            if attester_key != *attester || payer != *attester {
+                return Err(rejected("bind_identity attester and payer must be the attester key"));
+            }
+            if binding != identity_binding_pda(program_id, &signer) {
+                return Err(rejected("identity account does not match the signer"));
+            }
+            if decoded.issuer != claims.iss || decoded.subject != claims.sub || decoded.workflow_ref != claims.workflow_ref() {
+                return Err(rejected("bind_identity arguments do not match the ID token"));
+            }
+            if bound.replace(signer).is_some() {
+                return Err(rejected("only one bind_identity per transaction is attested"));
+            }
+        } else if let Some(data) = instruction.data.strip_prefix(&args::WriteSignature::DISCRIMINATOR[..]) {
+            let decoded = args::WriteSignature::deserialize(&mut &data[..])
+                .map_err(|e| rejected(format!("write_signature arguments do not decode: {}", e)))?;
+            let (signer, payer, pda) = (key(0)?, key(1)?, key(2)?);
+            if payer != *attester {
+                return Err(rejected("write_signature payer must be the attester key"));
+            }
+            if pda != signature_pda(program_id, &decoded.digest) {
+                return Err(rejected("signature account does not match the digest"));
+            }
+            if write.replace(ValidatedKeyless { signer, digest: decoded.digest }).is_some() {
+                return Err(rejected("only one write_signature per transaction is attested"));
+            }
+        } else {
+            return Err(rejected("only bind_identity and write_signature are attested"));
+        }
+    }
+    let bound = bound.ok_or_else(|| rejected("transaction does not contain bind_identity"))?;
+    let write = write.ok_or_else(|| rejected("transaction does not contain write_signature"))?;
+    if bound != write.signer {
+        return Err(rejected("bind_identity and write_signature must use the same signing key"));
+    }
+    if write.signer == *attester {
+        return Err(rejected("the attester key cannot be the signing key"));
+    }
+    relay::check_cosignatures(transaction).map_err(AttestError::Rejected)?;
+    Ok(write)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttesterInfo {
+    pub attester: String,
+    pub program_id: String,
+    pub issuer: String,
+    /// Audience ID tokens must be requested for
+    pub audience: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AttestRequest {
+    /// OIDC ID token of the signer
+    token: String,
+    /// Base64 bincode transaction, signed by the ephemeral key
+    transaction: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestReceipt {
+    pub signature: String,
+    pub signer: String,
+    pub digest: String,
+    pub issuer: String,
+    pub identity: String,
+}
+
+/// The attester service state
+pub struct Attester {
+    program_id: Pubkey,
+    key: SkelzSigner,
+    pool: RpcPool,
+    verifier: OidcVerifier,
+    policy: AttesterPolicy,
+}
+
+impl Attester {
+    pub fn new(cfg: &SkelzConfig, key: SkelzSigner, verifier: OidcVerifier, policy: AttesterPolicy) -> Result<Self> {
+        let pool = RpcPool::from_config(cfg)?;
+        Ok(Attester { program_id: cfg.program_id()?, key, pool, verifier, policy })
+    }
+
+    pub fn info(&self) -> AttesterInfo {
+        AttesterInfo {
+            attester: self.key.pubkey().to_string(),
+            program_id: self.program_id.to_string(),
+            issuer: self.verifier.issuer().to_string(),
+            audience: self.verifier.audience().to_string(),
+        }
+    }
+
+    /// Verify the token and transaction, co-sign and submit
+    pub fn attest(&mut self, token: &str, mut transaction: Transaction, now: i64) -> Result<AttestReceipt, AttestError> {
+        let claims = self.verifier.verify(token, now).map_err(|e| AttestError::Unauthorized(e.to_string()))?;
+        let identity = claims.identity();
+        if let Some(allowed) = &self.policy.identity_regexp {
+            if !allowed.is_match(&identity) {
+                return Err(AttestError::NotAllowed(identity));
+            }
+        }
+        let validated = validate_keyless_transaction(&transaction, &self.program_id, &self.key.pubkey(), &claims, &self.policy)?;
+
+        let blockhash = transaction.message.recent_blockhash;
+        transaction
+            .try_partial_sign(&[&self.key], blockhash)
+            .map_err(|e| AttestError::Submit(format!("co-sign: {}", e)))?;
+        // The transaction is fixed, so retrying the send cannot double-spend
+        let signature = self
+            .pool
+            .call("send attested transaction", |rpc| Ok(rpc.send_and_confirm_transaction(&transaction)?))
+            .map_err(|e| AttestError::Submit(e.to_string()))?;
+
+        info!(%signature, signer = %validated.signer, digest = %validated.digest, %identity, "attested identity binding");
+        Ok(AttestReceipt {
+            signature: signature.to_string(),
+            signer: validated.signer.to_string(),
+            digest: validated.digest,
+            issuer: claims.iss,
+            identity,
+        })
+    }
+
+    /// Serve requests one at a time until the listener fails
+    pub fn serve(&mut self, listener: TcpListener) -> Result<()> {
+        info!(addr = %listener.local_addr()?, attester = %self.key.pubkey(), issuer = self.verifier.issuer(), "attester listening");
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!(error = %e, "accept failed");
+                    continue;
+                }
+            };
+            if let Err(e) = self.handle_connection(&mut stream) {
+                warn!(error = %e, "request failed");
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_connection(&mut self, stream: &mut TcpStream) -> Result<()> {
+        http::handle_json(stream, |method, path, body| self.route(method, path, body))
+    }
+
+    fn route(&mut self, method: &str, path: &str, body: &[u8]) -> (u16, serde_json::Value) {
+        match (method, path) {
+            ("GET", "/v1/info") => (200, serde_json::to_value(self.info()).unwrap_or_default()),
+            ("POST", "/v1/attest") => {
+                let request = serde_json::from_slice::<AttestRequest>(body)
+                    .map_err(|e| rejected(format!("invalid request: {}", e)))
+                    .and_then(|request| {
+                        let transaction = relay::decode_transaction(&request.transaction).map_err(|e| rejected(e.to_string()))?;
+                        Ok((request.token, transaction))
+                    });
+                let result = request.and_then(|(token, tx)| self.attest(&token, tx, chrono::Utc::now().timestamp()));
+                match result {
+                    Ok(receipt) => (200, serde_json::to_value(receipt).unwrap_or_default()),
+                    Err(e) => {
+                        warn!(error = %e, "attester refused transaction");
+                        (e.status(), serde_json::json!({ "error": e.to_string() }))
+                    }
+                }
+            }
+            _ => (404, serde_json::json!({ "error": format!("no route for {} {}", method, path) })),
+        }
+    }
+}
+
+/// Client side of an attester, used by `skelz sign --keyless`
+pub struct AttesterClient {
+    url: String,
+    client: reqwest::blocking::Client,
+    pub info: AttesterInfo,
+    pub attester: Pubkey,
+}
+
+impl AttesterClient {
+    /// Fetch the attester's key, issuer and audience; the attester must serve `program_id`
+    pub fn connect(url: &str, program_id: &Pubkey) -> Result<Self> {
+        let url = url.trim_end_matches('/').to_string();
+        let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(90)).build()?;
+        let info: AttesterInfo = client
+            .get(format!("{}/v1/info", url))
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .with_context(|| format!("attester {}: fetch info", url))?;
+        if info.program_id != program_id.to_string() {
+            anyhow::bail!("attester {} serves program {}, not {}", url, info.program_id, program_id);
+        }
+        let attester = info
+            .attester
+            .parse()
+            .map_err(|e| anyhow!("attester {} returned an invalid key: {}", url, e))?;
+        Ok(AttesterClient { url, client, info, attester })
+    }
+
+    /// Post the ID token and a transaction signed by the ephemeral key
+    pub fn attest(&self, token: &str, transaction: &Transaction) -> Result<AttestReceipt> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(transaction)?);
+        let response = self
+            .client
+            .post(format!("{}/v1/attest", self.url))
+            .json(&AttestRequest { token: token.to_string(), transaction: encoded })
+            .send()
+            .with_context(|| format!("attester {}: submit", self.url))?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().with_context(|| format!("attester {}: read response", self.url))?;
+        if !status.is_success() {
+            let error = body["error"].as_str().unwrap_or("no details");
+            anyhow::bail!("attester {} refused the transaction ({}): {}", self.url, status, error);
+        }
+        let receipt: AttestReceipt = serde_json::from_value(body)?;
+        receipt
+            .signature
+            .parse::<TxSignature>()
+            .map_err(|e| anyhow!("attester returned an invalid signature: {}", e))?;
+        Ok(receipt)
+    }
+}
+
+/// ID token for `audience`: `SKELZ_OIDC_TOKEN`, else requested from GitHub Actions
+///
+/// GitHub only exposes the request URL to jobs with `permissions: id-token: write`.
+pub fn fetch_oidc_token(audience: &str) -> Result<String> {
+    if let Some(token) = std::env::var("SKELZ_OIDC_TOKEN").ok().filter(|t| !t.trim().is_empty()) {
+        return Ok(token.trim().to_string());
+    }
+    let (url, bearer) = match (std::env::var("ACTIONS_ID_TOKEN_REQUEST_URL"), std::env::var("ACTIONS_ID_TOKEN_REQUEST_TOKEN")) {
+        (Ok(url), Ok(bearer)) => (url, bearer),
+        _ => anyhow::bail!(
+            "no ID token: pass --oidc-token, set SKELZ_OIDC_TOKEN or run in GitHub Actions with `id-token: write`"
+        ),
+    };
+    #[derive(Deserialize)]
+    struct GithubToken {
+        value: String,
+    }
+    let response: GithubToken = reqwest::blocking::Client::new()
+        .get(&url)
+        .query(&[("audience", audience)])
+        .bearer_auth(bearer)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.json())
+        .context("request GitHub Actions ID token")?;
+    Ok(response.value)
+}
+
+/// Result of a keyless signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeylessOutcome {
+    /// Transaction that bound the identity and wrote the signature
+    pub signature: String,
+    /// The discarded ephemeral key
+    pub signer: Pubkey,
+    pub identity: String,
+}
+
+/// Record the signature of an image with an ephemeral key bound to the ID token's identity
+///
+/// Without `token`, one is obtained with [`fetch_oidc_token`].
+pub fn sign_keyless(
+    image_reference: &str,
+    cfg: &SkelzConfig,
+    attester_url: &str,
+    token: Option<&str>,
+    options: &SignOptions,
+) -> Result<KeylessOutcome> {
+    if options.fee_payer.is_some() || options.nonce.is_some() || options.relay_url.is_some() {
+        anyhow::bail!("keyless signing cannot be combined with a fee payer, durable nonce or relay");
+    }
+    let digest = extract_digest_from_reference(image_reference)?;
+    let attester = AttesterClient::connect(attester_url, &cfg.program_id()?)?;
+    let token = match token {
+        Some(token) => token.to_string(),
+        None => fetch_oidc_token(&attester.info.audience)?,
+    };
+    let claims = oidc::decode_unverified(&token)?;
+    if claims.iss != attester.info.issuer {
+        anyhow::bail!("ID token issuer {} is not the attester's ({})", claims.iss, attester.info.issuer);
+    }
+
+    let ephemeral = Rc::new(SkelzSigner::from(Keypair::new()));
+    let program = skelz_program_with_payer(cfg, ephemeral.clone())?;
+    if let Some(existing) = fetch_signature(&program, &digest)? {
+        return Err(SkelzError::AlreadySignedByOther { digest, signer: existing.signer.to_string() }.into());
+    }
+    info!(signer = %ephemeral.pubkey(), attester = %attester.attester, identity = %claims.identity(), "keyless signing");
+
+    let bind = program
+        .request()
+        .accounts(accounts::BindIdentity {
+            signer: ephemeral.pubkey(),
+            attester: attester.attester,
+            payer: attester.attester,
+            binding: identity_binding_pda(&program.id(), &ephemeral.pubkey()),
+            system_program: solana_sdk::system_program::ID,
+        })
+        .args(args::BindIdentity {
+            issuer: claims.iss.clone(),
+            subject: claims.sub.clone(),
+            workflow_ref: claims.workflow_ref().to_string(),
+        })
+        .instructions()?;
+    let write = write_signature_instructions(&program, &ephemeral.pubkey(), &attester.attester, &digest, options)?;
+    let instructions = [bind, write].concat();
+
+    let pool = program.pool();
+    let budget = pool.call("compute budget", |rpc| {
+        fees::compute_budget_instructions(
+            rpc,
+            &instructions,
+            &attester.attester,
+            options.priority_fee,
+            options.compute_unit_limit,
+        )
+    })?;
+    let instructions = [budget, instructions].concat();
+    let blockhash = pool.call("getLatestBlockhash", |rpc| Ok(rpc.get_latest_blockhash()?))?;
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&attester.attester));
+    transaction.try_partial_sign(&[ephemeral.as_ref()], blockhash)?;
+    let receipt = attester.attest(&token, &transaction)?;
+
+    info!(signature = %receipt.signature, identity = %receipt.identity, "keyless signature recorded");
+    Ok(KeylessOutcome { signature: receipt.signature, signer: ephemeral.pubkey(), identity: receipt.identity })
+}
+
+/// Identity requirements for keyless signatures (cosign's `--certificate-*` flags)
+#[derive(Debug, Clone)]
+pub struct IdentityPolicy {
+    /// Exact issuer the identity must come from
+    pub oidc_issuer: String,
+    /// Must match the identity (unanchored, like cosign's `--certificate-identity-regexp`)
+    pub identity_regexp: Regex,
+    /// Attesters whose bindings are trusted
+    pub attesters: Vec<Pubkey>,
+}
+
+/// Check a binding against the policy, returning the matched identity
+pub fn check_identity_binding(binding: &IdentityBinding, policy: &IdentityPolicy) -> Result<String> {
+    if policy.attesters.is_empty() {
+        anyhow::bail!("no trusted attester: pass --attester or set identity_attesters");
+    }
+    if !policy.attesters.contains(&binding.attester) {
+        anyhow::bail!("identity of {} was attested by untrusted key {}", binding.signer, binding.attester);
+    }
+    if binding.issuer != policy.oidc_issuer {
+        anyhow::bail!("identity issuer {} is not {}", binding.issuer, policy.oidc_issuer);
+    }
+    let identity = oidc::identity_for(&binding.issuer, &binding.subject, &binding.workflow_ref);
+    if !policy.identity_regexp.is_match(&identity) {
+        anyhow::bail!("identity {} does not match {}", identity, policy.identity_regexp);
+    }
+    Ok(identity)
+}
+
+/// Check that `binding` was recorded with a signature written at `signed_slot`
+///
+/// The attester only co-signs transactions holding both instructions, so the binding
+/// and the signature land in the same slot. A signature from another slot was written
+/// without the attester, e.g. by an ephemeral key kept after attestation.
+pub fn check_binding_covers(binding: &IdentityBinding, signed_slot: u64) -> Result<()> {
+    if binding.slot != signed_slot {
+        anyhow::bail!(
+            "signature by {} was written at slot {}, not with its identity binding at slot {}",
+            binding.signer,
+            signed_slot,
+            binding.slot
+        );
+    }
+    Ok(())
+}
+
+/// Verify a keyless signature of `digest` against an identity policy
+///
+/// Returns the binding of the signing key.
+pub fn verify_keyless_signature(
+    program: &SkelzProgram,
+    digest: &str,
+    policy: &IdentityPolicy,
+    options: &VerifyOptions,
+) -> Result<IdentityBinding> {
+    let signature = fetch_signature(program, digest)?
+        .ok_or_else(|| anyhow!("no signature recorded for {}", digest))?;
+    let binding = fetch_identity_binding(program, &signature.signer)?
+        .ok_or_else(|| anyhow!("signing key {} has no identity binding (not a keyless signature)", signature.signer))?;
+    let identity = check_identity_binding(&binding, policy)?;
+    check_binding_covers(&binding, signature.slot)?;
+
+    crate::verify_signature_with_signers(program, digest, &[signature.signer], options)?;
+    println!("   - Identity: {}", identity);
+    println!("   - Issuer: {}", binding.issuer);
+    if !binding.workflow_ref.is_empty() {
+        println!("   - Workflow: {}", binding.workflow_ref);
+    }
+    println!("   - Attester: {}", binding.attester);
+    Ok(binding)
+}
+
+/// Complete keyless verification of an image
+pub fn verify_image_keyless(
+    image_reference: &str,
+    config: &SkelzConfig,
+    policy: &IdentityPolicy,
+    options: &VerifyOptions,
+) -> Result<()> {
+    info!("Starting keyless image signature verification for: {}", image_reference);
+    if !image_reference.contains("@sha256:") {
+        anyhow::bail!("Image reference must be canonical with digest (e.g., ghcr.io/username/repo@sha256:abc123...)");
+    }
+    let digest = extract_digest_from_reference(image_reference)?;
+    let program = crate::load_skelz_program(config)?;
+    verify_keyless_signature(&program, &digest, policy, options)?;
+    println!("✅ Image is signed by an identity matching {}", policy.identity_regexp);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oidc::tests::{claims, TestIssuer};
+    use crate::relay::tests::{signed, write_instruction};
+    use anchor_lang::InstructionData;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+
+    fn bind_instruction(signer: &Pubkey, attester: &Pubkey, claims: &OidcClaims) -> Instruction {
+        Instruction {
+            program_id: crate::skelz::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*signer, true),
+                AccountMeta::new_readonly(*attester, true),
+                AccountMeta::new(*attester, true),
+                AccountMeta::new(identity_binding_pda(&crate::skelz::ID, signer), false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            data: args::BindIdentity {
+                issuer: claims.iss.clone(),
+                subject: claims.sub.clone(),
+                workflow_ref: claims.workflow_ref().to_string(),
+            }
+            .data(),
+        }
+    }
+
+    fn binding(attester: Pubkey, claims: &OidcClaims) -> IdentityBinding {
+        IdentityBinding {
+            signer: Pubkey::new_unique(),
+            attester,
+            issuer: claims.iss.clone(),
+            subject: claims.sub.clone(),
+            workflow_ref: claims.workflow_ref().to_string(),
+            slot: 10,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn validates_keyless_transactions() {
+        let attester = Keypair::new().pubkey();
+        let ephemeral = Keypair::new();
+        let policy = AttesterPolicy::default();
+        let token = claims(oidc::GITHUB_ACTIONS_ISSUER, "skelz", 0);
+        let bind = bind_instruction(&ephemeral.pubkey(), &attester, &token);
+        let write = write_instruction(&ephemeral.pubkey(), &attester, "sha256:abc");
+        let budget = ComputeBudgetInstruction::set_compute_unit_limit(40_000);
+
+        let ok = signed(&[budget, bind.clone(), write.clone()], &attester, &ephemeral);
+        assert_eq!(
+            validate_keyless_transaction(&ok, &crate::skelz::ID, &attester, &token, &policy).unwrap(),
+            ValidatedKeyless { signer: ephemeral.pubkey(), digest: "sha256:abc".to_string() }
+        );
+
+        // Claims recorded on-chain must be the verified ones
+        let other = OidcClaims { sub: "repo:mallory/app:ref:refs/heads/main".to_string(), ..token.clone() };
+        let lying = signed(&[bind_instruction(&ephemeral.pubkey(), &attester, &other), write.clone()], &attester, &ephemeral);
+        assert!(validate_keyless_transaction(&lying, &crate::skelz::ID, &attester, &token, &policy).is_err());
+
+        // The binding must cover the key that signs the image
+        let second = Keypair::new();
+        let mut split = Transaction::new_with_payer(
+            &[bind.clone(), write_instruction(&second.pubkey(), &attester, "sha256:abc")],
+            Some(&attester),
+        );
+        split.partial_sign(&[&ephemeral, &second], Hash::new_unique());
+        assert!(validate_keyless_transaction(&split, &crate::skelz::ID, &attester, &token, &policy).is_err());
+
+        let missing_write = signed(std::slice::from_ref(&bind), &attester, &ephemeral);
+        assert!(validate_keyless_transaction(&missing_write, &crate::skelz::ID, &attester, &token, &policy).is_err());
+
+        let transfer = solana_sdk::system_instruction::transfer(&attester, &ephemeral.pubkey(), 1_000_000);
+        let drain = signed(&[bind.clone(), write.clone(), transfer], &attester, &ephemeral);
+        assert!(validate_keyless_transaction(&drain, &crate::skelz::ID, &attester, &token, &policy).is_err());
+
+        let unsigned = Transaction::new_with_payer(&[bind, write], Some(&attester));
+        assert!(validate_keyless_transaction(&unsigned, &crate::skelz::ID, &attester, &token, &policy).is_err());
+
+        let mut out_of_range = ok.clone();
+        out_of_range.message.instructions[2].program_id_index = 200;
+        assert!(matches!(
+            validate_keyless_transaction(&out_of_range, &crate::skelz::ID, &attester, &token, &policy),
+            Err(AttestError::Rejected(_))
+        ));
+        let mut bad_account = ok;
+        bad_account.message.instructions[1].accounts[3] = 200;
+        assert!(matches!(
+            validate_keyless_transaction(&bad_account, &crate::skelz::ID, &attester, &token, &policy),
+            Err(AttestError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_signatures_written_after_the_binding() {
+        let token = claims(oidc::GITHUB_ACTIONS_ISSUER, "skelz", 0);
+        let binding = binding(Pubkey::new_unique(), &token);
+        assert!(check_binding_covers(&binding, binding.slot).is_ok());
+        // The ephemeral key signed again later, without the attester
+        let err = check_binding_covers(&binding, binding.slot + 1).unwrap_err();
+        assert!(err.to_string().contains("not with its identity binding"), "{}", err);
+        assert!(check_binding_covers(&binding, binding.slot - 1).is_err());
+    }
+
+    #[test]
+    fn matches_identity_policies() {
+        let attester = Pubkey::new_unique();
+        let token = claims(oidc::GITHUB_ACTIONS_ISSUER, "skelz", 0);
+        let binding = binding(attester, &token);
+        let policy = IdentityPolicy {
+            oidc_issuer: oidc::GITHUB_ACTIONS_ISSUER.to_string(),
+            identity_regexp: Regex::new(r"^https://github\.com/octo/app/\.github/workflows/.*@refs/heads/main$").unwrap(),
+            attesters: vec![attester],
+        };
+        assert_eq!(
+            check_identity_binding(&binding, &policy).unwrap(),
+            "https://github.com/octo/app/.github/workflows/release.yml@refs/heads/main"
+        );
+        // Unanchored like cosign
+        let loose = IdentityPolicy { identity_regexp: Regex::new("octo/app").unwrap(), ..policy.clone() };
+        assert!(check_identity_binding(&binding, &loose).is_ok());
+
+        let other_repo = IdentityPolicy { identity_regexp: Regex::new("^https://github.com/octo/lib/").unwrap(), ..policy.clone() };
+        assert!(check_identity_binding(&binding, &other_repo).is_err());
+        let other_issuer = IdentityPolicy { oidc_issuer: "https://gitlab.com".to_string(), ..policy.clone() };
+        assert!(check_identity_binding(&binding, &other_issuer).is_err());
+        let untrusted = IdentityPolicy { attesters: vec![Pubkey::new_unique()], ..policy.clone() };
+        assert!(check_identity_binding(&binding, &untrusted).is_err());
+        let none = IdentityPolicy { attesters: Vec::new(), ..policy };
+        assert!(check_identity_binding(&binding, &none).is_err());
+    }
+
+    #[test]
+    fn attester_rejects_bad_tokens_over_http() {
+        let issuer = TestIssuer::new();
+        let issuer_url = issuer.serve(2);
+        let key = Keypair::new();
+        let attester_key = key.pubkey();
+        let cfg = SkelzConfig { rpc_url: "http://127.0.0.1:1".to_string(), ..SkelzConfig::default() };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let verifier_issuer = issuer_url.clone();
+        std::thread::spawn(move || {
+            let verifier = OidcVerifier::new(&verifier_issuer, "skelz").unwrap();
+            let policy = AttesterPolicy {
+                identity_regexp: Some(Regex::new("^repo:octo/").unwrap()),
+                ..AttesterPolicy::default()
+            };
+            let mut attester = Attester::new(&cfg, key.into(), verifier, policy).unwrap();
+            for stream in listener.incoming().take(3) {
+                attester.handle_connection(&mut stream.unwrap()).unwrap();
+            }
+        });
+
+        let client = AttesterClient::connect(&url, &crate::skelz::ID).unwrap();
+        assert_eq!(client.attester, attester_key);
+        assert_eq!(client.info.issuer, issuer_url);
+
+        let now = chrono::Utc::now().timestamp();
+        let ephemeral = Keypair::new();
+        let transaction = signed(
+            &[write_instruction(&ephemeral.pubkey(), &attester_key, "sha256:abc")],
+            &attester_key,
+            &ephemeral,
+        );
+
+        let wrong_audience = issuer.token("ES256", &claims(&issuer_url, "sigstore", now));
+        let err = client.attest(&wrong_audience, &transaction).unwrap_err().to_string();
+        assert!(err.contains("401") && err.contains("audience"), "{}", err);
+
+        // Valid token, but the transaction binds nothing: refused before any RPC call
+        let token = issuer.token("RS256", &claims(&issuer_url, "skelz", now));
+        let err = client.attest(&token, &transaction).unwrap_err().to_string();
+        assert!(err.contains("400") && err.contains("bind_identity"), "{}", err);
+    }
+}
@@ -8,27 +8,24 @@
 //!
 //! - **Image Signing**: Sign Docker images with Solana blockchain signatures
 //! - **Signature Verification**: Verify image signatures against Solana blockchain
-//! - **OCI Registry Integration**: Upload and retrieve signature proofs from GHCR
+//! - **OCI Registry Integration**: Upload and retrieve signature proofs from any OCI registry
 //! - **Configuration Management**: Flexible configuration with environment variable overrides
 //! - **Multi-cluster Support**: Support for devnet, testnet, and mainnet-beta
 //!
 //! ## Quick Start
 //!
 //! ```rust,no_run
-//! use skelz::{SkelzConfig, sign_image_with_oci, verify_image_signature};
+//! use skelz::{SkelzConfig, SignOptions, resolve_image_credentials, sign_image_with_oci};
 //! use anyhow::Result;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<()> {
 //!     let config = SkelzConfig::default();
+//!     let image = "ghcr.io/username/repo@sha256:abc123...";
 //!     
-//!     // Sign an image
-//!     let signature = sign_image_with_oci(
-//!         "ghcr.io/username/repo@sha256:abc123...",
-//!         &config,
-//!         "github-username",
-//!         "github-token"
-//!     )?;
+//!     // Sign an image with the credentials configured for its registry
+//!     let credential = resolve_image_credentials(&config, image)?;
+//!     let signature = sign_image_with_oci(image, &config, credential.as_ref(), &SignOptions::default())?;
 //!     
 //!     println!("Image signed: {}", signature);
 //!     Ok(())
@@ -53,22 +50,22 @@ use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::process::Command;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::{read_keypair_file, Signer};
+use solana_sdk::signature::Signer;
+pub use signer::SkelzSigner;
+use solana_sdk::transaction::Transaction;
 use thiserror::Error;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 use anchor_client::{
     solana_sdk::{
         commitment_config::CommitmentConfig,
         system_program,
         pubkey::Pubkey as AnchorPubkey,
-        signature::Keypair,
     },
     Client, Cluster,
 };
@@ -76,12 +73,33 @@ use anchor_lang::prelude::*;
 use std::rc::Rc;
 use sha2::{Sha256, Digest};
 
+pub mod cache;
+pub mod credentials;
+pub mod doctor;
+pub mod events;
+pub mod fees;
+mod http;
+pub mod index;
+pub mod multisig;
+pub mod keyless;
+pub mod nonce;
+pub mod oidc;
+pub mod offline;
+pub mod policy;
+pub mod registry;
+pub mod relay;
+pub mod rpc;
+pub mod signer;
+pub use policy::{Decision, Policy};
+
 // Declare the program using the IDL (exactly like in the test)
 declare_program!(skelz);
-use skelz::{accounts::Signature, client::accounts, client::args};
+use self::credentials::Credential;
+use self::skelz::{accounts::Publisher, accounts::Rotation, accounts::Signature, client::accounts, client::args};
+
 
-// Define the program ID
-const SKELZ_PROGRAM_ID: &str = "4uw8DwTRdUMwGmbNrK5GZ5kgdVtco4aUaTGDnEUBrYKt";
+// Upper bound on rotation hops followed during verification
+pub(crate) const MAX_ROTATION_DEPTH: usize = 16;
 
 
 /// Errors that can occur during Skelz operations.
@@ -96,6 +114,15 @@ pub enum SkelzError {
     /// Unknown configuration key provided.
     #[error("unknown config key: {0}")]
     UnknownConfigKey(String),
+    /// No `[profiles.<name>]` section with this name.
+    #[error("unknown profile: {0}")]
+    UnknownProfile(String),
+    /// A profile with this name already exists.
+    #[error("profile exists: {0}")]
+    ProfileExists(String),
+    /// The digest already has an on-chain signature from a different key.
+    #[error("{digest} is already signed by {signer}; a digest can only be signed once")]
+    AlreadySignedByOther { digest: String, signer: String },
 }
 
 /// Configuration structure for Skelz operations.
@@ -112,14 +139,103 @@ pub struct SkelzConfig {
     pub keypair_path: PathBuf,
     /// Solana commitment level (processed, confirmed, finalized).
     pub commitment: String,
+    /// Address of the skelz program instance (default: the ID declared by the IDL).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
     /// GitHub Container Registry username (optional).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ghcr_user: Option<String>,
     /// GitHub Container Registry token (optional).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ghcr_token: Option<String>,
+    /// Additional RPC endpoints tried in order when `rpc_url` is unhealthy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rpc_fallback_urls: Vec<String>,
+    /// Per-request RPC timeout, e.g. `30s` (default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_timeout: Option<String>,
+    /// Retries per endpoint on transient RPC errors (default 3).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_max_retries: Option<u32>,
+    /// Priority fee for signing transactions: micro-lamports per compute unit or `auto`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_fee: Option<String>,
+    /// Compute-unit limit for signing transactions (estimated by simulation when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_unit_limit: Option<u32>,
+    /// Keypair path or signer URI paying signing fees and rent (defaults to the signer).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<String>,
+    /// Relay URL paying for and submitting signing transactions (see `skelz relay`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relay_url: Option<String>,
+    /// Attester (`skelz attester`) used by `skelz sign --keyless`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attester_url: Option<String>,
+    /// Attester keys whose identity bindings `skelz verify --identity-regexp` trusts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identity_attesters: Vec<String>,
+    /// Durable nonce account signing transactions are built on (base58).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_account: Option<String>,
+    /// Authority of `nonce_account` (defaults to the signing key).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_authority: Option<String>,
+    /// Verification cache: maximum in-memory entries (default 1024).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_capacity: Option<usize>,
+    /// Verification cache: lifetime of successful results, e.g. `10m` (default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_positive_ttl: Option<String>,
+    /// Verification cache: lifetime of failed results, e.g. `30s` (default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_negative_ttl: Option<String>,
+    /// Verification cache: SQLite store shared between processes (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_path: Option<PathBuf>,
+    /// Profile applied when neither `--profile` nor `SKELZ_PROFILE` selects one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Named profiles (`[profiles.<name>]`), each overriding some of the keys above.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, toml::Table>,
+}
+
+
+/// Options controlling how an image is signed
+#[derive(Debug, Clone, Default)]
+pub struct SignOptions {
+    /// Unix timestamp after which the signature is considered expired
+    pub not_after: Option<i64>,
+    /// Priority fee for the signing transaction (none by default)
+    pub priority_fee: Option<fees::PriorityFee>,
+    /// Compute-unit limit; estimated by simulation when unset
+    pub compute_unit_limit: Option<u32>,
+    /// Build the transaction on a durable nonce instead of a recent blockhash
+    pub nonce: Option<nonce::DurableNonce>,
+    /// Account paying fees and rent instead of the signer
+    pub fee_payer: Option<Rc<SkelzSigner>>,
+    /// Relay (`skelz relay`) that pays and submits instead
+    pub relay_url: Option<String>,
 }
 
+/// Options controlling how a signature is verified
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// Accept signatures past their expiry (still reported as expired)
+    pub allow_expired: bool,
+}
+
+/// Expiry state of a signature at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryStatus {
+    /// The signature was recorded without `not_after`
+    NoExpiry,
+    /// The signature is within its validity window
+    Valid { not_after: i64 },
+    /// The signature is past its `not_after` timestamp
+    Expired { not_after: i64 },
+}
 
 /// Structure for the Solana proof payload to be uploaded as OCI artifact
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -155,6 +271,16 @@ pub struct OciDiscoverResponse {
 }
 
 
+impl SkelzConfig {
+    /// Program signatures are recorded by: `program_id`, else the ID declared by the IDL
+    pub fn program_id(&self) -> Result<Pubkey> {
+        match self.program_id.as_deref() {
+            Some(id) => Pubkey::from_str(id).with_context(|| format!("invalid program_id {}", id)),
+            None => Ok(skelz::ID),
+        }
+    }
+}
+
 impl Default for SkelzConfig {
     fn default() -> Self {
         Self {
@@ -162,8 +288,26 @@ impl Default for SkelzConfig {
             rpc_url: default_cluster_rpc_url("devnet"),
             keypair_path: default_solana_keypair_path(),
             commitment: "confirmed".to_string(),
+            program_id: None,
             ghcr_user: None,
             ghcr_token: None,
+            rpc_fallback_urls: Vec::new(),
+            rpc_timeout: None,
+            rpc_max_retries: None,
+            priority_fee: None,
+            compute_unit_limit: None,
+            fee_payer: None,
+            relay_url: None,
+            attester_url: None,
+            identity_attesters: Vec::new(),
+            nonce_account: None,
+            nonce_authority: None,
+            cache_capacity: None,
+            cache_positive_ttl: None,
+            cache_negative_ttl: None,
+            cache_path: None,
+            default_profile: None,
+            profiles: BTreeMap::new(),
         }
     }
 }
@@ -190,53 +334,315 @@ pub fn save_default_config(cfg: &SkelzConfig) -> Result<()> {
 }
 
 pub fn read_config_file() -> Result<SkelzConfig> {
+    let path = default_config_file_path();
+    // Keys missing from the file keep their defaults
+    overlay_config(&SkelzConfig::default(), read_config_table()?).with_context(|| format!("parse TOML at {}", path.display()))
+}
+
+/// [`read_config_file`], or the defaults when there is no config file yet
+pub fn read_config_file_or_default() -> Result<SkelzConfig> {
+    match read_config_file() {
+        Err(e) if matches!(e.downcast_ref::<SkelzError>(), Some(SkelzError::ConfigNotFound(_))) => Ok(SkelzConfig::default()),
+        result => result,
+    }
+}
+
+/// Raw contents of the config file
+fn read_config_table() -> Result<toml::Table> {
     let path = default_config_file_path();
     if !path.exists() {
         return Err(SkelzError::ConfigNotFound(path.display().to_string()).into());
     }
     let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
-    let cfg: SkelzConfig = toml::from_str(std::str::from_utf8(&bytes).context("utf8 config")?)
-        .with_context(|| format!("parse TOML at {}", path.display()))?;
-    Ok(cfg)
+    toml::from_str(std::str::from_utf8(&bytes).context("utf8 config")?)
+        .with_context(|| format!("parse TOML at {}", path.display()))
 }
 
-pub fn resolve_ghcr_credentials(cfg: &SkelzConfig) -> Result<(String, String)> {
-    let env_user = std::env::var("GHCR_USER").ok().filter(|v| !v.trim().is_empty());
-    let env_token = std::env::var("GHCR_TOKEN").ok().filter(|v| !v.trim().is_empty());
+/// Apply the keys of `table` on top of `base`
+fn overlay_config(base: &SkelzConfig, table: toml::Table) -> Result<SkelzConfig> {
+    let mut merged = toml::Table::try_from(base)?;
+    // A cluster without its own RPC URL gets the cluster's default endpoint
+    if let (Some(cluster), false) = (table.get("cluster").and_then(|c| c.as_str()), table.contains_key("rpc_url")) {
+        merged.insert("rpc_url".to_string(), default_cluster_rpc_url(cluster).into());
+    }
+    merged.extend(table);
+    Ok(toml::Value::Table(merged).try_into()?)
+}
 
-    let user = env_user.or_else(|| cfg.ghcr_user.clone());
-    let token = env_token.or_else(|| cfg.ghcr_token.clone());
+/// Profile to apply: `flag` (`--profile`), else `SKELZ_PROFILE`, else `default_profile`
+pub fn selected_profile(flag: Option<&str>, cfg: &SkelzConfig) -> Option<String> {
+    select_profile(flag, cfg, |var| std::env::var(var).ok())
+}
 
-    match (user, token) {
-        (Some(u), Some(t)) => Ok((u, t)),
-        _ => Err(anyhow!(
-            "GHCR credentials not found. Set GHCR_USER/GHCR_TOKEN or set ghcr_user/ghcr_token in config.toml"
+fn select_profile(flag: Option<&str>, cfg: &SkelzConfig, env: impl Fn(&str) -> Option<String>) -> Option<String> {
+    flag.map(str::to_string)
+        .or_else(|| env("SKELZ_PROFILE").filter(|p| !p.trim().is_empty()))
+        .or_else(|| cfg.default_profile.clone())
+}
+
+/// Layer profile `name` on top of the top-level settings of `cfg`
+pub fn apply_profile(cfg: &SkelzConfig, name: &str) -> Result<SkelzConfig> {
+    let profile = cfg.profiles.get(name).ok_or_else(|| SkelzError::UnknownProfile(name.to_string()))?;
+    overlay_config(cfg, profile.clone()).with_context(|| format!("profile {}", name))
+}
+
+/// Set `key` in profile `name`, creating the profile if needed (validated as in [`set_config_value`])
+pub fn set_profile_value(cfg: &mut SkelzConfig, name: &str, key: &str, value: &str) -> Result<()> {
+    set_config_value(&mut SkelzConfig::default(), key, value)?;
+    let value = typed_config_value(key, value)?;
+    cfg.profiles.entry(name.to_string()).or_default().insert(key.to_string(), value);
+    Ok(())
+}
+
+/// `value` as the TOML value of `key`, in the format [`set_config_value`] accepts
+fn typed_config_value(key: &str, value: &str) -> Result<toml::Value> {
+    let mut scratch = SkelzConfig::default();
+    assign_config_value(&mut scratch, key, value)?;
+    // Empty lists are not serialized
+    Ok(toml::Table::try_from(&scratch)?.remove(key).unwrap_or_else(|| toml::Value::Array(Vec::new())))
+}
+
+/// Copy profile `from` to `to`, replacing `to` only with `force`
+pub fn copy_profile(cfg: &mut SkelzConfig, from: &str, to: &str, force: bool) -> Result<()> {
+    let profile = cfg.profiles.get(from).cloned().ok_or_else(|| SkelzError::UnknownProfile(from.to_string()))?;
+    if cfg.profiles.contains_key(to) && !force {
+        return Err(SkelzError::ProfileExists(to.to_string()).into());
+    }
+    cfg.profiles.insert(to.to_string(), profile);
+    Ok(())
+}
+
+/// Registry whose credentials [`resolve_ghcr_credentials`] looks up
+pub const GHCR_REGISTRY: &str = "ghcr.io";
+
+/// Credentials for `registry` (a host such as `ghcr.io`), from the first source that has them:
+/// 1. for ghcr.io, `GHCR_USER`/`GHCR_TOKEN`, else `ghcr_user`/`ghcr_token` (flags, `SKELZ_*` or a legacy config file)
+/// 2. the skelz credential store (`skelz registry store`), if it exists
+/// 3. docker's `config.json`: credential helpers, then `auths`
+///
+/// None means anonymous access, which registries allow for pulling public images.
+pub fn resolve_registry_credentials(cfg: &SkelzConfig, registry: &str) -> Result<Option<Credential>> {
+    let host = credentials::registry_host(registry);
+    if host == GHCR_REGISTRY {
+        let env_user = std::env::var("GHCR_USER").ok().filter(|v| !v.trim().is_empty());
+        let env_token = std::env::var("GHCR_TOKEN").ok().filter(|v| !v.trim().is_empty());
+        let user = env_user.or_else(|| cfg.ghcr_user.clone());
+        let token = env_token.or_else(|| cfg.ghcr_token.clone());
+        if let (Some(username), Some(secret)) = (user, token) {
+            return Ok(Some(Credential { username, secret }));
+        }
+    }
+
+    if let Some(store) = credentials::CredentialStore::open_default()? {
+        if let Some(credential) = store.get(&host) {
+            return Ok(Some(credential.clone()));
+        }
+    }
+    credentials::docker_credentials(&credentials::docker_config_path(), &host)
+}
+
+/// Credentials for the registry hosting `image_reference` (see [`resolve_registry_credentials`])
+pub fn resolve_image_credentials(cfg: &SkelzConfig, image_reference: &str) -> Result<Option<Credential>> {
+    resolve_registry_credentials(cfg, &registry::ImageReference::parse(image_reference)?.registry)
+}
+
+/// GHCR credentials; an error when none are configured
+pub fn resolve_ghcr_credentials(cfg: &SkelzConfig) -> Result<(String, String)> {
+    match resolve_registry_credentials(cfg, GHCR_REGISTRY)? {
+        Some(credential) => Ok((credential.username, credential.secret)),
+        None => Err(anyhow!(
+            "GHCR credentials not found. Set GHCR_USER/GHCR_TOKEN, run `skelz registry store` or `docker login ghcr.io`"
         )),
     }
 }
 
-pub fn load_config_with_overrides(
-    rpc_url: Option<String>,
-    keypair_path: Option<PathBuf>,
-) -> Result<SkelzConfig> {
-    let mut cfg = read_config_file().unwrap_or_default();
-    if let Some(rpc) = rpc_url {
-        cfg.rpc_url = rpc;
-    } else if let Ok(env_rpc) = std::env::var("SOLANA_RPC_URL") {
-        if !env_rpc.trim().is_empty() {
-            cfg.rpc_url = env_rpc;
+/// Configuration keys, in file order
+pub const CONFIG_KEYS: &[&str] = &[
+    "cluster",
+    "rpc_url",
+    "keypair_path",
+    "commitment",
+    "program_id",
+    "ghcr_user",
+    "ghcr_token",
+    "rpc_fallback_urls",
+    "rpc_timeout",
+    "rpc_max_retries",
+    "priority_fee",
+    "compute_unit_limit",
+    "fee_payer",
+    "relay_url",
+    "attester_url",
+    "identity_attesters",
+    "nonce_account",
+    "nonce_authority",
+    "cache_capacity",
+    "cache_positive_ttl",
+    "cache_negative_ttl",
+    "cache_path",
+];
+
+// Variables read when no `SKELZ_<KEY>` is set, kept for compatibility
+const LEGACY_ENV_VARS: &[(&str, &str)] = &[("rpc_url", "SOLANA_RPC_URL"), ("keypair_path", "SOLANA_KEYPAIR")];
+
+/// Environment variable overriding `key` (`SKELZ_<KEY>`)
+pub fn config_env_var(key: &str) -> String {
+    format!("SKELZ_{}", key.to_ascii_uppercase())
+}
+
+/// Command-line flag overriding `key` (`--<key>` with dashes)
+pub fn config_flag(key: &str) -> String {
+    format!("--{}", key.replace('_', "-"))
+}
+
+/// Layer an effective configuration value comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// Top-level key of the config file
+    File,
+    /// `[profiles.<name>]` section
+    Profile(String),
+    /// Environment variable
+    Env(String),
+    /// Command-line flag
+    Flag(String),
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File => write!(f, "config file"),
+            ConfigSource::Profile(name) => write!(f, "profile {}", name),
+            ConfigSource::Env(var) => write!(f, "env {}", var),
+            ConfigSource::Flag(flag) => write!(f, "flag {}", flag),
         }
     }
-    if let Some(kp) = keypair_path.as_deref().map(expand_tilde) {
-        cfg.keypair_path = kp;
-    } else if let Ok(env_kp) = std::env::var("SOLANA_KEYPAIR") {
-        if !env_kp.trim().is_empty() {
-            cfg.keypair_path = expand_tilde(Path::new(&env_kp));
+}
+
+/// Settings given on the command line
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// Profile given by `--profile`
+    pub profile: Option<String>,
+    /// `(key, value)` pairs given by flags, see [`config_flag`]
+    pub values: Vec<(String, String)>,
+}
+
+/// Effective configuration and the layer each key comes from
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub config: SkelzConfig,
+    /// Applied profile, if any
+    pub profile: Option<String>,
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+impl ResolvedConfig {
+    fn apply(&mut self, entries: Vec<(String, toml::Value, ConfigSource)>) -> Result<()> {
+        let cluster_source = entries.iter().find(|(key, ..)| key == "cluster").map(|(.., source)| source.clone());
+        let mut table = toml::Table::new();
+        for (key, value, source) in entries {
+            check_config_entry(&key, &value, &source)?;
+            if let Some(slot) = self.sources.get_mut(&key) {
+                *slot = source;
+            }
+            table.insert(key, value);
+        }
+        // overlay_config derives rpc_url from a cluster set without it
+        if let (Some(source), false) = (cluster_source, table.contains_key("rpc_url")) {
+            self.sources.insert("rpc_url".to_string(), source);
         }
+        self.config = overlay_config(&self.config, table)?;
+        Ok(())
     }
-    Ok(cfg)
 }
 
+/// Check a key read from the config file or a profile
+fn check_config_entry(key: &str, value: &toml::Value, source: &ConfigSource) -> Result<()> {
+    match source {
+        ConfigSource::File if key == "default_profile" || key == "profiles" => return Ok(()),
+        ConfigSource::File | ConfigSource::Profile(_) => {}
+        // Environment variables and flags are checked when parsed
+        ConfigSource::Default | ConfigSource::Env(_) | ConfigSource::Flag(_) => return Ok(()),
+    }
+    if !CONFIG_KEYS.contains(&key) {
+        return Err(anyhow!(SkelzError::UnknownConfigKey(key.to_string())).context(format!("in {}", source)));
+    }
+    if key == "ghcr_token" {
+        warn!("ghcr_token is stored in clear text in {}; move it with `skelz registry store`", source);
+    }
+    let single = toml::Table::from_iter([(key.to_string(), value.clone())]);
+    let parsed = overlay_config(&SkelzConfig::default(), single).with_context(|| format!("{} (from {})", key, source))?;
+    // Run the checks `skelz config set` applies to the value
+    let text = get_config_value(&parsed, key)?;
+    if !text.is_empty() && key != "ghcr_token" {
+        assign_config_value(&mut SkelzConfig::default(), key, &text).with_context(|| format!("{} (from {})", key, source))?;
+    }
+    Ok(())
+}
+
+/// Effective configuration: defaults, then the config file and the selected profile
+/// (see [`selected_profile`]), then `SKELZ_<KEY>` environment variables, then flags
+pub fn load_config_with_overrides(overrides: &ConfigOverrides) -> Result<SkelzConfig> {
+    Ok(resolve_config(overrides)?.config)
+}
+
+/// Like [`load_config_with_overrides`], also reporting where each value comes from
+pub fn resolve_config(overrides: &ConfigOverrides) -> Result<ResolvedConfig> {
+    let file = match read_config_table() {
+        Ok(table) => table,
+        Err(e) if matches!(e.downcast_ref::<SkelzError>(), Some(SkelzError::ConfigNotFound(_))) => toml::Table::new(),
+        Err(e) => return Err(e),
+    };
+    resolve_layers(file, overrides, |var| std::env::var(var).ok())
+}
+
+fn resolve_layers(
+    file: toml::Table,
+    overrides: &ConfigOverrides,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<ResolvedConfig> {
+    let mut resolved = ResolvedConfig {
+        config: SkelzConfig::default(),
+        profile: None,
+        sources: CONFIG_KEYS.iter().map(|key| (key.to_string(), ConfigSource::Default)).collect(),
+    };
+    resolved.apply(file.into_iter().map(|(key, value)| (key, value, ConfigSource::File)).collect())?;
+
+    if let Some(name) = select_profile(overrides.profile.as_deref(), &resolved.config, &env) {
+        let profile = resolved.config.profiles.get(&name).cloned().ok_or_else(|| SkelzError::UnknownProfile(name.clone()))?;
+        let source = ConfigSource::Profile(name.clone());
+        resolved
+            .apply(profile.into_iter().map(|(key, value)| (key, value, source.clone())).collect())
+            .with_context(|| format!("profile {}", name))?;
+        resolved.profile = Some(name);
+    }
+
+    let mut from_env = Vec::new();
+    for key in CONFIG_KEYS {
+        let var = config_env_var(key);
+        let legacy = LEGACY_ENV_VARS.iter().find(|(k, _)| k == key).map(|(_, var)| *var);
+        let found = std::iter::once(var.as_str())
+            .chain(legacy)
+            .find_map(|var| env(var).filter(|v| !v.trim().is_empty()).map(|value| (var.to_string(), value)));
+        if let Some((var, value)) = found {
+            let typed = typed_config_value(key, &value).with_context(|| format!("${}", var))?;
+            from_env.push((key.to_string(), typed, ConfigSource::Env(var)));
+        }
+    }
+    resolved.apply(from_env)?;
+
+    let mut from_flags = Vec::new();
+    for (key, value) in &overrides.values {
+        let typed = typed_config_value(key, value).with_context(|| config_flag(key))?;
+        from_flags.push((key.clone(), typed, ConfigSource::Flag(config_flag(key))));
+    }
+    resolved.apply(from_flags)?;
+    Ok(resolved)
+}
 
 /// Extract digest from canonical image reference
 pub fn extract_digest_from_reference(image_reference: &str) -> Result<String> {
@@ -256,21 +662,201 @@ pub fn get_config_value(cfg: &SkelzConfig, key: &str) -> Result<String> {
         "rpc_url" => Ok(cfg.rpc_url.clone()),
         "keypair_path" => Ok(cfg.keypair_path.display().to_string()),
         "commitment" => Ok(cfg.commitment.clone()),
+        "program_id" => Ok(cfg.program_id.clone().unwrap_or_default()),
         "ghcr_user" => Ok(cfg.ghcr_user.clone().unwrap_or_default()),
         // Do not print secrets in clear text
         "ghcr_token" => Ok("<redacted>".to_string()),
+        "rpc_fallback_urls" => Ok(cfg.rpc_fallback_urls.join(",")),
+        "rpc_timeout" => Ok(cfg.rpc_timeout.clone().unwrap_or_default()),
+        "rpc_max_retries" => Ok(cfg.rpc_max_retries.map(|r| r.to_string()).unwrap_or_default()),
+        "priority_fee" => Ok(cfg.priority_fee.clone().unwrap_or_default()),
+        "compute_unit_limit" => Ok(cfg.compute_unit_limit.map(|l| l.to_string()).unwrap_or_default()),
+        "fee_payer" => Ok(cfg.fee_payer.clone().unwrap_or_default()),
+        "relay_url" => Ok(cfg.relay_url.clone().unwrap_or_default()),
+        "attester_url" => Ok(cfg.attester_url.clone().unwrap_or_default()),
+        "identity_attesters" => Ok(cfg.identity_attesters.join(",")),
+        "nonce_account" => Ok(cfg.nonce_account.clone().unwrap_or_default()),
+        "nonce_authority" => Ok(cfg.nonce_authority.clone().unwrap_or_default()),
+        "cache_capacity" => Ok(cfg.cache_capacity.map(|c| c.to_string()).unwrap_or_default()),
+        "cache_positive_ttl" => Ok(cfg.cache_positive_ttl.clone().unwrap_or_default()),
+        "cache_negative_ttl" => Ok(cfg.cache_negative_ttl.clone().unwrap_or_default()),
+        "cache_path" => Ok(cfg.cache_path.as_ref().map(|p| p.display().to_string()).unwrap_or_default()),
         _ => Err(SkelzError::UnknownConfigKey(key.to_string()).into()),
     }
 }
 
+/// Set `key` to `value`; values are validated, and a keypair file must exist with safe permissions.
+/// `ghcr_token` is refused: tokens belong in the credential store (see [`credentials`])
 pub fn set_config_value(cfg: &mut SkelzConfig, key: &str, value: &str) -> Result<()> {
+    if key == "ghcr_token" {
+        anyhow::bail!("ghcr_token is not written to the config file; use `skelz registry store` (encrypted) or `docker login`");
+    }
+    if key == "keypair_path" {
+        if let signer::SignerSource::File(path) = signer::SignerSource::parse(value)? {
+            check_keypair_file(&path)?;
+        }
+    }
+    assign_config_value(cfg, key, value)
+}
+
+/// [`set_config_value`] for `skelz config init`: the keypair is often generated after the
+/// config, so a missing or shared keypair file is reported as a warning instead
+pub fn init_config_value(cfg: &mut SkelzConfig, key: &str, value: &str) -> Result<()> {
+    if key == "keypair_path" {
+        if let signer::SignerSource::File(path) = signer::SignerSource::parse(value)? {
+            if let Err(e) = check_keypair_file(&path) {
+                warn!("{:#}", e);
+            }
+        }
+        return assign_config_value(cfg, key, value);
+    }
+    set_config_value(cfg, key, value)
+}
+
+/// Cluster names accepted by `cluster`
+pub const CLUSTERS: &[&str] = &["devnet", "testnet", "mainnet-beta", "mainnet", "localnet", "local"];
+
+/// Commitment levels accepted by `commitment`
+pub const COMMITMENTS: &[&str] = &["processed", "confirmed", "finalized"];
+
+fn check_url(key: &str, value: &str) -> Result<()> {
+    let url = reqwest::Url::parse(value).with_context(|| format!("{}: {} is not a URL", key, value))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        anyhow::bail!("{}: {} is not an http(s) URL", key, value);
+    }
+    Ok(())
+}
+
+fn check_duration(key: &str, value: &str) -> Result<()> {
+    parse_validity_duration(value).with_context(|| format!("{}: {} is not a duration (e.g., 30s, 10m)", key, value))?;
+    Ok(())
+}
+
+/// Check that a keypair file is readable and not accessible to other users
+pub fn check_keypair_file(path: &Path) -> Result<()> {
+    let file = fs::File::open(path).with_context(|| format!("keypair file {} is not readable", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = file.metadata()?.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            anyhow::bail!(
+                "keypair file {} is accessible to other users (mode {:o}); run chmod 600 {}",
+                path.display(),
+                mode,
+                path.display()
+            );
+        }
+    }
+    #[cfg(not(unix))]
+    drop(file);
+    Ok(())
+}
+
+/// Set `key` after checking the format of `value`
+fn assign_config_value(cfg: &mut SkelzConfig, key: &str, value: &str) -> Result<()> {
     match key {
-        "cluster" => cfg.cluster = value.to_string(),
-        "rpc_url" => cfg.rpc_url = value.to_string(),
-        "keypair_path" => cfg.keypair_path = expand_tilde(Path::new(value)),
-        "commitment" => cfg.commitment = value.to_string(),
+        "cluster" => {
+            if !CLUSTERS.contains(&value) {
+                anyhow::bail!("cluster must be one of devnet, testnet, mainnet-beta, localnet (got {})", value);
+            }
+            cfg.cluster = value.to_string()
+        }
+        "rpc_url" => {
+            check_url(key, value)?;
+            cfg.rpc_url = value.to_string()
+        }
+        "keypair_path" => {
+            let source = signer::SignerSource::parse(value).context("keypair_path")?;
+            cfg.keypair_path = match source {
+                signer::SignerSource::File(path) => path,
+                _ => PathBuf::from(value),
+            }
+        }
+        "commitment" => {
+            if !COMMITMENTS.contains(&value) {
+                anyhow::bail!("commitment must be one of {} (got {})", COMMITMENTS.join(", "), value);
+            }
+            cfg.commitment = value.to_string()
+        }
+        "program_id" => {
+            Pubkey::from_str(value).context("program_id must be a base58 public key")?;
+            cfg.program_id = Some(value.to_string())
+        }
         "ghcr_user" => cfg.ghcr_user = Some(value.to_string()),
         "ghcr_token" => cfg.ghcr_token = Some(value.to_string()),
+        "rpc_fallback_urls" => {
+            let urls: Vec<String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect();
+            for url in &urls {
+                check_url(key, url)?;
+            }
+            cfg.rpc_fallback_urls = urls
+        }
+        "rpc_timeout" => {
+            check_duration(key, value)?;
+            cfg.rpc_timeout = Some(value.to_string())
+        }
+        "rpc_max_retries" => {
+            cfg.rpc_max_retries = Some(value.parse().context("rpc_max_retries must be a number")?)
+        }
+        "priority_fee" => {
+            value.parse::<fees::PriorityFee>().context("priority_fee")?;
+            cfg.priority_fee = Some(value.to_string())
+        }
+        "compute_unit_limit" => {
+            cfg.compute_unit_limit = Some(value.parse().context("compute_unit_limit must be a number")?)
+        }
+        "fee_payer" => {
+            if Pubkey::from_str(value).is_err() {
+                signer::SignerSource::parse(value).context("fee_payer")?;
+            }
+            cfg.fee_payer = Some(value.to_string())
+        }
+        "relay_url" => {
+            check_url(key, value)?;
+            cfg.relay_url = Some(value.to_string())
+        }
+        "attester_url" => {
+            check_url(key, value)?;
+            cfg.attester_url = Some(value.to_string())
+        }
+        "identity_attesters" => {
+            let keys: Vec<String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+                .collect();
+            for key in &keys {
+                Pubkey::from_str(key).with_context(|| format!("identity_attesters: {} is not a base58 public key", key))?;
+            }
+            cfg.identity_attesters = keys
+        }
+        "nonce_account" => {
+            Pubkey::from_str(value).context("nonce_account must be a base58 public key")?;
+            cfg.nonce_account = Some(value.to_string())
+        }
+        "nonce_authority" => {
+            Pubkey::from_str(value).context("nonce_authority must be a base58 public key")?;
+            cfg.nonce_authority = Some(value.to_string())
+        }
+        "cache_capacity" => {
+            cfg.cache_capacity = Some(value.parse().context("cache_capacity must be a number")?)
+        }
+        "cache_positive_ttl" => {
+            check_duration(key, value)?;
+            cfg.cache_positive_ttl = Some(value.to_string())
+        }
+        "cache_negative_ttl" => {
+            check_duration(key, value)?;
+            cfg.cache_negative_ttl = Some(value.to_string())
+        }
+        "cache_path" => cfg.cache_path = Some(expand_tilde(Path::new(value))),
         _ => return Err(SkelzError::UnknownConfigKey(key.to_string()).into()),
     }
     Ok(())
@@ -319,43 +905,257 @@ pub fn expand_tilde(path: &Path) -> PathBuf {
     PathBuf::from(path)
 }
 
+/// The skelz Anchor client and the RPC settings it was built from
+///
+/// Dereferences to the Anchor client; [`SkelzProgram::pool`] gives RPC access with
+/// the same failover and retries.
+pub struct SkelzProgram {
+    program: anchor_client::Program<Rc<SkelzSigner>>,
+    rpc: rpc::RpcSettings,
+}
+
+impl SkelzProgram {
+    /// Pool starting at the client's endpoint, then the other configured endpoints
+    pub fn pool(&self) -> rpc::RpcPool {
+        rpc::RpcPool::for_url(self.rpc.clone(), &self.program.rpc().url())
+    }
+}
+
+impl std::ops::Deref for SkelzProgram {
+    type Target = anchor_client::Program<Rc<SkelzSigner>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.program
+    }
+}
+
+/// Build an Anchor client for the skelz program on the first healthy RPC endpoint
+pub fn skelz_program_with_payer(cfg: &SkelzConfig, payer: Rc<SkelzSigner>) -> Result<SkelzProgram> {
+    let settings = rpc::RpcSettings::from_config(cfg)?;
+    let endpoint = rpc::RpcPool::new(settings.clone()).select_healthy()?;
+    info!(%endpoint, "using RPC endpoint");
+    let cluster = Cluster::Custom(endpoint.clone(), events::websocket_url(&endpoint));
+    let provider = Client::new_with_options(cluster, payer, CommitmentConfig::confirmed());
+    Ok(SkelzProgram { program: provider.program(cfg.program_id()?)?, rpc: settings })
+}
+
+/// Load the configured signer (`keypair_path` may be a path or a signer URI, see [`signer`])
+pub fn load_config_signer(cfg: &SkelzConfig) -> Result<SkelzSigner> {
+    SkelzSigner::load(&cfg.keypair_path.to_string_lossy())
+}
+
+/// Build an Anchor client for the skelz program using the configured signer
+pub fn load_skelz_program(cfg: &SkelzConfig) -> Result<SkelzProgram> {
+    skelz_program_with_payer(cfg, Rc::new(load_config_signer(cfg)?))
+}
+
+/// Parse a validity duration such as `90d`, `12h`, `30m`, `2w` or `3600s`
+pub fn parse_validity_duration(value: &str) -> Result<chrono::Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| anyhow!("missing unit in duration '{}' (use s, m, h, d or w)", value))?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .with_context(|| format!("invalid duration '{}'", value))?;
+    if amount <= 0 {
+        anyhow::bail!("duration must be positive: '{}'", value);
+    }
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(amount)),
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        "w" => Ok(chrono::Duration::weeks(amount)),
+        _ => Err(anyhow!("unknown duration unit '{}' in '{}' (use s, m, h, d or w)", unit, value)),
+    }
+}
+
+/// Classify a signature expiry relative to `now` (unix seconds)
+pub fn expiry_status(not_after: Option<i64>, now: i64) -> ExpiryStatus {
+    match not_after {
+        None => ExpiryStatus::NoExpiry,
+        Some(not_after) if now > not_after => ExpiryStatus::Expired { not_after },
+        Some(not_after) => ExpiryStatus::Valid { not_after },
+    }
+}
+
+/// Current unix time from the cluster clock, falling back to local time
+pub fn cluster_unix_timestamp(program: &SkelzProgram) -> i64 {
+    let rpc = program.rpc();
+    match rpc.get_slot().and_then(|slot| rpc.get_block_time(slot)) {
+        Ok(timestamp) => timestamp,
+        Err(e) => {
+            info!("cluster clock unavailable ({}), using local time", e);
+            chrono::Utc::now().timestamp()
+        }
+    }
+}
+
+/// Format a unix timestamp as RFC 3339
+pub fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Instructions recording `digest` with `signer` as the signing key, rent paid by `payer`
+pub(crate) fn write_signature_instructions(
+    program: &SkelzProgram,
+    signer: &Pubkey,
+    payer: &Pubkey,
+    digest: &str,
+    options: &SignOptions,
+) -> Result<Vec<solana_sdk::instruction::Instruction>> {
+    Ok(program
+        .request()
+        .accounts(accounts::WriteSignature {
+            signer: *signer,
+            payer: *payer,
+            signature: signature_pda(&program.id(), digest),
+            system_program: system_program::ID,
+        })
+        .args(args::WriteSignature {
+            digest: digest.to_string(),
+            not_after: options.not_after,
+        })
+        .instructions()?)
+}
+
+/// Result of recording a signature on-chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignOutcome {
+    /// Transaction that wrote the signature account
+    pub signature: String,
+    /// Expiry recorded on-chain
+    pub not_after: Option<i64>,
+    /// The digest was already signed by this key and no transaction was sent
+    pub reused: bool,
+}
+
+/// Whether the logs show the skelz program itself recording `digest` for `signer`
+fn logs_write_signature(program_id: &Pubkey, logs: &[String], digest: &str, signer: &Pubkey) -> bool {
+    events::decode_program_logs(program_id, logs).iter().any(|event| {
+        matches!(event, events::SkelzEvent::SignatureWritten { digest: d, signer: s, .. }
+            if d == digest && *s == signer.to_string())
+    })
+}
+
+/// Find the transaction that recorded `digest` for `signer`
+///
+/// Anyone can send a transaction touching the signature account, so only one whose
+/// logs carry the program's `SignatureWritten` event for the digest is accepted.
+fn find_signing_transaction(program: &SkelzProgram, digest: &str, signer: &Pubkey) -> Result<String> {
+    let pda = signature_pda(&program.id(), digest);
+    let pool = program.pool();
+    // Pages come newest first; walk back until the history is exhausted
+    let mut candidates = Vec::new();
+    let mut before = None;
+    loop {
+        let page = pool.call("getSignaturesForAddress", |rpc| {
+            rpc.get_signatures_for_address_with_config(
+                &pda,
+                solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(index::SIGNATURE_PAGE_SIZE),
+                    commitment: None,
+                },
+            )
+            .with_context(|| format!("getSignaturesForAddress {}", pda))
+        })?;
+        candidates.extend(page.iter().filter(|entry| entry.err.is_none()).map(|entry| entry.signature.clone()));
+        if page.len() < index::SIGNATURE_PAGE_SIZE {
+            break;
+        }
+        before = page.last().map(|entry| solana_sdk::signature::Signature::from_str(&entry.signature)).transpose()?;
+    }
+    // The account is created once, so the signing transaction is among the oldest
+    for candidate in candidates.iter().rev() {
+        let tx = solana_sdk::signature::Signature::from_str(candidate)?;
+        let (logs, _) = pool.call("getTransaction", |rpc| events::fetch_transaction_logs(rpc, &tx))?;
+        if logs_write_signature(&program.id(), &logs, digest, signer) {
+            return Ok(candidate.clone());
+        }
+    }
+    Err(anyhow!("no transaction recording {} by {} found for signature account {}", digest, signer, pda))
+}
+
 /// Sign a Docker image using the Anchor program
-pub fn sign_docker_image_with_anchor(image_reference: &str, cfg: &SkelzConfig) -> Result<String> {
+pub fn sign_docker_image_with_anchor(
+    image_reference: &str,
+    cfg: &SkelzConfig,
+    options: &SignOptions,
+) -> Result<String> {
+    Ok(write_or_reuse_signature(image_reference, cfg, options)?.signature)
+}
+
+/// Record the signature of an image, or reuse it if this key already signed the digest
+///
+/// Fails with [`SkelzError::AlreadySignedByOther`] when another key signed the digest.
+pub fn write_or_reuse_signature(
+    image_reference: &str,
+    cfg: &SkelzConfig,
+    options: &SignOptions,
+) -> Result<SignOutcome> {
     info!("Signing image with Anchor program: {}", image_reference);
     
     // Extract the image digest from the canonical reference
     let digest = extract_digest_from_reference(image_reference)?;
     info!(%digest, "calculated image digest");
     
-    // Use the hardcoded program ID
-    let program_id = AnchorPubkey::from_str(SKELZ_PROGRAM_ID)
-        .context("Invalid program ID format")?;
+    let program_id = cfg.program_id()?;
     
     info!("Using program ID: {}", program_id);
     
-    // Load the keypair
-    let payer = read_keypair_file(&cfg.keypair_path)
-        .map_err(|e| anyhow!("read keypair at {}: {}", cfg.keypair_path.display(), e))?;
+    // Load the signer; the fee payer defaults to it
+    let signer = Rc::new(load_config_signer(cfg)?);
+    let payer = options.fee_payer.clone().unwrap_or_else(|| signer.clone());
     
     // Create the Anchor client
-    let cluster = match cfg.cluster.as_str() {
-        "mainnet" | "mainnet-beta" => Cluster::Mainnet,
-        "testnet" => Cluster::Testnet,
-        "localnet" | "local" => Cluster::Localnet,
-        _ => Cluster::Devnet,
-    };
-    
-    info!("Using cluster: {:?}", cluster);
-    info!("RPC URL: {}", cfg.rpc_url);
+    info!("Using cluster: {}", cfg.cluster);
+    info!("Signer: {}", signer.pubkey());
     info!("Payer: {}", payer.pubkey());
     
-    let provider = Client::new_with_options(
-        cluster,
-        Rc::new(payer),
-        CommitmentConfig::confirmed(),
-    );
+    let program = skelz_program_with_payer(cfg, payer.clone())?;
+    
+    // The PDA is created with `init`: check for an existing signature before sending
+    if let Some(existing) = fetch_signature(&program, &digest)? {
+        if existing.signer != signer.pubkey() {
+            return Err(SkelzError::AlreadySignedByOther {
+                digest,
+                signer: existing.signer.to_string(),
+            }
+            .into());
+        }
+        if let Some(revoked_at) = existing.revoked_at {
+            anyhow::bail!(
+                "{} was signed by this key but revoked at {}; it cannot be signed again",
+                digest,
+                format_timestamp(revoked_at)
+            );
+        }
+        if existing.not_after != options.not_after {
+            info!("keeping the expiry recorded on-chain; --valid-for is ignored for an existing signature");
+        }
+        let signature = find_signing_transaction(&program, &digest, &existing.signer)?;
+        info!(%signature, %digest, "digest already signed by this key, skipping transaction");
+        println!("Digest already signed by this key (transaction {}), reattaching proof", signature);
+        return Ok(SignOutcome { signature, not_after: existing.not_after, reused: true });
+    }
     
-    let program = provider.program(program_id)?;
+    // With a relay, its key pays and co-signs; we only sign as the identity
+    let relay = match options.relay_url.as_deref() {
+        Some(url) => {
+            if options.fee_payer.is_some() || options.nonce.is_some() {
+                anyhow::bail!("a relay cannot be combined with a fee payer or a durable nonce");
+            }
+            Some(relay::RelayClient::connect(url, &cfg.program_id()?)?)
+        }
+        None => None,
+    };
+    let payer_key = relay.as_ref().map(|r| r.fee_payer).unwrap_or_else(|| payer.pubkey());
     
     // Derive the PDA for this signature
     // Hash the digest to create a shorter seed (32 bytes max)
@@ -374,180 +1174,294 @@ pub fn sign_docker_image_with_anchor(image_reference: &str, cfg: &SkelzConfig) -
     
     // Use Anchor's request builder exactly like in the test
     info!("Sending transaction with accounts:");
-    info!("  signer: {}", program.payer());
+    info!("  signer: {}", signer.pubkey());
+    info!("  payer: {}", payer_key);
     info!("  pda: {}", signature_pda);
     info!("  system_program: {}", system_program::ID);
     info!("  digest: {}", digest);
     
-    let result = program
-        .request()
-        .accounts(accounts::WriteSignature {
-            signer: program.payer(),
-            signature: signature_pda,
-            system_program: system_program::ID,
-        })
-        .args(args::WriteSignature {
-            digest: digest.clone(),
-        })
-        .send();
+    let instructions = write_signature_instructions(&program, &signer.pubkey(), &payer_key, &digest, options)?;
+    
+    // Each attempt uses a fresh blockhash (or the same nonce), so a retry can never be replayed twice
+    let pool = program.pool();
+    let budget = pool.call("compute budget", |rpc| {
+        fees::compute_budget_instructions(
+            rpc,
+            &instructions,
+            &payer_key,
+            options.priority_fee,
+            options.compute_unit_limit,
+        )
+    })?;
+    let mut instructions = [budget, instructions].concat();
+    if let Some(nonce) = &options.nonce {
+        if nonce.authority != signer.pubkey() && nonce.authority != payer.pubkey() {
+            anyhow::bail!(
+                "nonce authority {} is neither the signing key nor the fee payer; use `skelz sign --prepare` to collect its signature",
+                nonce.authority
+            );
+        }
+        instructions.insert(0, nonce.advance_instruction());
+    }
+    // The nonce value is read once: every attempt then sends the same transaction
+    let nonce_value = match &options.nonce {
+        Some(nonce) => Some(pool.call("nonce account", |rpc| nonce::fetch_nonce_value(rpc, nonce))?),
+        None => None,
+    };
+    
+    let result = match &relay {
+        Some(relay) => pool.call("getLatestBlockhash", |rpc| Ok(rpc.get_latest_blockhash()?)).and_then(|blockhash| {
+            let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer_key));
+            transaction.try_partial_sign(&[signer.as_ref()], blockhash)?;
+            let receipt = relay.submit(&transaction)?;
+            info!(remaining = receipt.remaining, "relayed through {}", relay.info.fee_payer);
+            Ok(receipt.signature.parse()?)
+        }),
+        None => pool.call("send write_signature", |rpc| {
+            let blockhash = match nonce_value {
+                Some(value) => value,
+                None => rpc.get_latest_blockhash()?,
+            };
+            // try_sign: remote and prompt signers can fail
+            let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+            if payer.pubkey() == signer.pubkey() {
+                transaction.try_sign(&[payer.as_ref()], blockhash)?;
+            } else {
+                transaction.try_sign(&[payer.as_ref(), signer.as_ref()], blockhash)?;
+            }
+            Ok(rpc.send_and_confirm_transaction(&transaction)?)
+        }),
+    };
     
     let signature = match result {
         Ok(sig) => sig,
         Err(e) => {
             error!("Transaction failed: {:?}", e);
-            if let anchor_client::ClientError::ProgramError(program_error) = &e {
-                error!("Program error: {:?}", program_error);
-            }
-            return Err(e.into());
+            return Err(e);
         }
     };
     
     info!(%signature, %image_reference, "image signed successfully with Anchor program");
-    Ok(signature.to_string())
+    Ok(SignOutcome { signature: signature.to_string(), not_after: options.not_after, reused: false })
 }
 
 
 
-/// Sign an image with Solana and upload proof as OCI artifact
-pub fn sign_image_with_oci(
-    image_reference: &str,
-    config: &SkelzConfig,
-    username: &str,
-    token: &str,
-) -> Result<String> {
-    info!("Signing image with OCI: {}", image_reference);
-    
-    // Sign the image on Solana using the Anchor program
-    let signature = sign_docker_image_with_anchor(image_reference, config)?;
-    info!(%signature, "image signed on Solana with Anchor program");
-    
-    // Create the Solana proof payload
+const SKELZ_ARTIFACT_TYPE: &str = "application/vnd.skelz.proof.v1+json";
+const PROOF_FILE_NAME: &str = "skelz-signature.json";
+
+/// Serialized proof payload attached to the image
+fn proof_payload_bytes(signature: &str) -> Result<Vec<u8>> {
     let payload = SolanaProofPayload {
         network: "solana-devnet".to_string(),
-        tx_hash: signature.clone(),
+        tx_hash: signature.to_string(),
         tool: "skelz-cli@v1.0.0".to_string(),
     };
-    
-    let payload_json = json!(payload);
-    let payload_bytes = serde_json::to_vec(&payload_json)
-        .context("Failed to serialize payload to JSON")?;
-    
-    // Write payload to temporary file in current directory
-    let signature_file = std::path::PathBuf::from("skelz-signature.json");
-    std::fs::write(&signature_file, &payload_bytes)
-        .context("Failed to write signature file")?;
-    
-    // Ensure image reference is for GHCR
-    let ghcr_reference = if image_reference.starts_with("ghcr.io/") {
-        image_reference.to_string()
-    } else {
-        // Extract repository and digest from the original reference
-        let parts: Vec<&str> = image_reference.split('/').collect();
-        if parts.len() >= 2 {
-            let repo_with_tag = parts[1..].join("/");
-            format!("ghcr.io/{}", repo_with_tag)
-        } else {
-            anyhow::bail!("Invalid image reference format: {}", image_reference);
-        }
-    };
-    
-    info!("Using GHCR reference: {}", ghcr_reference);
-    
-    // Use oras attach to attach the signature to the image
-    let mut cmd = Command::new("oras");
-    cmd.arg("attach")
-        .arg("--artifact-type")
-        .arg("application/vnd.skelz.proof.v1+json")
-        .arg("--annotation")
-        .arg(format!("skelz.signature={}", signature))
-        .arg("--annotation")
-        .arg(format!("skelz.original-image={}", image_reference))
-        .arg("--annotation")
-        .arg("skelz.tool=skelz-cli@v1.0.0")
-        .arg(&ghcr_reference)
-        .arg(&signature_file);
-    
-    // Set authentication
-    cmd.env("ORAS_USERNAME", username);
-    cmd.env("ORAS_PASSWORD", token);
-    
-    info!("Running oras attach command...");
-    let output = cmd.output()
-        .context("Failed to execute oras command")?;
-    
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        anyhow::bail!("oras attach failed:\nSTDOUT: {}\nSTDERR: {}", stdout, stderr);
-    }
-    
-    info!("Successfully attached signature to image: {}", ghcr_reference);
-    
-    // Discover and display attached artifacts
-    info!("Discovering attached artifacts...");
-    let mut discover_cmd = Command::new("oras");
-    discover_cmd.arg("discover")
-        .arg(&ghcr_reference);
-    
-    // Set authentication for discover command
-    discover_cmd.env("ORAS_USERNAME", username);
-    discover_cmd.env("ORAS_PASSWORD", token);
-    
-    let discover_output = discover_cmd.output()
-        .context("Failed to execute oras discover command")?;
-    
-    if discover_output.status.success() {
-        let discover_stdout = String::from_utf8_lossy(&discover_output.stdout);
-        info!("Attached artifacts:\n{}", discover_stdout);
-        println!("Attached artifacts:\n{}", discover_stdout);
-    } else {
-        let discover_stderr = String::from_utf8_lossy(&discover_output.stderr);
-        info!("Warning: Could not discover artifacts: {}", discover_stderr);
+    serde_json::to_vec(&json!(payload)).context("Failed to serialize payload to JSON")
+}
+
+/// Annotations of the proof artifact
+fn proof_annotations(signature: &str, image_reference: &str, options: &SignOptions) -> Vec<(String, String)> {
+    let mut annotations = vec![
+        ("skelz.signature".to_string(), signature.to_string()),
+        ("skelz.original-image".to_string(), image_reference.to_string()),
+        ("skelz.tool".to_string(), "skelz-cli@v1.0.0".to_string()),
+    ];
+    if let Some(not_after) = options.not_after {
+        annotations.push(("skelz.not-after".to_string(), format_timestamp(not_after)));
     }
+    annotations
+}
+
+/// OCI manifest pushed for the proof of `signature`; the registry client fills in the
+/// subject's media type and size and the empty config
+pub fn proof_manifest(image_reference: &str, signature: &str, options: &SignOptions) -> Result<serde_json::Value> {
+    let payload = proof_payload_bytes(signature)?;
+    let annotations: serde_json::Map<String, serde_json::Value> = proof_annotations(signature, image_reference, options)
+        .into_iter()
+        .map(|(key, value)| (key, json!(value)))
+        .collect();
+    Ok(json!({
+        "schemaVersion": 2,
+        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+        "artifactType": SKELZ_ARTIFACT_TYPE,
+        "subject": {
+            "reference": image_reference,
+            "digest": extract_digest_from_reference(image_reference)?,
+        },
+        "layers": [{
+            "mediaType": "application/vnd.oci.image.layer.v1.tar",
+            "digest": format!("sha256:{}", hex::encode(Sha256::digest(&payload))),
+            "size": payload.len(),
+            "annotations": { "org.opencontainers.image.title": PROOF_FILE_NAME },
+        }],
+        "annotations": annotations,
+    }))
+}
+
+/// Account size of a `Signature` PDA (discriminator + fields, as allocated by the program)
+///
+/// Mirrors the program's `SIGNATURE_SPACE`; a test pins it to the largest record
+/// the IDL type can hold.
+pub const SIGNATURE_ACCOUNT_SPACE: usize = 8 + 100 + 32 + 8 + 8 + 9 + 9;
+
+/// Outcome of `skelz sign --dry-run`
+#[derive(Debug, Clone, Serialize)]
+pub struct SignPlan {
+    pub digest: String,
+    pub pda: String,
+    pub signer: String,
+    /// Account paying the transaction fee and rent
+    pub fee_payer: String,
+    /// Signer of the existing signature account, if the digest is already signed
+    pub existing_signer: Option<String>,
+    /// Simulation error, if the transaction would fail
+    pub simulation_error: Option<String>,
+    pub simulation_logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// Limit that would be requested (flag/config or simulated consumption + margin)
+    pub compute_unit_limit: u32,
+    /// Compute-unit price in micro-lamports
+    pub priority_fee: Option<u64>,
+    /// Transaction fee from `getFeeForMessage`, priority fee included
+    pub fee_lamports: u64,
+    /// Rent-exempt deposit for the signature account
+    pub rent_lamports: u64,
+    /// Manifest of the proof artifact that would be attached
+    pub manifest: serde_json::Value,
+}
+
+/// Build and simulate the signing transaction without sending it or touching the registry
+pub fn plan_image_signature(image_reference: &str, cfg: &SkelzConfig, options: &SignOptions) -> Result<SignPlan> {
+    let digest = extract_digest_from_reference(image_reference)?;
+    let program = load_skelz_program(cfg)?;
+    let signer = program.payer();
+    let payer = match options.relay_url.as_deref() {
+        Some(url) => relay::RelayClient::connect(url, &cfg.program_id()?)?.fee_payer,
+        None => options.fee_payer.as_ref().map(|p| p.pubkey()).unwrap_or(signer),
+    };
+    let pda = signature_pda(&program.id(), &digest);
+    let existing_signer = fetch_signature(&program, &digest)?.map(|s| s.signer.to_string());
+
+    let instructions = write_signature_instructions(&program, &signer, &payer, &digest, options)?;
+    let pool = program.pool();
+
+    // Simulate with the maximum limit (or the requested one) to measure consumption
+    let simulated_limit = options.compute_unit_limit.unwrap_or(fees::MAX_COMPUTE_UNIT_LIMIT);
+    let simulation = pool.call("simulate write_signature", |rpc| {
+        let mut simulated = vec![solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(simulated_limit)];
+        simulated.extend_from_slice(&instructions);
+        let transaction = Transaction::new_with_payer(&simulated, Some(&payer));
+        Ok(rpc
+            .simulate_transaction_with_config(
+                &transaction,
+                solana_client::rpc_config::RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    ..Default::default()
+                },
+            )?
+            .value)
+    })?;
+    let compute_unit_limit = match (options.compute_unit_limit, simulation.units_consumed) {
+        (Some(limit), _) => limit,
+        (None, Some(consumed)) if simulation.err.is_none() => fees::compute_unit_limit_for(consumed),
+        (None, _) => fees::MAX_COMPUTE_UNIT_LIMIT,
+    };
+
+    let priority_fee = pool.call("priority fee", |rpc| {
+        fees::resolve_priority_fee(rpc, &instructions, options.priority_fee)
+    })?;
+    let mut budgeted = vec![solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit)];
+    if let Some(price) = priority_fee {
+        budgeted.push(solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+    budgeted.extend_from_slice(&instructions);
+    let (fee_lamports, rent_lamports) = pool.call("fee and rent", |rpc| {
+        let blockhash = rpc.get_latest_blockhash()?;
+        let message = solana_sdk::message::Message::new_with_blockhash(&budgeted, Some(&payer), &blockhash);
+        Ok((
+            rpc.get_fee_for_message(&message)?,
+            rpc.get_minimum_balance_for_rent_exemption(SIGNATURE_ACCOUNT_SPACE)?,
+        ))
+    })?;
+
+    Ok(SignPlan {
+        digest,
+        pda: pda.to_string(),
+        signer: signer.to_string(),
+        fee_payer: payer.to_string(),
+        existing_signer,
+        simulation_error: simulation.err.map(|e| e.to_string()),
+        simulation_logs: simulation.logs.unwrap_or_default(),
+        units_consumed: simulation.units_consumed,
+        compute_unit_limit,
+        priority_fee,
+        fee_lamports,
+        rent_lamports,
+        manifest: proof_manifest(image_reference, "<transaction signature>", options)?,
+    })
+}
+
+/// Sign an image with Solana and upload proof as OCI artifact
+pub fn sign_image_with_oci(
+    image_reference: &str,
+    config: &SkelzConfig,
+    credential: Option<&Credential>,
+    options: &SignOptions,
+) -> Result<String> {
+    info!("Signing image with OCI: {}", image_reference);
     
-    // Clean up temporary file
-    let _ = std::fs::remove_file(&signature_file);
+    // Sign the image on Solana using the Anchor program
+    let outcome = write_or_reuse_signature(image_reference, config, options)?;
+    let signature = outcome.signature;
+    // Annotate with the expiry actually recorded, which differs when the signature was reused
+    let options = &SignOptions { not_after: outcome.not_after, ..options.clone() };
+    info!(%signature, "image signed on Solana with Anchor program");
     
-    info!(%signature, "signature attached successfully");
+    attach_signature_proof(image_reference, &signature, options, credential)?;
     Ok(signature)
 }
 
-/// Discover OCI artifacts attached to an image
-pub fn discover_oci_artifacts(
+/// Attach the proof of signing transaction `signature` to the image as an OCI referrer
+pub fn attach_signature_proof(
     image_reference: &str,
-    username: &str,
-    token: &str,
-) -> Result<Vec<OciArtifact>> {
+    signature: &str,
+    options: &SignOptions,
+    credential: Option<&Credential>,
+) -> Result<()> {
+    let image = registry::ImageReference::parse(image_reference)?;
+    let payload_bytes = proof_payload_bytes(signature)?;
+    let mut manifest = proof_manifest(image_reference, signature, options)?;
+    manifest["annotations"]["org.opencontainers.image.created"] = json!(chrono::Utc::now().to_rfc3339());
+
+    let mut client = registry::RegistryClient::new(&image.registry, credential.cloned())?;
+    let descriptor = client.attach(&image, manifest, &[&payload_bytes])?;
+    info!(%signature, artifact = %descriptor.digest, "signature attached successfully");
+    println!("Attached artifact {}@{}", image_reference.split('@').next().unwrap_or(image_reference), descriptor.digest);
+    Ok(())
+}
+
+/// Discover OCI artifacts attached to an image, anonymously when `credential` is None
+pub fn discover_oci_artifacts(image_reference: &str, credential: Option<&Credential>) -> Result<Vec<OciArtifact>> {
     info!("Discovering OCI artifacts for image: {}", image_reference);
-    
-    let mut cmd = Command::new("oras");
-    cmd.arg("discover")
-        .arg("--format")
-        .arg("json")
-        .arg(image_reference);
-    
-    // Set authentication
-    cmd.env("ORAS_USERNAME", username);
-    cmd.env("ORAS_PASSWORD", token);
-    
-    let output = cmd.output()
-        .context("Failed to execute oras discover command")?;
-    
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        anyhow::bail!("oras discover failed:\nSTDOUT: {}\nSTDERR: {}", stdout, stderr);
-    }
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    info!("ORAS discover output: {}", stdout);
-    
-    // Parse the JSON response
-    let discover_response: OciDiscoverResponse = serde_json::from_str(&stdout)
-        .context("Failed to parse oras discover JSON response")?;
-    
-    info!("Found {} artifacts", discover_response.referrers.len());
-    Ok(discover_response.referrers)
+    let image = registry::ImageReference::parse(image_reference)?;
+    let mut client = registry::RegistryClient::new(&image.registry, credential.cloned())?;
+    let artifacts: Vec<OciArtifact> = client
+        .referrers(&image.repository, &image.digest)?
+        .into_iter()
+        .map(|descriptor| OciArtifact {
+            reference: format!("{}/{}@{}", image.registry, image.repository, descriptor.digest),
+            media_type: descriptor.media_type,
+            digest: descriptor.digest,
+            size: descriptor.size,
+            annotations: descriptor.annotations.into_iter().collect(),
+            artifact_type: descriptor.artifact_type.unwrap_or_default(),
+            referrers: Vec::new(),
+        })
+        .collect();
+    info!("Found {} artifacts", artifacts.len());
+    Ok(artifacts)
 }
 
 /// Get the latest Skelz artifact from a list of OCI artifacts
@@ -586,11 +1500,7 @@ pub fn get_latest_skelz_artifact<'a>(artifacts: &'a [OciArtifact], expected_imag
 }
 
 /// Simple verification function that only checks OCI artifacts (without Solana verification)
-pub fn verify_oci_artifacts(
-    image_reference: &str,
-    username: &str,
-    token: &str,
-) -> Result<()> {
+pub fn verify_oci_artifacts(image_reference: &str, credential: Option<&Credential>) -> Result<()> {
     info!("Starting OCI artifact verification for: {}", image_reference);
     
     // Step 1: Validate image reference format
@@ -598,12 +1508,8 @@ pub fn verify_oci_artifacts(
         anyhow::bail!("Image reference must be canonical with digest (e.g., ghcr.io/username/repo@sha256:abc123...)");
     }
     
-    if !image_reference.starts_with("ghcr.io") {
-        anyhow::bail!("Only GitHub Container Registry is supported. Use format: ghcr.io/username/repo@sha256:abc123...");
-    }
-    
     // Step 2: Discover OCI artifacts
-    let artifacts = discover_oci_artifacts(image_reference, username, token)?;
+    let artifacts = discover_oci_artifacts(image_reference, credential)?;
     
     // Step 3: Get the latest Skelz artifact
     let skelz_artifact = get_latest_skelz_artifact(&artifacts, image_reference)?;
@@ -629,11 +1535,22 @@ pub fn verify_oci_artifacts(
 
 
 /// Verify signature using PDA-based system with Anchor
-pub fn verify_signature(
-    program: &anchor_client::Program<Rc<Keypair>>,
+pub fn verify_signature(program: &SkelzProgram, digest: &str, expected_signer: &str) -> Result<()> {
+    let expected_pubkey = Pubkey::from_str(expected_signer)
+        .context("Invalid expected signer public key format")?;
+    verify_signature_with_signers(program, digest, &[expected_pubkey], &VerifyOptions::default())?;
+    Ok(())
+}
+
+/// Verify signature using PDA-based system, accepting any of the trusted signers
+///
+/// Returns the verified signature account.
+pub fn verify_signature_with_signers(
+    program: &SkelzProgram,
     digest: &str,
-    expected_signer: &str,
-) -> Result<()> {
+    trusted_signers: &[Pubkey],
+    options: &VerifyOptions,
+) -> Result<Signature> {
     info!("Verifying signature for digest: {}", digest);
     
     // Step 1: Calculate PDA with the same seed as the program
@@ -649,8 +1566,9 @@ pub fn verify_signature(
     info!("Calculated PDA: {}", signature_pda);
     
     // Step 2: Check if the account exists on Solana using Anchor IDL
-    let signature_account: Signature = program.account::<Signature>(signature_pda)
-        .map_err(|e| anyhow!("Signature account not found: {}. This means the image was not signed or not exists.", e))?;
+    // RPC failures keep their error type so callers can tell them from a missing signature
+    let signature_account: Signature = fetch_optional_account(program, &signature_pda)?
+        .ok_or_else(|| anyhow!("Signature account not found: {}. This means the image was not signed or not exists.", signature_pda))?;
     
     // Step 3: Verify the account data matches expectations
     if signature_account.digest != digest {
@@ -661,25 +1579,60 @@ pub fn verify_signature(
         );
     }
     
-    // Step 4: Verify the signer matches expected signer
-    let expected_pubkey = Pubkey::from_str(expected_signer)
-        .context("Invalid expected signer public key format")?;
+    if let Some(revoked_at) = signature_account.revoked_at {
+        anyhow::bail!("Signature was revoked by its signer at {}", format_timestamp(revoked_at));
+    }
     
-    if signature_account.signer != expected_pubkey {
-        anyhow::bail!(
-            "Signer mismatch: expected {}, got {}",
-            expected_pubkey,
-            signature_account.signer
-        );
+    // Step 4: Verify the signer is trusted, directly, through key rotations or as a co-signer
+    let co_signers = multisig::co_signers_of(trusted_signers, signature_account.slot, |key| {
+        Ok(multisig::fetch_multisig(program, key)?.map(|m| m.co_signers).unwrap_or_default())
+    })?;
+    let mut trusted: Vec<Pubkey> = trusted_signers.to_vec();
+    trusted.extend(co_signers.iter().map(|(co_signer, _)| *co_signer));
+    let chain = follow_rotation_chain(
+        signature_account.signer,
+        signature_account.slot,
+        &trusted,
+        |key| Ok(fetch_rotation(program, key)?.map(|r| (r.new_key, r.effective_slot))),
+    )?;
+    
+    // Step 5: Enforce the validity window
+    let expiry = expiry_status(signature_account.not_after, cluster_unix_timestamp(program));
+    if let ExpiryStatus::Expired { not_after } = expiry {
+        if !options.allow_expired {
+            anyhow::bail!(
+                "Signature expired at {} (use --allow-expired to accept it anyway)",
+                format_timestamp(not_after)
+            );
+        }
+        info!("⚠️ accepting expired signature (allow_expired)");
     }
     
     info!("✅ Signature verification successful!");
     println!("✅ Signature verification successful!");
     println!("   - Digest: {}", signature_account.digest);
     println!("   - Signer: {}", signature_account.signer);
+    println!("   - Slot: {}", signature_account.slot);
+    println!("   - Signed at: {}", format_timestamp(signature_account.created_at));
+    match expiry {
+        ExpiryStatus::NoExpiry => println!("   - Expiry: none"),
+        ExpiryStatus::Valid { not_after } => {
+            println!("   - Expiry: valid until {}", format_timestamp(not_after))
+        }
+        ExpiryStatus::Expired { not_after } => {
+            println!("   - Expiry: ⚠️ EXPIRED since {} (allowed by override)", format_timestamp(not_after))
+        }
+    }
     println!("   - PDA: {}", signature_pda);
+    if chain.len() > 1 {
+        let path: Vec<String> = chain.iter().map(|k| k.to_string()).collect();
+        println!("   - Trusted via rotation: {}", path.join(" -> "));
+    }
+    if let Some((_, principal)) = co_signers.iter().find(|(co_signer, _)| chain.last() == Some(co_signer)) {
+        println!("   - Trusted as co-signer of: {}", principal);
+    }
     
-    Ok(())
+    Ok(signature_account)
 }
 
 /// Complete verification function using PDA-based system
@@ -687,8 +1640,7 @@ pub fn verify_image_signature(
     image_reference: &str,
     expected_signer: &str,
     config: &SkelzConfig,
-    _username: &str,
-    _token: &str,
+    options: &VerifyOptions,
 ) -> Result<()> {
     info!("Starting PDA-based image signature verification for: {}", image_reference);
     
@@ -697,37 +1649,657 @@ pub fn verify_image_signature(
         anyhow::bail!("Image reference must be canonical with digest (e.g., ghcr.io/username/repo@sha256:abc123...)");
     }
     
-    if !image_reference.starts_with("ghcr.io") {
-        anyhow::bail!("Only GitHub Container Registry is supported. Use format: ghcr.io/username/repo@sha256:abc123...");
-    }
-    
     // Step 2: Extract digest from image reference
     let digest = extract_digest_from_reference(image_reference)?;
     info!("Extracted digest: {}", digest);
     
     // Step 3: Configure Anchor program client using config keypair
-    let payer = read_keypair_file(&config.keypair_path)
-        .map_err(|e| anyhow!("read keypair at {}: {}", config.keypair_path.display(), e))?;
-    let provider = Client::new_with_options(
-        Cluster::Devnet,
-        Rc::new(payer),
-        CommitmentConfig::confirmed(),
-    );
-    let program = provider.program(skelz::ID)?;
+    let program = load_skelz_program(config)?;
     
     // Step 4: Verify signature using PDA with Anchor IDL
-    verify_signature(&program, &digest, expected_signer)?;
+    let expected_pubkey = Pubkey::from_str(expected_signer)
+        .context("Invalid expected signer public key format")?;
+    verify_signature_with_signers(&program, &digest, &[expected_pubkey], options)?;
     
     info!("✅ Complete image signature verification successful!");
     println!("✅ Complete image signature verification successful!");
     Ok(())
 }
 
+/// Derive the rotation PDA recording the handover away from `old_key`
+pub fn rotation_pda(program_id: &Pubkey, old_key: &Pubkey) -> Pubkey {
+    let (pda, _bump) = Pubkey::find_program_address(&[b"rotation", old_key.as_ref()], program_id);
+    pda
+}
+
+/// Hand trust over from the configured keypair to `new_key`, which co-signs the handover
+///
+/// Without `effective_slot` the rotation takes effect at the current slot.
+pub fn rotate_signing_key(
+    cfg: &SkelzConfig,
+    new_key: &SkelzSigner,
+    effective_slot: Option<u64>,
+) -> Result<String> {
+    let program = load_skelz_program(cfg)?;
+    let old_key = program.payer();
+    let rotation = rotation_pda(&program.id(), &old_key);
+    info!(%old_key, new_key = %new_key.pubkey(), %rotation, "rotating signing key");
+
+    let signature = program
+        .request()
+        .accounts(accounts::RotateKey {
+            old_key,
+            new_key: new_key.pubkey(),
+            rotation,
+            system_program: system_program::ID,
+        })
+        .args(args::RotateKey { effective_slot })
+        .signer(new_key)
+        .send()
+        .map_err(|e| anyhow!("rotate key {}: {}", old_key, e))?;
+    Ok(signature.to_string())
+}
+
+/// Fetch and decode a program account, returning `None` if it does not exist
+pub(crate) fn fetch_optional_account<T: AccountDeserialize>(program: &SkelzProgram, pda: &Pubkey) -> Result<Option<T>> {
+    match fetch_account_data(program, pda)? {
+        Some(data) => {
+            let decoded = T::try_deserialize(&mut data.as_slice())
+                .with_context(|| format!("decode account {}", pda))?;
+            Ok(Some(decoded))
+        }
+        None => Ok(None),
+    }
+}
+
+fn fetch_account_data(program: &SkelzProgram, pda: &Pubkey) -> Result<Option<Vec<u8>>> {
+    let account = program.pool()
+        .call("getAccountInfo", |rpc| {
+            rpc.get_account_with_commitment(pda, CommitmentConfig::confirmed())
+                .with_context(|| format!("fetch account {}", pda))
+        })?
+        .value;
+    Ok(account.map(|account| account.data))
+}
+
+/// Fetch the rotation record for a key, if it has been rotated
+pub fn fetch_rotation(program: &SkelzProgram, key: &Pubkey) -> Result<Option<Rotation>> {
+    fetch_optional_account(program, &rotation_pda(&program.id(), key))
+}
+
+/// Derive the signature PDA for an image digest (e.g., sha256:abc...)
+pub fn signature_pda(program_id: &Pubkey, digest: &str) -> Pubkey {
+    let digest_hash = Sha256::digest(digest.as_bytes());
+    let (pda, _bump) = Pubkey::find_program_address(&[b"signature", &digest_hash[..]], program_id);
+    pda
+}
+
+/// Fetch the on-chain signature record for a digest, if the digest was signed
+pub fn fetch_signature(program: &SkelzProgram, digest: &str) -> Result<Option<Signature>> {
+    let pda = signature_pda(&program.id(), digest);
+    match fetch_account_data(program, &pda)? {
+        Some(data) => Ok(Some(decode_signature_account(&data).with_context(|| format!("decode account {}", pda))?)),
+        None => Ok(None),
+    }
+}
+
+/// Signature account layout before slots, expiry and revocation were recorded
+///
+/// Such accounts are `8 + 100 + 32` bytes; when the digest leaves fewer than 18
+/// bytes of padding the current layout does not decode.
+#[derive(AnchorDeserialize)]
+struct LegacySignature {
+    digest: String,
+    signer: Pubkey,
+}
+
+/// Decode a signature account, falling back to the legacy layout (slot and timestamps 0)
+pub fn decode_signature_account(data: &[u8]) -> Result<Signature> {
+    if let Ok(signature) = Signature::try_deserialize(&mut &data[..]) {
+        return Ok(signature);
+    }
+    let body = data
+        .strip_prefix(&<Signature as anchor_lang::Discriminator>::DISCRIMINATOR[..])
+        .ok_or_else(|| anyhow!("not a signature account"))?;
+    let legacy = LegacySignature::deserialize(&mut &body[..]).context("signature account does not decode")?;
+    Ok(Signature {
+        digest: legacy.digest,
+        signer: legacy.signer,
+        slot: 0,
+        created_at: 0,
+        not_after: None,
+        revoked_at: None,
+    })
+}
+
+/// Revoke the signature of an image; the configured keypair must be its signer
+pub fn revoke_signature(cfg: &SkelzConfig, image_reference: &str) -> Result<String> {
+    let digest = extract_digest_from_reference(image_reference)?;
+    let program = load_skelz_program(cfg)?;
+    let signature_pda = signature_pda(&program.id(), &digest);
+    if multisig::fetch_multisig(&program, &program.payer())?.is_some() {
+        anyhow::bail!(
+            "{} is governed by a multisig; use `skelz multisig propose --signer {} revoke {}`",
+            program.payer(),
+            program.payer(),
+            image_reference
+        );
+    }
+    info!(%digest, %signature_pda, "revoking signature");
+
+    let signature = program
+        .request()
+        .accounts(accounts::RevokeSignature {
+            signer: program.payer(),
+            signature: signature_pda,
+            multisig: multisig::multisig_pda(&program.id(), &program.payer()),
+            system_program: system_program::ID,
+        })
+        .args(args::RevokeSignature { digest: digest.clone() })
+        .send()
+        .map_err(|e| anyhow!("revoke signature for {}: {}", digest, e))?;
+    Ok(signature.to_string())
+}
+
+/// Follow key rotations from the key that produced a signature to a trusted key
+///
+/// `next_rotation` returns the successor of a key and the slot from which the
+/// handover is effective. The signing key must not have been rotated out when it
+/// signed at `signed_slot`. Returns the path from the signer to the trusted key.
+pub fn follow_rotation_chain<F>(
+    signer: Pubkey,
+    signed_slot: u64,
+    trusted_signers: &[Pubkey],
+    mut next_rotation: F,
+) -> Result<Vec<Pubkey>>
+where
+    F: FnMut(&Pubkey) -> Result<Option<(Pubkey, u64)>>,
+{
+    let mut next = next_rotation(&signer)?;
+    if let Some((_, effective_slot)) = next {
+        if signed_slot >= effective_slot {
+            anyhow::bail!(
+                "Signer {} was rotated out at slot {} but signed at slot {}",
+                signer,
+                effective_slot,
+                signed_slot
+            );
+        }
+    }
+
+    let mut chain = vec![signer];
+    if trusted_signers.contains(&signer) {
+        return Ok(chain);
+    }
+    while let Some((successor, _)) = next {
+        if chain.contains(&successor) || chain.len() > MAX_ROTATION_DEPTH {
+            anyhow::bail!("Rotation chain from {} is cyclic or too long", signer);
+        }
+        chain.push(successor);
+        if trusted_signers.contains(&successor) {
+            return Ok(chain);
+        }
+        next = next_rotation(&successor)?;
+    }
+
+    match trusted_signers {
+        [expected_pubkey] => Err(anyhow!(
+            "Signer mismatch: expected {}, got {}",
+            expected_pubkey,
+            signer
+        )),
+        _ => Err(anyhow!(
+            "Signer mismatch: {} is not one of the trusted signers [{}]",
+            signer,
+            trusted_signers.iter().map(|k| k.to_string()).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// Publisher identity fields submitted on register/update
+#[derive(Debug, Clone)]
+pub struct PublisherParams {
+    /// Repository namespace pattern (e.g., ghcr.io/kevredlabs/*)
+    pub namespace: String,
+    /// Human-readable name shown to verifiers
+    pub display_name: String,
+    /// Security contact (email or URL)
+    pub contact: String,
+    /// Keys authorized to sign images under the namespace
+    pub keys: Vec<Pubkey>,
+}
+
+/// Derive the publisher PDA for a publisher handle
+pub fn publisher_pda(program_id: &Pubkey, name: &str) -> Pubkey {
+    let (pda, _bump) = Pubkey::find_program_address(&[b"publisher", name.as_bytes()], program_id);
+    pda
+}
+
+/// Register a new publisher on-chain; the configured keypair becomes its authority
+pub fn register_publisher(cfg: &SkelzConfig, name: &str, params: &PublisherParams) -> Result<String> {
+    let program = load_skelz_program(cfg)?;
+    let publisher = publisher_pda(&program.id(), name);
+    info!(%publisher, %name, "registering publisher");
+
+    let signature = program
+        .request()
+        .accounts(accounts::RegisterPublisher {
+            authority: program.payer(),
+            publisher,
+            system_program: system_program::ID,
+        })
+        .args(args::RegisterPublisher {
+            name: name.to_string(),
+            namespace: params.namespace.clone(),
+            display_name: params.display_name.clone(),
+            contact: params.contact.clone(),
+            keys: params.keys.clone(),
+        })
+        .send()
+        .map_err(|e| anyhow!("register publisher {}: {}", name, e))?;
+    Ok(signature.to_string())
+}
+
+/// Update an existing publisher; the configured keypair must be its authority
+pub fn update_publisher(cfg: &SkelzConfig, name: &str, params: &PublisherParams) -> Result<String> {
+    let program = load_skelz_program(cfg)?;
+    let publisher = publisher_pda(&program.id(), name);
+    info!(%publisher, %name, "updating publisher");
+
+    let signature = program
+        .request()
+        .accounts(accounts::UpdatePublisher {
+            authority: program.payer(),
+            publisher,
+        })
+        .args(args::UpdatePublisher {
+            namespace: params.namespace.clone(),
+            display_name: params.display_name.clone(),
+            contact: params.contact.clone(),
+            keys: params.keys.clone(),
+        })
+        .send()
+        .map_err(|e| anyhow!("update publisher {}: {}", name, e))?;
+    Ok(signature.to_string())
+}
+
+/// Fetch a publisher account by handle
+pub fn fetch_publisher(program: &SkelzProgram, name: &str) -> Result<Publisher> {
+    let pda = publisher_pda(&program.id(), name);
+    program
+        .account::<Publisher>(pda)
+        .map_err(|e| anyhow!("Publisher '{}' not found at {}: {}", name, pda, e))
+}
+
+/// Repository part of an image reference (without tag or digest)
+pub fn image_repository(image_reference: &str) -> &str {
+    let without_digest = image_reference.split('@').next().unwrap_or(image_reference);
+    // A ':' after the last '/' is a tag separator, not a registry port
+    match without_digest.rfind(':') {
+        Some(idx) if idx > without_digest.rfind('/').unwrap_or(0) => &without_digest[..idx],
+        _ => without_digest,
+    }
+}
+
+/// Match a repository against a glob pattern where `*` matches any sequence of characters
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    let (p, v): (Vec<char>, Vec<char>) = (pattern.chars().collect(), value.chars().collect());
+    let (mut pi, mut vi) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while vi < v.len() {
+        if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, vi));
+            pi += 1;
+        } else if pi < p.len() && (p[pi] == '?' || p[pi] == v[vi]) {
+            pi += 1;
+            vi += 1;
+        } else if let Some((star_pi, star_vi)) = backtrack {
+            pi = star_pi + 1;
+            vi = star_vi + 1;
+            backtrack = Some((star_pi, star_vi + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+/// Resolve the keys a publisher authorizes for the given image
+pub fn resolve_publisher_signers(program: &SkelzProgram, name: &str, image_reference: &str) -> Result<Vec<Pubkey>> {
+    let publisher = fetch_publisher(program, name)?;
+    let repository = image_repository(image_reference);
+    if !glob_match(&publisher.namespace, repository) {
+        anyhow::bail!(
+            "Image {} is outside the namespace of publisher '{}' ({})",
+            repository,
+            name,
+            publisher.namespace
+        );
+    }
+    info!(
+        publisher = %publisher.display_name,
+        keys = publisher.keys.len(),
+        "resolved publisher signing keys"
+    );
+    Ok(publisher.keys)
+}
+
+/// Complete verification against the keys registered by an on-chain publisher
+pub fn verify_image_signature_for_publisher(
+    image_reference: &str,
+    publisher: &str,
+    config: &SkelzConfig,
+    options: &VerifyOptions,
+) -> Result<()> {
+    info!("Starting publisher-based image signature verification for: {}", image_reference);
+
+    if !image_reference.contains("@sha256:") {
+        anyhow::bail!("Image reference must be canonical with digest (e.g., ghcr.io/username/repo@sha256:abc123...)");
+    }
+
+    let digest = extract_digest_from_reference(image_reference)?;
+    let program = load_skelz_program(config)?;
+    let trusted_signers = resolve_publisher_signers(&program, publisher, image_reference)?;
+
+    verify_signature_with_signers(&program, &digest, &trusted_signers, options)?;
+
+    println!("✅ Image is signed by publisher '{}'", publisher);
+    Ok(())
+}
+
+/// Evaluate the on-chain policy `owner`/`name` for an image and print the decision
+pub fn verify_image_with_onchain_policy(
+    image_reference: &str,
+    owner: &Pubkey,
+    name: &str,
+    config: &SkelzConfig,
+    credential: Option<&Credential>,
+) -> Result<Decision> {
+    let program = load_skelz_program(config)?;
+    let account = policy::fetch_onchain_policy(&program, owner, name)?
+        .ok_or_else(|| anyhow!("on-chain policy {}/{} not found", owner, name))?;
+    println!(
+        "Policy {}/{} version {} ({})",
+        owner,
+        name,
+        account.version,
+        if account.frozen { "frozen" } else { "mutable" }
+    );
+    verify_image_with_policy(image_reference, &Policy::from_onchain(&account), config, credential)
+}
+
+/// Evaluate a verification policy for an image and print the decision
+pub fn verify_image_with_policy(
+    image_reference: &str,
+    policy: &Policy,
+    config: &SkelzConfig,
+    credential: Option<&Credential>,
+) -> Result<Decision> {
+    info!("Starting policy-based image verification for: {}", image_reference);
+
+    if !image_reference.contains("@sha256:") {
+        anyhow::bail!("Image reference must be canonical with digest (e.g., ghcr.io/username/repo@sha256:abc123...)");
+    }
+
+    let program = load_skelz_program(config)?;
+    let now = cluster_unix_timestamp(&program);
+    let evidence = policy::collect_image_evidence(
+        &program,
+        policy,
+        image_reference,
+        &config.cluster,
+        now,
+        credential,
+    )?;
+    let decision = policy.evaluate(&evidence);
+
+    let verdict = if decision.allow { "✅ ALLOW" } else { "❌ DENY" };
+    println!("{} {}", verdict, image_reference);
+    if let Some(rule) = decision.rule.as_deref() {
+        println!("   - Rule: {}", rule);
+    }
+    for reason in &decision.reasons {
+        println!("   - Reason: {}", reason);
+    }
+    for warning in &decision.warnings {
+        println!("   - Warning: {}", warning);
+    }
+    Ok(decision)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::path::PathBuf;
 
+    #[test]
+    fn already_signed_error_names_signer() {
+        let err: anyhow::Error = SkelzError::AlreadySignedByOther {
+            digest: "sha256:abc".to_string(),
+            signer: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+        }
+        .into();
+        assert_eq!(
+            err.to_string(),
+            "sha256:abc is already signed by 9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin; a digest can only be signed once"
+        );
+        assert!(matches!(err.downcast_ref::<SkelzError>(), Some(SkelzError::AlreadySignedByOther { .. })));
+    }
+
+    #[test]
+    fn profiles_layer_over_top_level_settings() {
+        let mut cfg: SkelzConfig = toml::from_str(
+            r#"
+            cluster = "localnet"
+            rpc_url = "http://127.0.0.1:8899"
+            keypair_path = "/keys/dev.json"
+            commitment = "confirmed"
+            ghcr_user = "dev"
+
+            [profiles.prod]
+            cluster = "mainnet-beta"
+            keypair_path = "/keys/prod.json"
+            "#,
+        )
+        .unwrap();
+
+        let prod = apply_profile(&cfg, "prod").unwrap();
+        assert_eq!(prod.rpc_url, "https://api.mainnet-beta.solana.com");
+        assert_eq!(prod.keypair_path, PathBuf::from("/keys/prod.json"));
+        assert_eq!(prod.ghcr_user.as_deref(), Some("dev"));
+        assert_eq!(selected_profile(Some("prod"), &cfg).as_deref(), Some("prod"));
+        assert!(matches!(
+            apply_profile(&cfg, "staging").unwrap_err().downcast_ref::<SkelzError>(),
+            Some(SkelzError::UnknownProfile(_))
+        ));
+
+        copy_profile(&mut cfg, "prod", "staging", false).unwrap();
+        set_profile_value(&mut cfg, "staging", "cluster", "devnet").unwrap();
+        set_profile_value(&mut cfg, "staging", "rpc_max_retries", "5").unwrap();
+        assert!(set_profile_value(&mut cfg, "staging", "nonce_account", "not-a-key").is_err());
+        assert!(copy_profile(&mut cfg, "prod", "staging", false).is_err());
+        let staging = apply_profile(&cfg, "staging").unwrap();
+        assert_eq!((staging.cluster.as_str(), staging.rpc_max_retries), ("devnet", Some(5)));
+        assert_eq!(staging.keypair_path, PathBuf::from("/keys/prod.json"));
+
+        let written = toml::to_string_pretty(&cfg).unwrap();
+        assert!(written.contains("[profiles.staging]"));
+        let reread: SkelzConfig = toml::from_str(&written).unwrap();
+        assert_eq!(reread.profiles, cfg.profiles);
+    }
+
+    #[test]
+    fn resolves_layers_with_their_sources() {
+        let file: toml::Table = toml::from_str(
+            r#"
+            cluster = "localnet"
+            commitment = "finalized"
+            default_profile = "prod"
+
+            [profiles.prod]
+            cluster = "mainnet-beta"
+            rpc_max_retries = 5
+            "#,
+        )
+        .unwrap();
+        let env = |var: &str| match var {
+            "SOLANA_KEYPAIR" => Some("/keys/legacy.json".to_string()),
+            "SKELZ_COMMITMENT" => Some("processed".to_string()),
+            "SKELZ_IDENTITY_ATTESTERS" => Some(Pubkey::new_unique().to_string()),
+            _ => None,
+        };
+        let overrides = ConfigOverrides {
+            profile: None,
+            values: vec![("commitment".to_string(), "confirmed".to_string())],
+        };
+
+        let resolved = resolve_layers(file.clone(), &overrides, env).unwrap();
+        assert_eq!(resolved.profile.as_deref(), Some("prod"));
+        assert_eq!(resolved.config.rpc_url, "https://api.mainnet-beta.solana.com");
+        assert_eq!(resolved.config.rpc_max_retries, Some(5));
+        assert_eq!(resolved.config.keypair_path, PathBuf::from("/keys/legacy.json"));
+        assert_eq!(resolved.config.commitment, "confirmed");
+        assert_eq!(resolved.config.identity_attesters.len(), 1);
+        assert_eq!(resolved.sources["rpc_url"], ConfigSource::Profile("prod".to_string()));
+        assert_eq!(resolved.sources["keypair_path"], ConfigSource::Env("SOLANA_KEYPAIR".to_string()));
+        assert_eq!(resolved.sources["commitment"], ConfigSource::Flag("--commitment".to_string()));
+        assert_eq!(resolved.sources["ghcr_user"], ConfigSource::Default);
+
+        let bad_env = |var: &str| (var == "SKELZ_NONCE_ACCOUNT").then(|| "not-a-key".to_string());
+        assert!(resolve_layers(file, &ConfigOverrides::default(), bad_env).is_err());
+        for key in CONFIG_KEYS {
+            get_config_value(&SkelzConfig::default(), key).unwrap();
+        }
+    }
+
+    #[test]
+    fn validates_config_values_at_set_and_load_time() {
+        let mut cfg = SkelzConfig::default();
+        assert!(set_config_value(&mut cfg, "cluster", "mainnet-beta").is_ok());
+        assert!(set_config_value(&mut cfg, "cluster", "mainnet-bta").is_err());
+        assert!(set_config_value(&mut cfg, "commitment", "max").is_err());
+        assert!(set_config_value(&mut cfg, "rpc_url", "api.devnet.solana.com").is_err());
+        assert!(set_config_value(&mut cfg, "rpc_fallback_urls", "https://a.example,ftp://b.example").is_err());
+        assert!(set_config_value(&mut cfg, "cache_positive_ttl", "10 minutes").is_err());
+        let missing = std::env::temp_dir().join(format!("skelz-missing-keypair-{}.json", std::process::id()));
+        assert!(set_config_value(&mut cfg, "keypair_path", missing.to_str().unwrap()).is_err());
+        init_config_value(&mut cfg, "keypair_path", missing.to_str().unwrap()).unwrap();
+        assert_eq!(cfg.keypair_path, missing);
+        assert!(init_config_value(&mut cfg, "ghcr_token", "ghp_secret").is_err());
+        assert!(set_config_value(&mut cfg, "priority_fee", "cheap").is_err());
+        assert!(set_config_value(&mut cfg, "ghcr_token", "ghp_plaintext").is_err());
+        assert!(set_config_value(&mut cfg, "keypair_path", "env://SKELZ_SIGNER_KEY").is_ok());
+        assert!(set_config_value(&mut cfg, "keypair_path", "/nonexistent/id.json").is_err());
+        assert_eq!(cfg.cluster, "mainnet-beta");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let path = std::env::temp_dir().join(format!("skelz-keypair-{}.json", std::process::id()));
+            fs::write(&path, "[]").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+            let err = set_config_value(&mut cfg, "keypair_path", path.to_str().unwrap()).unwrap_err();
+            assert!(err.to_string().contains("mode 644"), "{}", err);
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+            assert!(set_config_value(&mut cfg, "keypair_path", path.to_str().unwrap()).is_ok());
+            fs::remove_file(&path).unwrap();
+        }
+
+        let no_env = |_: &str| None;
+        let file: toml::Table = toml::from_str("rpc_ur = \"http://127.0.0.1:8899\"").unwrap();
+        let err = resolve_layers(file, &ConfigOverrides::default(), no_env).unwrap_err();
+        assert_eq!(format!("{:#}", err), "in config file: unknown config key: rpc_ur");
+        let file: toml::Table = toml::from_str("[profiles.prod]\ncommitment = \"final\"").unwrap();
+        let overrides = ConfigOverrides { profile: Some("prod".to_string()), values: Vec::new() };
+        let err = resolve_layers(file, &overrides, no_env).unwrap_err();
+        assert!(format!("{:#}", err).contains("commitment (from profile prod): commitment must be one of"), "{:#}", err);
+    }
+
+    #[test]
+    fn finds_signing_transactions_by_their_event() {
+        use anchor_lang::Event;
+        use base64::Engine as _;
+
+        let (program_id, spoofer, signer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let data = skelz::events::SignatureWritten { digest: "sha256:abc".to_string(), signer, slot: 10, not_after: None }
+            .data();
+        let event = format!("Program data: {}", base64::engine::general_purpose::STANDARD.encode(data));
+        let invoked = |program: &Pubkey| {
+            vec![format!("Program {} invoke [1]", program), event.clone(), format!("Program {} success", program)]
+        };
+
+        assert!(logs_write_signature(&program_id, &invoked(&program_id), "sha256:abc", &signer));
+        assert!(!logs_write_signature(&program_id, &invoked(&program_id), "sha256:other", &signer));
+        assert!(!logs_write_signature(&program_id, &invoked(&program_id), "sha256:abc", &spoofer));
+        // A plain transfer to the account, or a look-alike event logged by another program
+        assert!(!logs_write_signature(&program_id, &[], "sha256:abc", &signer));
+        assert!(!logs_write_signature(&program_id, &invoked(&spoofer), "sha256:abc", &signer));
+    }
+
+    #[test]
+    fn signature_account_space_fits_the_largest_record() {
+        let largest = Signature {
+            digest: "a".repeat(96),
+            signer: Pubkey::new_unique(),
+            slot: u64::MAX,
+            created_at: i64::MAX,
+            not_after: Some(i64::MAX),
+            revoked_at: Some(i64::MAX),
+        };
+        let mut data = <Signature as anchor_lang::Discriminator>::DISCRIMINATOR.to_vec();
+        AnchorSerialize::serialize(&largest, &mut data).unwrap();
+        assert_eq!(data.len(), SIGNATURE_ACCOUNT_SPACE);
+        assert_eq!(decode_signature_account(&data).unwrap().revoked_at, Some(i64::MAX));
+    }
+
+    #[test]
+    fn decodes_legacy_signature_accounts() {
+        let signer = Pubkey::new_unique();
+        let legacy = |digest: &str| {
+            let mut data = <Signature as anchor_lang::Discriminator>::DISCRIMINATOR.to_vec();
+            AnchorSerialize::serialize(&digest.to_string(), &mut data).unwrap();
+            AnchorSerialize::serialize(&signer, &mut data).unwrap();
+            data.resize(8 + 100 + 32, 0);
+            data
+        };
+        // A sha256 digest leaves room for the new fields in the zero padding
+        let padded = decode_signature_account(&legacy(&format!("sha256:{}", "a".repeat(64)))).unwrap();
+        assert_eq!((padded.signer, padded.slot, padded.revoked_at), (signer, 0, None));
+        // A full-length digest only decodes with the legacy layout
+        let full = decode_signature_account(&legacy(&"b".repeat(96))).unwrap();
+        assert_eq!((full.digest.len(), full.signer, full.created_at, full.not_after), (96, signer, 0, None));
+        assert!(decode_signature_account(&[0u8; 140]).is_err());
+    }
+
+    #[test]
+    fn program_id_defaults_to_the_idl_and_follows_the_profile() {
+        assert_eq!(SkelzConfig::default().program_id().unwrap(), skelz::ID);
+        let staging = Pubkey::new_unique();
+        let file: toml::Table = toml::from_str(&format!("[profiles.staging]\nprogram_id = \"{}\"", staging)).unwrap();
+        let overrides = ConfigOverrides { profile: Some("staging".to_string()), values: Vec::new() };
+        let resolved = resolve_layers(file, &overrides, |_| None).unwrap();
+        assert_eq!(resolved.config.program_id().unwrap(), staging);
+        assert_ne!(signature_pda(&staging, "sha256:abc"), signature_pda(&skelz::ID, "sha256:abc"));
+
+        let mut cfg = SkelzConfig::default();
+        assert!(set_config_value(&mut cfg, "program_id", "not-a-program").is_err());
+        let env = |var: &str| (var == "SKELZ_PROGRAM_ID").then(|| "not-a-program".to_string());
+        assert!(resolve_layers(toml::Table::new(), &ConfigOverrides::default(), env).is_err());
+    }
+
+    #[test]
+    fn proof_manifest_describes_attached_artifact() {
+        let options = SignOptions { not_after: Some(1_900_000_000), ..SignOptions::default() };
+        let manifest = proof_manifest("ghcr.io/acme/app@sha256:abc", "5sig", &options).unwrap();
+        assert_eq!(manifest["artifactType"], "application/vnd.skelz.proof.v1+json");
+        assert_eq!(manifest["subject"]["digest"], "sha256:abc");
+        assert_eq!(manifest["annotations"]["skelz.signature"], "5sig");
+        assert_eq!(manifest["annotations"]["skelz.original-image"], "ghcr.io/acme/app@sha256:abc");
+        assert!(manifest["annotations"]["skelz.not-after"].as_str().unwrap().starts_with("2030-"));
+        let payload = proof_payload_bytes("5sig").unwrap();
+        assert_eq!(manifest["layers"][0]["size"], payload.len());
+    }
+
     #[test]
     fn default_rpc_for_devnet() {
         assert_eq!(default_cluster_rpc_url("devnet"), "https://api.devnet.solana.com");
@@ -825,6 +2397,95 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn image_repository_strips_digest_and_tag() {
+        assert_eq!(image_repository("ghcr.io/kevredlabs/skelz@sha256:abc"), "ghcr.io/kevredlabs/skelz");
+        assert_eq!(image_repository("ghcr.io/kevredlabs/skelz:latest"), "ghcr.io/kevredlabs/skelz");
+        assert_eq!(image_repository("localhost:5000/skelz"), "localhost:5000/skelz");
+    }
+
+    #[test]
+    fn glob_match_namespaces() {
+        assert!(glob_match("ghcr.io/kevredlabs/*", "ghcr.io/kevredlabs/skelz"));
+        assert!(glob_match("ghcr.io/kevredlabs/*", "ghcr.io/kevredlabs/team/app"));
+        assert!(glob_match("ghcr.io/*/skelz", "ghcr.io/kevredlabs/skelz"));
+        assert!(glob_match("ghcr.io/kevredlabs/skelz", "ghcr.io/kevredlabs/skelz"));
+        assert!(!glob_match("ghcr.io/kevredlabs/*", "ghcr.io/other/skelz"));
+        assert!(!glob_match("ghcr.io/kevredlabs/skelz", "ghcr.io/kevredlabs/skelz-fork"));
+    }
+
+    #[test]
+    fn publisher_pda_is_deterministic() {
+        let a = publisher_pda(&skelz::ID, "kevredlabs");
+        let b = publisher_pda(&skelz::ID, "kevredlabs");
+        let c = publisher_pda(&skelz::ID, "someone-else");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn parse_validity_duration_units() {
+        assert_eq!(parse_validity_duration("90d").unwrap(), chrono::Duration::days(90));
+        assert_eq!(parse_validity_duration("12h").unwrap(), chrono::Duration::hours(12));
+        assert_eq!(parse_validity_duration("2w").unwrap(), chrono::Duration::weeks(2));
+        assert_eq!(parse_validity_duration("3600s").unwrap(), chrono::Duration::seconds(3600));
+        assert!(parse_validity_duration("90").is_err());
+        assert!(parse_validity_duration("0d").is_err());
+        assert!(parse_validity_duration("5y").is_err());
+    }
+
+    #[test]
+    fn expiry_status_windows() {
+        assert_eq!(expiry_status(None, 1_000), ExpiryStatus::NoExpiry);
+        assert_eq!(expiry_status(Some(2_000), 1_000), ExpiryStatus::Valid { not_after: 2_000 });
+        assert_eq!(expiry_status(Some(2_000), 2_000), ExpiryStatus::Valid { not_after: 2_000 });
+        assert_eq!(expiry_status(Some(2_000), 2_001), ExpiryStatus::Expired { not_after: 2_000 });
+    }
+
+    fn rotations(pairs: &[(Pubkey, Pubkey, u64)]) -> HashMap<Pubkey, (Pubkey, u64)> {
+        pairs.iter().map(|(old, new, slot)| (*old, (*new, *slot))).collect()
+    }
+
+    #[test]
+    fn rotation_chain_direct_trust() {
+        let key = Pubkey::new_unique();
+        let chain = follow_rotation_chain(key, 10, &[key], |_| Ok(None)).unwrap();
+        assert_eq!(chain, vec![key]);
+    }
+
+    #[test]
+    fn rotation_chain_follows_handovers() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let records = rotations(&[(a, b, 100), (b, c, 200)]);
+        let chain = follow_rotation_chain(a, 50, &[c], |k| Ok(records.get(k).copied())).unwrap();
+        assert_eq!(chain, vec![a, b, c]);
+    }
+
+    #[test]
+    fn rotation_chain_rejects_signature_after_rotation() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let records = rotations(&[(a, b, 100)]);
+        assert!(follow_rotation_chain(a, 100, &[b], |k| Ok(records.get(k).copied())).is_err());
+        // A retired key is rejected even when still listed as trusted
+        assert!(follow_rotation_chain(a, 150, &[a], |k| Ok(records.get(k).copied())).is_err());
+    }
+
+    #[test]
+    fn rotation_chain_untrusted_successor() {
+        let (a, b, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let records = rotations(&[(a, b, 100)]);
+        let err = follow_rotation_chain(a, 50, &[other], |k| Ok(records.get(k).copied())).unwrap_err();
+        assert!(err.to_string().contains("Signer mismatch"));
+    }
+
+    #[test]
+    fn rotation_chain_detects_cycles() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let records = rotations(&[(a, b, 100), (b, a, 200)]);
+        let other = Pubkey::new_unique();
+        assert!(follow_rotation_chain(a, 50, &[other], |k| Ok(records.get(k).copied())).is_err());
+    }
+
     #[test]
     fn solana_proof_payload_serialization() {
         let payload = SolanaProofPayload {
@@ -1,14 +1,42 @@
 use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
+use std::rc::Rc;
 use tracing::{info, Level};
 use tracing_subscriber::EnvFilter;
 
 use skelz::{
-    default_cluster_rpc_url, default_config_file_path, expand_tilde, get_config_value,
-    load_config_with_overrides, resolve_ghcr_credentials, save_default_config, set_config_value,
-    write_config_file, sign_image_with_oci, verify_image_signature, SkelzConfig,
+    apply_profile, copy_profile, default_cluster_rpc_url, default_config_file_path, expand_tilde, get_config_value,
+    config_env_var, config_flag, load_config_with_overrides, resolve_config, resolve_image_credentials, resolve_registry_credentials, save_default_config, selected_profile,
+    init_config_value, set_config_value, set_profile_value,
+    write_config_file, parse_validity_duration, sign_image_with_oci, verify_image_signature,
+    verify_image_signature_for_publisher, verify_image_with_onchain_policy,
+    verify_image_with_policy, ConfigOverrides, Policy, PublisherParams, SignOptions, SkelzConfig, SkelzSigner,
+    CONFIG_KEYS,
+    VerifyOptions,
 };
+use skelz::cache::{default_cache_path, verify_signature_cached, CacheSettings, VerificationCache};
+use skelz::credentials;
+use skelz::doctor::{run_doctor, CheckStatus};
+use skelz::events::{watch_events, WatchOptions};
+use skelz::fees::{priority_fee_lamports, PriorityFee};
+use skelz::{plan_image_signature, SignPlan};
+use skelz::index::{
+    default_index_path, sync_index, verify_image_signature_from_index, IndexedSignature,
+    SignatureIndex,
+};
+use skelz::keyless::{sign_keyless, verify_image_keyless, Attester, AttesterPolicy, IdentityPolicy};
+use skelz::multisig::{self, MultisigAction};
+use skelz::nonce::{create_nonce_account, resolve_nonce};
+use skelz::oidc::{OidcVerifier, GITHUB_ACTIONS_ISSUER};
+use skelz::offline::{
+    prepare_signing_transaction, sign_prepared_transaction, submit_prepared_transaction,
+    PreparedTransaction,
+};
+use skelz::policy::{fetch_onchain_policy, OnchainPolicyParams};
+use skelz::relay::{Relay, RelayPolicy};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
 
 #[derive(Debug, Parser)]
 #[command(name = "skelz", version, about = "Skelz CLI")] 
@@ -16,11 +44,123 @@ struct Cli {
     /// Increase verbosity (-v, -vv)
     #[arg(short = 'v', action = clap::ArgAction::Count)]
     verbose: u8,
+    /// Configuration profile to use (overrides SKELZ_PROFILE and default_profile)
+    #[arg(long = "profile", global = true)]
+    profile: Option<String>,
+
+    #[command(flatten)]
+    config: ConfigFlags,
 
     #[command(subcommand)]
     command: Commands,
 }
 
+/// One global flag per config key, overriding config file, profile and `SKELZ_<KEY>`
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Config overrides")]
+struct ConfigFlags {
+    /// Cluster: devnet|testnet|mainnet-beta|localnet (sets the cluster's RPC URL unless --rpc-url)
+    #[arg(long = "cluster", global = true)]
+    cluster: Option<String>,
+    /// RPC URL
+    #[arg(long = "rpc-url", global = true)]
+    rpc_url: Option<String>,
+    /// Signing keypair path or signer URI (env://, http(s)://, prompt://)
+    #[arg(long = "keypair-path", visible_alias = "keypair", global = true)]
+    keypair_path: Option<String>,
+    /// Commitment: processed|confirmed|finalized
+    #[arg(long = "commitment", global = true)]
+    commitment: Option<String>,
+    /// Address of the skelz program instance (default: the ID declared by the IDL)
+    #[arg(long = "program-id", global = true)]
+    program_id: Option<String>,
+    /// GHCR username
+    #[arg(long = "ghcr-user", global = true)]
+    ghcr_user: Option<String>,
+    /// GHCR token (prefer GHCR_TOKEN; command lines are visible to other users)
+    #[arg(long = "ghcr-token", global = true)]
+    ghcr_token: Option<String>,
+    /// Fallback RPC URLs, comma-separated
+    #[arg(long = "rpc-fallback-urls", global = true)]
+    rpc_fallback_urls: Option<String>,
+    /// Per-request RPC timeout (e.g., 30s)
+    #[arg(long = "rpc-timeout", global = true)]
+    rpc_timeout: Option<String>,
+    /// Retries per RPC endpoint on transient errors
+    #[arg(long = "rpc-max-retries", global = true)]
+    rpc_max_retries: Option<String>,
+    /// Priority fee in micro-lamports per compute unit, or `auto`
+    #[arg(long = "priority-fee", global = true)]
+    priority_fee: Option<String>,
+    /// Compute-unit limit (estimated by simulation by default)
+    #[arg(long = "compute-unit-limit", global = true)]
+    compute_unit_limit: Option<String>,
+    /// Keypair or signer URI paying fees and rent, so the signing key needs no SOL (with sign --prepare a public key is enough)
+    #[arg(long = "fee-payer", global = true)]
+    fee_payer: Option<String>,
+    /// Relay (`skelz relay`) that pays fees and rent and submits; only the identity key signs
+    #[arg(long = "relay-url", global = true)]
+    relay_url: Option<String>,
+    /// Attester (`skelz attester`) for sign --keyless
+    #[arg(long = "attester-url", global = true)]
+    attester_url: Option<String>,
+    /// Attester keys trusted to bind identities, comma-separated
+    #[arg(long = "identity-attesters", global = true)]
+    identity_attesters: Option<String>,
+    /// Durable nonce account to build signing transactions on
+    #[arg(long = "nonce-account", global = true)]
+    nonce_account: Option<String>,
+    /// Authority of the nonce account, which must sign too (defaults to the signer)
+    #[arg(long = "nonce-authority", global = true)]
+    nonce_authority: Option<String>,
+    /// Verification cache: maximum in-memory entries
+    #[arg(long = "cache-capacity", global = true)]
+    cache_capacity: Option<String>,
+    /// Verification cache: lifetime of successful results (e.g., 10m)
+    #[arg(long = "cache-positive-ttl", global = true)]
+    cache_positive_ttl: Option<String>,
+    /// Verification cache: lifetime of failed results (e.g., 30s)
+    #[arg(long = "cache-negative-ttl", global = true)]
+    cache_negative_ttl: Option<String>,
+    /// Verification cache: SQLite store shared between processes
+    #[arg(long = "cache-path", global = true)]
+    cache_path: Option<String>,
+}
+
+impl ConfigFlags {
+    /// Flags given, as `(key, value)` pairs in key order
+    fn values(&self) -> Vec<(String, String)> {
+        let flags = [
+            ("cluster", &self.cluster),
+            ("rpc_url", &self.rpc_url),
+            ("keypair_path", &self.keypair_path),
+            ("commitment", &self.commitment),
+            ("program_id", &self.program_id),
+            ("ghcr_user", &self.ghcr_user),
+            ("ghcr_token", &self.ghcr_token),
+            ("rpc_fallback_urls", &self.rpc_fallback_urls),
+            ("rpc_timeout", &self.rpc_timeout),
+            ("rpc_max_retries", &self.rpc_max_retries),
+            ("priority_fee", &self.priority_fee),
+            ("compute_unit_limit", &self.compute_unit_limit),
+            ("fee_payer", &self.fee_payer),
+            ("relay_url", &self.relay_url),
+            ("attester_url", &self.attester_url),
+            ("identity_attesters", &self.identity_attesters),
+            ("nonce_account", &self.nonce_account),
+            ("nonce_authority", &self.nonce_authority),
+            ("cache_capacity", &self.cache_capacity),
+            ("cache_positive_ttl", &self.cache_positive_ttl),
+            ("cache_negative_ttl", &self.cache_negative_ttl),
+            ("cache_path", &self.cache_path),
+        ];
+        flags
+            .into_iter()
+            .filter_map(|(key, value)| value.as_ref().map(|v| (key.to_string(), v.clone())))
+            .collect()
+    }
+}
+
 #[derive(Debug, Subcommand)]
 enum Commands {
     /// Manage configuration
@@ -28,11 +168,184 @@ enum Commands {
     Config(ConfigCommand),
     /// Sign a Docker image with Solana signature and upload to OCI registry
     Sign(SignCmd),
-    /// Verify (placeholder)
+    /// Verify an image signature against a signer, publisher or policy file
     Verify(VerifyCmd),
+    /// Revoke the on-chain signature of an image (signer only)
+    Revoke(RevokeCmd),
     /// Registry operations
     #[command(subcommand)]
     Registry(RegistryCommand),
+    /// Manage on-chain publisher identities
+    #[command(subcommand)]
+    Publisher(PublisherCommand),
+    /// Signing key lifecycle (rotation)
+    #[command(subcommand)]
+    Key(KeyCommand),
+    /// Multisig authority over a signer's revocations and co-signing keys
+    #[command(subcommand)]
+    Multisig(MultisigCommand),
+    /// Manage on-chain admission policies
+    #[command(subcommand)]
+    Policy(PolicyCommand),
+    /// Stream skelz program events as JSON lines
+    Watch(WatchCmd),
+    /// Local SQLite index of signatures and rotations
+    #[command(subcommand)]
+    Index(IndexCommand),
+    /// Offline signing of prepared transactions
+    #[command(subcommand)]
+    Tx(TxCommand),
+    /// Durable nonce accounts for delayed submission
+    #[command(subcommand)]
+    Nonce(NonceCommand),
+    /// Run a fee relay that co-signs and submits signing transactions
+    Relay(RelayCmd),
+    /// Run an attester binding OIDC identities to keyless signing keys
+    Attester(AttesterCmd),
+    /// Check configuration, RPC, program deployment, keypair balance and registry access
+    Doctor,
+}
+
+#[derive(Debug, Args)]
+struct AttesterCmd {
+    /// Address to listen on
+    #[arg(long = "listen", default_value = "127.0.0.1:8788")]
+    listen: String,
+    /// OIDC issuer whose ID tokens are accepted
+    #[arg(long = "issuer", default_value = GITHUB_ACTIONS_ISSUER)]
+    issuer: String,
+    /// Audience ID tokens must be issued for
+    #[arg(long = "audience", default_value = "skelz")]
+    audience: String,
+    /// Fetch the issuer's keys from this JWKS URL instead of OIDC discovery
+    #[arg(long = "jwks-url")]
+    jwks_url: Option<String>,
+    /// Only attest identities matching this regular expression (default: any identity of the issuer)
+    #[arg(long = "allow-identity-regexp")]
+    allow_identity_regexp: Option<regex::Regex>,
+    /// Highest priority fee accepted, in micro-lamports per compute unit
+    #[arg(long = "max-priority-fee", default_value_t = 100_000)]
+    max_priority_fee: u64,
+    /// Highest compute-unit limit accepted
+    #[arg(long = "max-compute-unit-limit", default_value_t = 200_000)]
+    max_compute_unit_limit: u32,
+}
+
+#[derive(Debug, Args)]
+struct RelayCmd {
+    /// Address to listen on
+    #[arg(long = "listen", default_value = "127.0.0.1:8787")]
+    listen: String,
+    /// Transactions relayed per signer key within --quota-window
+    #[arg(long = "quota", default_value_t = 100)]
+    quota: u32,
+    /// Quota window (e.g., 1d, 12h)
+    #[arg(long = "quota-window", default_value = "1d")]
+    quota_window: String,
+    /// Lamports spent on fees and rent within --quota-window, across all signers
+    #[arg(long = "spend-limit", default_value_t = 1_000_000_000)]
+    spend_limit: u64,
+    /// Relay for these signer keys (repeatable; required unless --allow-any-signer)
+    #[arg(long = "allow-signer", required_unless_present = "allow_any_signer")]
+    allowed_signers: Vec<Pubkey>,
+    /// Relay for any signer key, limited only by the quotas
+    #[arg(long = "allow-any-signer", conflicts_with = "allowed_signers")]
+    allow_any_signer: bool,
+    /// Highest priority fee accepted, in micro-lamports per compute unit
+    #[arg(long = "max-priority-fee", default_value_t = 100_000)]
+    max_priority_fee: u64,
+    /// Highest compute-unit limit accepted
+    #[arg(long = "max-compute-unit-limit", default_value_t = 200_000)]
+    max_compute_unit_limit: u32,
+}
+
+#[derive(Debug, Subcommand)]
+enum NonceCommand {
+    /// Create and fund a nonce account from the configured keypair
+    Create(NonceCreateCmd),
+}
+
+#[derive(Debug, Args)]
+struct NonceCreateCmd {
+    /// Key allowed to advance the nonce (defaults to the keypair)
+    #[arg(long = "authority")]
+    authority: Option<Pubkey>,
+    /// Store the account as `nonce_account`/`nonce_authority` in the config file
+    #[arg(long = "save")]
+    save: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum TxCommand {
+    /// Sign a prepared transaction file (no network access needed)
+    Sign(TxSignCmd),
+    /// Submit a fully signed transaction file and attach the OCI proof
+    Submit(TxSubmitCmd),
+}
+
+#[derive(Debug, Args)]
+struct TxSignCmd {
+    /// Prepared transaction file (from `skelz sign --prepare`)
+    file: PathBuf,
+    /// Write the signed transaction here instead of updating FILE in place
+    #[arg(long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct TxSubmitCmd {
+    /// Fully signed transaction file
+    file: PathBuf,
+    /// Only send the transaction; do not attach the OCI proof
+    #[arg(long = "skip-attach")]
+    skip_attach: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum IndexCommand {
+    /// Backfill or incrementally update the index from the cluster
+    Sync(IndexSyncCmd),
+    /// Show the sync cursor, row counts and staleness
+    Status(IndexStatusCmd),
+    /// List signatures recorded by a signer
+    Signer(IndexSignerCmd),
+    /// Show the indexed signature of a digest
+    Digest(IndexDigestCmd),
+}
+
+#[derive(Debug, Args)]
+struct IndexOpts {
+    /// Index database path (default: ~/.config/skelz/index.sqlite)
+    #[arg(long = "index")]
+    index_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct IndexSyncCmd {
+    #[command(flatten)]
+    index: IndexOpts,
+}
+
+#[derive(Debug, Args)]
+struct IndexStatusCmd {
+    #[command(flatten)]
+    index: IndexOpts,
+}
+
+#[derive(Debug, Args)]
+struct IndexSignerCmd {
+    /// Signer public key (base58)
+    signer: Pubkey,
+    #[command(flatten)]
+    index: IndexOpts,
+}
+
+#[derive(Debug, Args)]
+struct IndexDigestCmd {
+    /// Image digest (sha256:...) or canonical image reference
+    digest: String,
+    #[command(flatten)]
+    index: IndexOpts,
 }
 
 #[derive(Debug, Subcommand)]
@@ -41,14 +354,239 @@ enum ConfigCommand {
     Init(ConfigInitCmd),
     /// Get current config settings
     Get(ConfigGetCmd),
-    /// Set a config setting
+    /// Set a config setting (in the selected profile, if any)
     Set(ConfigSetCmd),
+    /// Manage named profiles
+    #[command(subcommand)]
+    Profiles(ProfilesCommand),
+    /// Show effective settings and the layer each comes from
+    Explain(ConfigExplainCmd),
+}
+
+#[derive(Debug, Args)]
+struct ConfigExplainCmd {
+    /// Only explain this key
+    key: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum ProfilesCommand {
+    /// List profiles, marking the selected one
+    List,
+    /// Make a profile the default
+    Use(ProfilesUseCmd),
+    /// Copy a profile to a new name
+    Copy(ProfilesCopyCmd),
+}
+
+#[derive(Debug, Args)]
+struct ProfilesUseCmd {
+    /// Profile name
+    #[arg(required_unless_present = "clear")]
+    name: Option<String>,
+    /// Go back to the top-level settings
+    #[arg(long = "clear", conflicts_with = "name")]
+    clear: bool,
+}
+
+#[derive(Debug, Args)]
+struct ProfilesCopyCmd {
+    /// Profile to copy
+    from: String,
+    /// New profile name
+    to: String,
+    /// Overwrite the target profile if it exists
+    #[arg(long = "force")]
+    force: bool,
 }
 
 #[derive(Debug, Subcommand)]
 enum RegistryCommand {
-    /// Log into GitHub Container Registry (GHCR) using env/TOML creds
+    /// Log into GitHub Container Registry (GHCR) using the resolved credentials
     Login(RegistryLoginCmd),
+    /// Save registry credentials in the encrypted credential store
+    Store(RegistryStoreCmd),
+    /// Remove a registry from the credential store
+    Remove(RegistryRemoveCmd),
+    /// List registries in the credential store
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+enum PublisherCommand {
+    /// Register a publisher (the configured keypair becomes its authority)
+    Register(PublisherRegisterCmd),
+    /// Update a publisher's namespace, display name, contact or keys
+    Update(PublisherUpdateCmd),
+    /// Show a publisher registered on-chain
+    Show(PublisherShowCmd),
+}
+
+#[derive(Debug, Args)]
+struct PublisherRegisterCmd {
+    /// Publisher handle (e.g., kevredlabs)
+    name: String,
+    /// Repository namespace pattern (e.g., ghcr.io/kevredlabs/*)
+    #[arg(long = "namespace")]
+    namespace: String,
+    /// Human-readable name
+    #[arg(long = "display-name", default_value = "")]
+    display_name: String,
+    /// Security contact (email or URL)
+    #[arg(long = "contact", default_value = "")]
+    contact: String,
+    /// Authorized signing key (base58), repeatable
+    #[arg(long = "key", required = true)]
+    keys: Vec<Pubkey>,
+}
+
+#[derive(Debug, Args)]
+struct PublisherUpdateCmd {
+    /// Publisher handle (e.g., kevredlabs)
+    name: String,
+    /// New repository namespace pattern
+    #[arg(long = "namespace")]
+    namespace: Option<String>,
+    /// New human-readable name
+    #[arg(long = "display-name")]
+    display_name: Option<String>,
+    /// New security contact
+    #[arg(long = "contact")]
+    contact: Option<String>,
+    /// Replace authorized signing keys (base58), repeatable
+    #[arg(long = "key")]
+    keys: Vec<Pubkey>,
+}
+
+#[derive(Debug, Args)]
+struct PublisherShowCmd {
+    /// Publisher handle (e.g., kevredlabs)
+    name: String,
+}
+
+#[derive(Debug, Subcommand)]
+enum PolicyCommand {
+    /// Create or update a policy owned by the configured keypair
+    Push(PolicyPushCmd),
+    /// Show an on-chain policy
+    Show(PolicyShowCmd),
+    /// Make a policy immutable (irreversible)
+    Freeze(PolicyFreezeCmd),
+}
+
+#[derive(Debug, Args)]
+struct PolicyPushCmd {
+    /// Policy name (unique per owner)
+    name: String,
+    /// Repository pattern (e.g., ghcr.io/kevredlabs/*)
+    #[arg(long = "repository")]
+    repository: String,
+    /// Required signer public key (base58), repeatable
+    #[arg(long = "signer", required = true)]
+    signers: Vec<Pubkey>,
+    /// Distinct signers required among --signer (must be 1)
+    #[arg(long = "threshold", default_value_t = 1)]
+    threshold: u8,
+}
+
+#[derive(Debug, Args)]
+struct PolicyShowCmd {
+    /// Policy name
+    name: String,
+    /// Policy owner (default: configured keypair)
+    #[arg(long = "owner")]
+    owner: Option<Pubkey>,
+}
+
+#[derive(Debug, Args)]
+struct PolicyFreezeCmd {
+    /// Policy name
+    name: String,
+}
+
+#[derive(Debug, Subcommand)]
+enum KeyCommand {
+    /// Hand trust over from the configured keypair to a new key
+    Rotate(KeyRotateCmd),
+    /// Show the rotation history starting from a key
+    Show(KeyShowCmd),
+}
+
+#[derive(Debug, Args)]
+struct KeyRotateCmd {
+    /// Keypair taking over (same locations as keypair_path); it co-signs the handover
+    #[arg(long = "new-keypair")]
+    new_keypair: String,
+    /// Slot from which the old key is retired (default: current slot)
+    #[arg(long = "effective-slot")]
+    effective_slot: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct KeyShowCmd {
+    /// Public key to start from (base58)
+    key: Pubkey,
+}
+
+#[derive(Debug, Subcommand)]
+enum MultisigCommand {
+    /// Hand the authority over the configured key's records to an m-of-n multisig
+    Create(MultisigCreateCmd),
+    /// Show a multisig, its co-signers and proposals
+    Show(MultisigShowCmd),
+    /// Propose an action (the configured keypair must be a member)
+    Propose(MultisigProposeCmd),
+    /// Approve a proposal with the configured keypair
+    Approve(MultisigProposalCmd),
+    /// Execute a proposal that has enough approvals
+    Execute(MultisigProposalCmd),
+}
+
+#[derive(Debug, Args)]
+struct MultisigCreateCmd {
+    /// Member public key (repeatable)
+    #[arg(long = "member", required = true)]
+    members: Vec<Pubkey>,
+    /// Approvals required to execute a proposal
+    #[arg(long = "threshold")]
+    threshold: u8,
+}
+
+#[derive(Debug, Args)]
+struct MultisigShowCmd {
+    /// Governed signer public key (default: the configured keypair's)
+    signer: Option<Pubkey>,
+}
+
+#[derive(Debug, Args)]
+struct MultisigProposeCmd {
+    /// Governed signer public key
+    #[arg(long = "signer")]
+    signer: Pubkey,
+    #[command(subcommand)]
+    action: MultisigActionCmd,
+}
+
+#[derive(Debug, Subcommand)]
+enum MultisigActionCmd {
+    /// Revoke the signature of an image
+    Revoke {
+        /// Canonical image reference with digest
+        image_reference: String,
+    },
+    /// Trust a key to sign on behalf of the signer
+    AddCoSigner { key: Pubkey },
+    /// Stop trusting a co-signing key
+    RemoveCoSigner { key: Pubkey },
+}
+
+#[derive(Debug, Args)]
+struct MultisigProposalCmd {
+    /// Proposal index
+    index: u64,
+    /// Governed signer public key
+    #[arg(long = "signer")]
+    signer: Pubkey,
 }
 
 #[derive(Debug, Args)]
@@ -61,6 +599,26 @@ struct RegistryLoginCmd {
     username: Option<String>,
 }
 
+#[derive(Debug, Args)]
+struct RegistryStoreCmd {
+    /// Registry hostname
+    #[arg(long = "registry", default_value = "ghcr.io")]
+    registry: String,
+    /// Registry username
+    #[arg(long = "username")]
+    username: String,
+    /// Read the token from stdin instead of prompting
+    #[arg(long = "password-stdin")]
+    password_stdin: bool,
+}
+
+#[derive(Debug, Args)]
+struct RegistryRemoveCmd {
+    /// Registry hostname
+    #[arg(long = "registry", default_value = "ghcr.io")]
+    registry: String,
+}
+
 #[derive(Debug, Args)]
 struct ConfigInitCmd {
     /// Output path for the config file. Defaults to XDG config dir.
@@ -69,15 +627,6 @@ struct ConfigInitCmd {
     /// Overwrite existing file if present
     #[arg(long = "force")]
     force: bool,
-    /// Cluster shortcut: devnet|testnet|mainnet-beta|localnet
-    #[arg(long = "cluster")]
-    cluster: Option<String>,
-    /// RPC URL (overrides cluster default)
-    #[arg(long = "rpc-url")]
-    rpc_url: Option<String>,
-    /// Path to Solana keypair (id.json)
-    #[arg(long = "keypair")]
-    keypair_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Args)]
@@ -98,12 +647,24 @@ struct ConfigSetCmd {
 struct SignCmd {
     /// Canonical image reference with digest (e.g., docker.io/tonorg/tonimage@sha256:abc123...)
     image_reference: String,
-    /// RPC URL (overrides config and env)
-    #[arg(long = "rpc-url")]
-    rpc_url: Option<String>,
-    /// Path to Solana keypair (id.json) (overrides config and env)
-    #[arg(long = "keypair")]
-    keypair_path: Option<PathBuf>,
+    /// Signature validity period from now (e.g., 90d, 12h, 2w)
+    #[arg(long = "valid-for")]
+    valid_for: Option<String>,
+    /// Simulate the transaction and print costs and the artifact manifest without sending or attaching
+    #[arg(long = "dry-run", conflicts_with = "prepare")]
+    dry_run: bool,
+    /// Write the unsigned transaction to FILE for offline signing (`skelz tx sign`) instead of sending it
+    #[arg(long = "prepare", value_name = "FILE")]
+    prepare: Option<PathBuf>,
+    /// Signer public key of the prepared transaction (defaults to the configured keypair's)
+    #[arg(long = "signer", requires = "prepare")]
+    signer: Option<Pubkey>,
+    /// Sign with a throwaway key bound to your OIDC identity by an attester (`skelz attester`)
+    #[arg(long = "keyless", conflicts_with_all = ["fee_payer", "relay_url", "prepare", "nonce_account", "dry_run"])]
+    keyless: bool,
+    /// OIDC ID token for --keyless (default: SKELZ_OIDC_TOKEN, or requested from GitHub Actions)
+    #[arg(long = "oidc-token", requires = "keyless")]
+    oidc_token: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -111,11 +672,138 @@ struct VerifyCmd {
     /// Canonical image reference with digest (e.g., ghcr.io/username/repo@sha256:abc123...)
     image_reference: String,
     /// Expected signer public key (base58 encoded)
-    #[arg(long = "signer")]
-    expected_signer: String,
-    /// RPC URL (overrides config and env)
-    #[arg(long = "rpc-url")]
-    rpc_url: Option<String>,
+    #[arg(
+        long = "signer",
+        required_unless_present_any = ["publisher", "policy", "onchain_policy", "identity_regexp"],
+        conflicts_with_all = ["publisher", "policy", "onchain_policy", "identity_regexp"]
+    )]
+    expected_signer: Option<String>,
+    /// Trust the keys registered on-chain by this publisher (e.g., kevredlabs)
+    #[arg(long = "publisher", conflicts_with_all = ["policy", "onchain_policy"])]
+    publisher: Option<String>,
+    /// Evaluate a verification policy file (TOML)
+    #[arg(long = "policy", conflicts_with = "onchain_policy")]
+    policy: Option<PathBuf>,
+    /// Evaluate an on-chain policy given as <OWNER>/<NAME>
+    #[arg(long = "onchain-policy")]
+    onchain_policy: Option<String>,
+    /// Accept expired signatures (expiry is still reported)
+    #[arg(long = "allow-expired")]
+    allow_expired: bool,
+    /// Reuse recent results from the verification cache (with --signer or --publisher)
+    #[arg(long = "cache", conflicts_with_all = ["policy", "onchain_policy", "from_index"])]
+    cache: bool,
+    /// Answer from the local index instead of RPC (only with --signer)
+    #[arg(long = "from-index", requires = "expected_signer")]
+    from_index: bool,
+    /// Index database path used with --from-index
+    #[arg(long = "index", requires = "from_index")]
+    index_path: Option<PathBuf>,
+    /// Accept a keyless signature whose identity matches this regular expression (unanchored, as in cosign)
+    #[arg(
+        long = "identity-regexp",
+        requires = "oidc_issuer",
+        conflicts_with_all = ["publisher", "policy", "onchain_policy", "cache"]
+    )]
+    identity_regexp: Option<regex::Regex>,
+    /// Issuer the keyless identity must come from (e.g., https://token.actions.githubusercontent.com)
+    #[arg(long = "oidc-issuer", requires = "identity_regexp")]
+    oidc_issuer: Option<String>,
+    /// Attester key trusted to bind identities (repeatable; overrides config identity_attesters)
+    #[arg(long = "attester", requires = "identity_regexp")]
+    attesters: Vec<Pubkey>,
+}
+
+#[derive(Debug, Args)]
+struct RevokeCmd {
+    /// Canonical image reference with digest (e.g., ghcr.io/username/repo@sha256:abc123...)
+    image_reference: String,
+}
+
+#[derive(Debug, Args)]
+struct WatchCmd {
+    /// Subscribe over websocket (logsSubscribe) instead of polling
+    #[arg(long = "websocket")]
+    websocket: bool,
+    /// Seconds between polls of getSignaturesForAddress
+    #[arg(long = "poll-interval", default_value_t = 5)]
+    poll_interval: u64,
+    /// Replay the last N program transactions before following new ones (polling only)
+    #[arg(long = "backfill", default_value_t = 0)]
+    backfill: usize,
+    /// Expected signer/owner key (base58), repeatable; events by other keys get "alert": true
+    #[arg(long = "expect-signer")]
+    expected_signers: Vec<Pubkey>,
+    /// Drop verification cache entries affected by observed events (cache_path or default store)
+    #[arg(long = "invalidate-cache")]
+    invalidate_cache: bool,
+}
+
+impl IndexOpts {
+    fn path(&self) -> PathBuf {
+        self.index_path.as_deref().map(expand_tilde).unwrap_or_else(default_index_path)
+    }
+}
+
+fn print_indexed_signature(signature: &IndexedSignature) {
+    let state = match (signature.revoked_at, signature.not_after) {
+        (Some(revoked_at), _) => format!("revoked {}", skelz::format_timestamp(revoked_at)),
+        (None, Some(not_after)) => format!("expires {}", skelz::format_timestamp(not_after)),
+        (None, None) => "active".to_string(),
+    };
+    println!(
+        "{}  signer={}  slot={}  signed={}  {}",
+        signature.digest,
+        signature.signer,
+        signature.slot,
+        skelz::format_timestamp(signature.created_at),
+        state
+    );
+}
+
+fn print_sign_plan(plan: &SignPlan) -> Result<()> {
+    const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+    println!("Dry run: no transaction sent, no artifact attached");
+    println!("   - Digest: {}", plan.digest);
+    println!("   - Signer: {}", plan.signer);
+    if plan.fee_payer != plan.signer {
+        println!("   - Fee payer: {}", plan.fee_payer);
+    }
+    println!("   - PDA: {}", plan.pda);
+    match &plan.existing_signer {
+        Some(signer) => println!("   - PDA exists: yes, signed by {}", signer),
+        None => println!("   - PDA exists: no"),
+    }
+    match &plan.simulation_error {
+        None => println!(
+            "   - Simulation: ok ({} compute units)",
+            plan.units_consumed.map(|u| u.to_string()).unwrap_or_else(|| "?".to_string())
+        ),
+        Some(err) => {
+            println!("   - Simulation: FAILED: {}", err);
+            for line in &plan.simulation_logs {
+                println!("       {}", line);
+            }
+        }
+    }
+    println!("   - Compute unit limit: {}", plan.compute_unit_limit);
+    match plan.priority_fee {
+        Some(price) => println!(
+            "   - Priority fee: {} micro-lamports/CU ({} lamports)",
+            price,
+            priority_fee_lamports(plan.compute_unit_limit, price)
+        ),
+        None => println!("   - Priority fee: none"),
+    }
+    println!("   - Transaction fee: {} lamports", plan.fee_lamports);
+    println!("   - Rent (signature account): {} lamports", plan.rent_lamports);
+    println!(
+        "   - Total cost: {:.9} SOL",
+        (plan.fee_lamports + plan.rent_lamports) as f64 / LAMPORTS_PER_SOL
+    );
+    println!("Artifact manifest that would be attached:");
+    println!("{}", serde_json::to_string_pretty(&plan.manifest)?);
+    Ok(())
 }
 
 fn init_tracing(verbosity: u8) {
@@ -125,27 +813,36 @@ fn init_tracing(verbosity: u8) {
         _ => Level::TRACE,
     };
     let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new(level.as_str().to_string()));
+        .unwrap_or_else(|_| EnvFilter::new(level.as_str()));
     tracing_subscriber::fmt().with_env_filter(filter).init();
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     init_tracing(cli.verbose);
+    let flags = cli.config;
+    let overrides = ConfigOverrides { profile: cli.profile, values: flags.values() };
 
     match cli.command {
+        Commands::Doctor => {
+            let checks = run_doctor(&overrides);
+            for check in &checks {
+                println!("{}", check);
+            }
+            let failed = checks.iter().filter(|c| c.status == CheckStatus::Fail).count();
+            if failed > 0 {
+                anyhow::bail!("{} check(s) failed", failed);
+            }
+            Ok(())
+        }
         Commands::Config(cmd) => match cmd {
             ConfigCommand::Init(cmd) => {
                 let mut cfg = SkelzConfig::default();
-                if let Some(cluster) = cmd.cluster.as_deref() {
-                    cfg.cluster = cluster.to_string();
-                    cfg.rpc_url = default_cluster_rpc_url(cluster);
-                }
-                if let Some(rpc) = cmd.rpc_url.as_deref() {
-                    cfg.rpc_url = rpc.to_string();
+                for (key, value) in &overrides.values {
+                    init_config_value(&mut cfg, key, value)?;
                 }
-                if let Some(path) = cmd.keypair_path.as_deref() {
-                    cfg.keypair_path = expand_tilde(path);
+                if flags.rpc_url.is_none() {
+                    cfg.rpc_url = default_cluster_rpc_url(&cfg.cluster);
                 }
 
                 let output_path = cmd
@@ -165,11 +862,14 @@ fn main() -> Result<()> {
                 Ok(())
             }
             ConfigCommand::Get(cmd) => {
-                let cfg = skelz::read_config_file().or_else(|_| {
-                    let cfg = SkelzConfig::default();
-                    save_default_config(&cfg).ok();
-                    Ok::<SkelzConfig, anyhow::Error>(cfg)
-                })?;
+                if !default_config_file_path().exists() {
+                    save_default_config(&SkelzConfig::default()).ok();
+                }
+                let cfg = skelz::read_config_file_or_default()?;
+                let cfg = match selected_profile(overrides.profile.as_deref(), &cfg) {
+                    Some(name) => apply_profile(&cfg, &name)?,
+                    None => cfg,
+                };
                 if let Some(key) = cmd.key.as_deref() {
                     let value = get_config_value(&cfg, key)?;
                     println!("{}", value);
@@ -180,53 +880,619 @@ fn main() -> Result<()> {
                 Ok(())
             }
             ConfigCommand::Set(cmd) => {
-                let mut cfg = skelz::read_config_file().unwrap_or_default();
-                set_config_value(&mut cfg, &cmd.key, &cmd.value)?;
-                save_default_config(&cfg)?;
-                println!("updated {}", cmd.key);
+                let mut cfg = skelz::read_config_file_or_default()?;
+                match selected_profile(overrides.profile.as_deref(), &cfg) {
+                    Some(name) => {
+                        set_profile_value(&mut cfg, &name, &cmd.key, &cmd.value)?;
+                        save_default_config(&cfg)?;
+                        println!("updated {} in profile {}", cmd.key, name);
+                    }
+                    None => {
+                        set_config_value(&mut cfg, &cmd.key, &cmd.value)?;
+                        save_default_config(&cfg)?;
+                        println!("updated {}", cmd.key);
+                    }
+                }
+                Ok(())
+            }
+            ConfigCommand::Explain(cmd) => {
+                let resolved = resolve_config(&overrides)?;
+                let keys: Vec<&str> = match cmd.key.as_deref() {
+                    Some(key) if CONFIG_KEYS.contains(&key) => vec![key],
+                    Some(key) => return Err(skelz::SkelzError::UnknownConfigKey(key.to_string()).into()),
+                    None => CONFIG_KEYS.to_vec(),
+                };
+                if let Some(name) = &resolved.profile {
+                    println!("Profile: {}", name);
+                }
+                for key in keys {
+                    let value = get_config_value(&resolved.config, key)?;
+                    let source = &resolved.sources[key];
+                    println!("{:<20} {:<48} {}", key, value, source);
+                    if cmd.key.is_some() {
+                        println!("   - Environment variable: {}", config_env_var(key));
+                        println!("   - Flag: {}", config_flag(key));
+                    }
+                }
+                Ok(())
+            }
+            ConfigCommand::Profiles(cmd) => {
+                let mut cfg = skelz::read_config_file_or_default()?;
+                match cmd {
+                    ProfilesCommand::List => {
+                        let selected = selected_profile(overrides.profile.as_deref(), &cfg);
+                        if cfg.profiles.is_empty() {
+                            println!("No profiles in {}", default_config_file_path().display());
+                        }
+                        for name in cfg.profiles.keys() {
+                            let marker = if selected.as_deref() == Some(name.as_str()) { "*" } else { " " };
+                            let effective = apply_profile(&cfg, name)?;
+                            println!("{} {} ({}, {})", marker, name, effective.cluster, effective.rpc_url);
+                        }
+                    }
+                    ProfilesCommand::Use(cmd) => {
+                        if let Some(name) = &cmd.name {
+                            if !cfg.profiles.contains_key(name) {
+                                return Err(skelz::SkelzError::UnknownProfile(name.clone()).into());
+                            }
+                        }
+                        cfg.default_profile = cmd.name.clone();
+                        save_default_config(&cfg)?;
+                        match cmd.name {
+                            Some(name) => println!("Using profile {}", name),
+                            None => println!("Using the top-level settings"),
+                        }
+                    }
+                    ProfilesCommand::Copy(cmd) => {
+                        copy_profile(&mut cfg, &cmd.from, &cmd.to, cmd.force)?;
+                        save_default_config(&cfg)?;
+                        println!("Copied profile {} to {}", cmd.from, cmd.to);
+                    }
+                }
                 Ok(())
             }
         },
         Commands::Sign(cmd) => {
-            let config = load_config_with_overrides(cmd.rpc_url.clone(), cmd.keypair_path.clone())?;
+            let config = load_config_with_overrides(&overrides)?;
             
             // Validate canonical reference format
             if !cmd.image_reference.contains("@sha256:") {
                 return Err(anyhow::anyhow!("Image reference must be canonical with digest (e.g., ghcr.io/username/repo@sha256:abc123...)"));
             }
             
-            // Validate GHCR reference
-            if !cmd.image_reference.starts_with("ghcr.io") {
-                return Err(anyhow::anyhow!("Only GitHub Container Registry is supported. Use format: ghcr.io/username/repo@sha256:abc123..."));
+            let not_after = cmd
+                .valid_for
+                .as_deref()
+                .map(parse_validity_duration)
+                .transpose()?
+                .map(|validity| (chrono::Utc::now() + validity).timestamp());
+            if flags.relay_url.is_some() && (flags.fee_payer.is_some() || cmd.prepare.is_some()) {
+                anyhow::bail!("--relay-url cannot be used with --fee-payer or --prepare");
+            }
+            let priority_fee: Option<PriorityFee> = config.priority_fee.as_deref().map(str::parse).transpose()?;
+            let mut options = SignOptions {
+                not_after,
+                priority_fee,
+                compute_unit_limit: config.compute_unit_limit,
+                nonce: None,
+                fee_payer: None,
+                relay_url: config.relay_url.clone(),
+            };
+            
+            if cmd.keyless {
+                let attester_url = config
+                    .attester_url
+                    .clone()
+                    .ok_or_else(|| anyhow::anyhow!("--keyless needs --attester-url or attester_url in config"))?;
+                let options = SignOptions { relay_url: None, ..options };
+                let credential = resolve_image_credentials(&config, &cmd.image_reference)?;
+                let outcome = sign_keyless(&cmd.image_reference, &config, &attester_url, cmd.oidc_token.as_deref(), &options)?;
+                skelz::attach_signature_proof(&cmd.image_reference, &outcome.signature, &options, credential.as_ref())?;
+                println!("Identity={}", outcome.identity);
+                println!("Ephemeral signer={}", outcome.signer);
+                println!("Image Signature={}", outcome.signature);
+                println!("Proof attached to: {}", cmd.image_reference);
+                return Ok(());
+            }
+            
+            let fee_payer = config.fee_payer.clone();
+            if cmd.prepare.is_none() {
+                options.fee_payer = fee_payer.as_deref().map(SkelzSigner::load).transpose()?.map(Rc::new);
             }
             
-            // Resolve GHCR authentication credentials from config
-            let (username, token) = resolve_ghcr_credentials(&config)?;
+            if cmd.dry_run {
+                let plan = plan_image_signature(&cmd.image_reference, &config, &options)?;
+                print_sign_plan(&plan)?;
+                return Ok(());
+            }
+            
+            let signer = || match cmd.signer {
+                Some(signer) => Ok(signer),
+                None => Ok(skelz::load_config_signer(&config)?.pubkey()),
+            };
+            options.nonce = resolve_nonce(&config, None, None, signer)?;
+            if let Some(nonce) = &options.nonce {
+                info!(account = %nonce.account, authority = %nonce.authority, "using durable nonce");
+            }
+            
+            if let Some(path) = cmd.prepare.as_deref() {
+                // A public key is enough to name the fee payer of a prepared transaction
+                let fee_payer = match fee_payer.as_deref() {
+                    Some(location) => Some(match location.parse::<Pubkey>() {
+                        Ok(pubkey) => pubkey,
+                        Err(_) => SkelzSigner::load(location)?.pubkey(),
+                    }),
+                    None => None,
+                };
+                let prepared = prepare_signing_transaction(&cmd.image_reference, &config, &signer()?, fee_payer.as_ref(), &options)?;
+                let path = expand_tilde(path);
+                prepared.save(&path)?;
+                println!("{}", prepared.describe());
+                println!("Prepared transaction written to {}", path.display());
+                println!("Next: skelz tx sign {} --keypair <KEYPAIR> (offline), then skelz tx submit", path.display());
+                return Ok(());
+            }
+            
+            // Resolve credentials for the image's registry
+            let credential = resolve_image_credentials(&config, &cmd.image_reference)?;
             
             // Sign image and upload to OCI registry
-            let signature = sign_image_with_oci(&cmd.image_reference, &config, &username, &token)?;
+            let signature = sign_image_with_oci(&cmd.image_reference, &config, credential.as_ref(), &options)?;
             
-            info!(%signature, "image signed and uploaded to GHCR");
+            info!(%signature, "image signed and proof attached");
             println!("Image Signature={}", signature);
-            println!("Artifact uploaded to GHCR: {}", cmd.image_reference);
+            println!("Proof attached to: {}", cmd.image_reference);
             Ok(())
         }
         Commands::Verify(cmd) => {
             // Load config with overrides
-            let config = load_config_with_overrides(cmd.rpc_url.clone(), None)?;
+            let config = load_config_with_overrides(&overrides)?;
+            
+            // Credentials for the image's registry; public images are read anonymously
+            let credential = resolve_image_credentials(&config, &cmd.image_reference)?;
             
-            // Resolve GHCR authentication credentials from config
-            let (username, token) = resolve_ghcr_credentials(&config)?;
+            let options = VerifyOptions { allow_expired: cmd.allow_expired };
             
             // Verify complete image signature using PDA-based system
-            verify_image_signature(&cmd.image_reference, &cmd.expected_signer, &config, &username, &token)?;
+            if let Some(path) = cmd.policy.as_deref() {
+                let policy = Policy::load(&expand_tilde(path))?;
+                let decision = verify_image_with_policy(&cmd.image_reference, &policy, &config, credential.as_ref())?;
+                if !decision.allow {
+                    anyhow::bail!("image denied by policy {}", path.display());
+                }
+            } else if let Some(spec) = cmd.onchain_policy.as_deref() {
+                let (owner, name) = spec
+                    .split_once('/')
+                    .ok_or_else(|| anyhow::anyhow!("--onchain-policy expects <OWNER>/<NAME>"))?;
+                let owner: Pubkey = owner.parse().map_err(|e| anyhow::anyhow!("invalid policy owner {}: {}", owner, e))?;
+                let decision = verify_image_with_onchain_policy(&cmd.image_reference, &owner, name, &config, credential.as_ref())?;
+                if !decision.allow {
+                    anyhow::bail!("image denied by on-chain policy {}", spec);
+                }
+            } else if let Some(identity_regexp) = cmd.identity_regexp.clone() {
+                let attesters = if cmd.attesters.is_empty() {
+                    config
+                        .identity_attesters
+                        .iter()
+                        .map(|key| key.parse().map_err(|e| anyhow::anyhow!("invalid identity attester {}: {}", key, e)))
+                        .collect::<Result<Vec<Pubkey>>>()?
+                } else {
+                    cmd.attesters.clone()
+                };
+                let policy = IdentityPolicy {
+                    oidc_issuer: cmd.oidc_issuer.clone().unwrap_or_default(),
+                    identity_regexp,
+                    attesters,
+                };
+                verify_image_keyless(&cmd.image_reference, &config, &policy, &options)?;
+            } else if cmd.cache {
+                let mut settings = CacheSettings::from_config(&config)?;
+                settings.path.get_or_insert_with(default_cache_path);
+                let mut cache = VerificationCache::new(settings)?;
+                let program = skelz::load_skelz_program(&config)?;
+                let digest = skelz::extract_digest_from_reference(&cmd.image_reference)?;
+                let trusted = match (cmd.publisher.as_deref(), cmd.expected_signer.as_deref()) {
+                    (Some(publisher), _) => skelz::resolve_publisher_signers(&program, publisher, &cmd.image_reference)?,
+                    (None, Some(signer)) => vec![signer.parse().map_err(|e| anyhow::anyhow!("invalid signer {}: {}", signer, e))?],
+                    (None, None) => unreachable!("clap requires --signer or --publisher"),
+                };
+                verify_signature_cached(&mut cache, &program, &config.cluster, &digest, &trusted, &options)?;
+            } else if let Some(publisher) = cmd.publisher.as_deref() {
+                verify_image_signature_for_publisher(&cmd.image_reference, publisher, &config, &options)?;
+            } else if let Some(expected_signer) = cmd.expected_signer.as_deref().filter(|_| cmd.from_index) {
+                let index_path = cmd.index_path.as_deref().map(expand_tilde).unwrap_or_else(default_index_path);
+                verify_image_signature_from_index(&cmd.image_reference, expected_signer, &config, &index_path, &options)?;
+            } else if let Some(expected_signer) = cmd.expected_signer.as_deref() {
+                verify_image_signature(&cmd.image_reference, expected_signer, &config, &options)?;
+            }
             
             Ok(())
         }
+        Commands::Revoke(cmd) => {
+            let config = load_config_with_overrides(&overrides)?;
+            let signature = skelz::revoke_signature(&config, &cmd.image_reference)?;
+            println!("Revoked signature of {}\nSignature={}", cmd.image_reference, signature);
+            Ok(())
+        }
+        Commands::Publisher(cmd) => match cmd {
+            PublisherCommand::Register(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let params = PublisherParams {
+                    namespace: cmd.namespace,
+                    display_name: cmd.display_name,
+                    contact: cmd.contact,
+                    keys: cmd.keys,
+                };
+                let signature = skelz::register_publisher(&config, &cmd.name, &params)?;
+                println!("Registered publisher '{}'\nSignature={}", cmd.name, signature);
+                Ok(())
+            }
+            PublisherCommand::Update(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let program = skelz::load_skelz_program(&config)?;
+                let current = skelz::fetch_publisher(&program, &cmd.name)?;
+                let params = PublisherParams {
+                    namespace: cmd.namespace.unwrap_or(current.namespace),
+                    display_name: cmd.display_name.unwrap_or(current.display_name),
+                    contact: cmd.contact.unwrap_or(current.contact),
+                    keys: if cmd.keys.is_empty() { current.keys } else { cmd.keys },
+                };
+                let signature = skelz::update_publisher(&config, &cmd.name, &params)?;
+                println!("Updated publisher '{}'\nSignature={}", cmd.name, signature);
+                Ok(())
+            }
+            PublisherCommand::Show(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let program = skelz::load_skelz_program(&config)?;
+                let publisher = skelz::fetch_publisher(&program, &cmd.name)?;
+                println!("name={}", publisher.name);
+                println!("display_name={}", publisher.display_name);
+                println!("contact={}", publisher.contact);
+                println!("namespace={}", publisher.namespace);
+                println!("authority={}", publisher.authority);
+                for key in &publisher.keys {
+                    println!("key={}", key);
+                }
+                Ok(())
+            }
+        },
+        Commands::Policy(cmd) => match cmd {
+            PolicyCommand::Push(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let params = OnchainPolicyParams {
+                    repository: cmd.repository,
+                    signers: cmd.signers,
+                    threshold: cmd.threshold,
+                };
+                let (signature, created) = skelz::policy::push_onchain_policy(&config, &cmd.name, &params)?;
+                let action = if created { "Created" } else { "Updated" };
+                println!("{} policy '{}'\nSignature={}", action, cmd.name, signature);
+                Ok(())
+            }
+            PolicyCommand::Show(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let program = skelz::load_skelz_program(&config)?;
+                let owner = cmd.owner.unwrap_or_else(|| program.payer());
+                let policy = fetch_onchain_policy(&program, &owner, &cmd.name)?
+                    .ok_or_else(|| anyhow::anyhow!("policy {}/{} not found", owner, cmd.name))?;
+                println!("owner={}", policy.owner);
+                println!("name={}", policy.name);
+                println!("repository={}", policy.repository);
+                println!("threshold={}", policy.threshold);
+                for signer in &policy.signers {
+                    println!("signer={}", signer);
+                }
+                println!("version={}", policy.version);
+                println!("frozen={}", policy.frozen);
+                println!("updated_slot={}", policy.updated_slot);
+                Ok(())
+            }
+            PolicyCommand::Freeze(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let signature = skelz::policy::freeze_onchain_policy(&config, &cmd.name)?;
+                println!("Froze policy '{}'\nSignature={}", cmd.name, signature);
+                Ok(())
+            }
+        },
+        Commands::Watch(cmd) => {
+            let config = load_config_with_overrides(&overrides)?;
+            let options = WatchOptions {
+                expected_signers: cmd.expected_signers,
+                websocket: cmd.websocket,
+                poll_interval: std::time::Duration::from_secs(cmd.poll_interval),
+                backfill: cmd.backfill,
+            };
+            let mut cache = if cmd.invalidate_cache {
+                let mut settings = CacheSettings::from_config(&config)?;
+                settings.path.get_or_insert_with(default_cache_path);
+                Some(VerificationCache::new(settings)?)
+            } else {
+                None
+            };
+            watch_events(&config, &options, |record| {
+                use std::io::Write as _;
+                if let Some(cache) = cache.as_mut() {
+                    cache.apply_event(&record.event)?;
+                }
+                let mut stdout = std::io::stdout().lock();
+                writeln!(stdout, "{}", serde_json::to_string(record)?)?;
+                stdout.flush()?;
+                Ok(())
+            })
+        }
+        Commands::Index(cmd) => match cmd {
+            IndexCommand::Sync(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let index = SignatureIndex::open(&cmd.index.path())?;
+                let report = sync_index(&config, &index)?;
+                println!(
+                    "Index synced: {} accounts backfilled, {} transactions, {} events",
+                    report.accounts, report.transactions, report.events
+                );
+                if let Some(slot) = report.last_slot {
+                    println!("Last program slot: {}", slot);
+                }
+                Ok(())
+            }
+            IndexCommand::Status(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let path = cmd.index.path();
+                let status = SignatureIndex::open(&path)?.status()?;
+                let now = chrono::Utc::now().timestamp();
+                println!("Index: {}", path.display());
+                println!("Cluster: {}", status.cluster.as_deref().unwrap_or("(unbound)"));
+                println!("Signatures: {}", status.signatures);
+                println!("Rotations: {}", status.rotations);
+                println!("Freshness: {}", status.describe(now));
+                // Best effort: the lag is informative only
+                let rpc = solana_client::rpc_client::RpcClient::new(config.rpc_url.clone());
+                match (status.last_slot, rpc.get_slot()) {
+                    (Some(last_slot), Ok(current)) => {
+                        println!("Cluster slot: {} ({} slots ahead of the last indexed transaction)", current, current.saturating_sub(last_slot))
+                    }
+                    (_, Err(e)) => println!("Cluster slot: unavailable ({})", e),
+                    (None, Ok(current)) => println!("Cluster slot: {}", current),
+                }
+                Ok(())
+            }
+            IndexCommand::Signer(cmd) => {
+                let index = SignatureIndex::open(&cmd.index.path())?;
+                let signatures = index.signatures_by_signer(&cmd.signer)?;
+                for signature in &signatures {
+                    print_indexed_signature(signature);
+                }
+                println!("{} signature(s) by {}", signatures.len(), cmd.signer);
+                println!("Index: {}", index.status()?.describe(chrono::Utc::now().timestamp()));
+                Ok(())
+            }
+            IndexCommand::Digest(cmd) => {
+                let digest = if cmd.digest.contains('@') {
+                    skelz::extract_digest_from_reference(&cmd.digest)?
+                } else {
+                    cmd.digest.clone()
+                };
+                let index = SignatureIndex::open(&cmd.index.path())?;
+                match index.signature(&digest)? {
+                    Some(signature) => print_indexed_signature(&signature),
+                    None => println!("{}: not in index", digest),
+                }
+                println!("Index: {}", index.status()?.describe(chrono::Utc::now().timestamp()));
+                Ok(())
+            }
+        },
+        Commands::Relay(cmd) => {
+            let config = load_config_with_overrides(&overrides)?;
+            let quota_window = parse_validity_duration(&cmd.quota_window)?
+                .to_std()
+                .map_err(|_| anyhow::anyhow!("--quota-window must be positive"))?;
+            let policy = RelayPolicy {
+                quota: cmd.quota,
+                quota_window,
+                spend_limit: cmd.spend_limit,
+                allowed_signers: cmd.allowed_signers,
+                allow_any_signer: cmd.allow_any_signer,
+                max_priority_fee: cmd.max_priority_fee,
+                max_compute_unit_limit: cmd.max_compute_unit_limit,
+            };
+            let fee_payer = skelz::load_config_signer(&config)?;
+            println!("Relay fee payer: {}", fee_payer.pubkey());
+            let listener = std::net::TcpListener::bind(&cmd.listen)
+                .map_err(|e| anyhow::anyhow!("listen on {}: {}", cmd.listen, e))?;
+            println!("Listening on http://{}", listener.local_addr()?);
+            Relay::new(&config, fee_payer, policy)?.serve(listener)
+        }
+        Commands::Attester(cmd) => {
+            let config = load_config_with_overrides(&overrides)?;
+            let mut verifier = OidcVerifier::new(&cmd.issuer, &cmd.audience)?;
+            if let Some(url) = cmd.jwks_url.as_deref() {
+                verifier = verifier.with_jwks_url(url);
+            }
+            let policy = AttesterPolicy {
+                identity_regexp: cmd.allow_identity_regexp,
+                max_priority_fee: cmd.max_priority_fee,
+                max_compute_unit_limit: cmd.max_compute_unit_limit,
+            };
+            let key = skelz::load_config_signer(&config)?;
+            println!("Attester key: {}", key.pubkey());
+            println!("Issuer: {} (audience {})", cmd.issuer, cmd.audience);
+            let listener = std::net::TcpListener::bind(&cmd.listen)
+                .map_err(|e| anyhow::anyhow!("listen on {}: {}", cmd.listen, e))?;
+            println!("Listening on http://{}", listener.local_addr()?);
+            Attester::new(&config, key, verifier, policy)?.serve(listener)
+        }
+        Commands::Nonce(cmd) => match cmd {
+            NonceCommand::Create(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let created = create_nonce_account(&config, cmd.authority)?;
+                println!("Nonce account: {}", created.nonce.account);
+                println!("Authority: {}", created.nonce.authority);
+                println!("Balance: {} lamports (rent-exempt)", created.lamports);
+                println!("Transaction: {}", created.signature);
+                if cmd.save {
+                    let mut file = skelz::read_config_file_or_default()?;
+                    let account = created.nonce.account.to_string();
+                    let authority = created.nonce.authority.to_string();
+                    match selected_profile(overrides.profile.as_deref(), &file) {
+                        Some(name) => {
+                            set_profile_value(&mut file, &name, "nonce_account", &account)?;
+                            set_profile_value(&mut file, &name, "nonce_authority", &authority)?;
+                        }
+                        None => {
+                            set_config_value(&mut file, "nonce_account", &account)?;
+                            set_config_value(&mut file, "nonce_authority", &authority)?;
+                        }
+                    }
+                    save_default_config(&file)?;
+                    println!("Saved as nonce_account/nonce_authority in {}", default_config_file_path().display());
+                }
+                Ok(())
+            }
+        },
+        Commands::Tx(cmd) => match cmd {
+            TxCommand::Sign(cmd) => {
+                let path = expand_tilde(&cmd.file);
+                let mut prepared = PreparedTransaction::load(&path)?;
+                let keypair = skelz::load_config_signer(&load_config_with_overrides(&overrides)?)?;
+                println!("{}", prepared.describe());
+                let missing = sign_prepared_transaction(&mut prepared, &keypair)?;
+                let output = cmd.output.as_deref().map(expand_tilde).unwrap_or(path);
+                prepared.save(&output)?;
+                println!("Signed by {}; written to {}", keypair.pubkey(), output.display());
+                if missing.is_empty() {
+                    println!("All signatures present; run skelz tx submit {}", output.display());
+                } else {
+                    for key in missing {
+                        println!("Still missing signature from {}", key);
+                    }
+                }
+                Ok(())
+            }
+            TxCommand::Submit(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let prepared = PreparedTransaction::load(&expand_tilde(&cmd.file))?;
+                let signature = submit_prepared_transaction(&prepared, &config)?;
+                println!("Image Signature={}", signature);
+                if cmd.skip_attach {
+                    return Ok(());
+                }
+                let credential = resolve_image_credentials(&config, &prepared.image_reference)?;
+                let options = SignOptions { not_after: prepared.not_after, ..SignOptions::default() };
+                skelz::attach_signature_proof(&prepared.image_reference, &signature, &options, credential.as_ref())?;
+                println!("Proof attached to: {}", prepared.image_reference);
+                Ok(())
+            }
+        },
+        Commands::Key(cmd) => match cmd {
+            KeyCommand::Rotate(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let new_key = SkelzSigner::load(&cmd.new_keypair)?;
+                let signature = skelz::rotate_signing_key(&config, &new_key, cmd.effective_slot)?;
+                println!("Rotated signing key to {}\nSignature={}", new_key.pubkey(), signature);
+                Ok(())
+            }
+            KeyCommand::Show(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let program = skelz::load_skelz_program(&config)?;
+                let mut key = cmd.key;
+                let mut seen = vec![key];
+                println!("{}", key);
+                while let Some(rotation) = skelz::fetch_rotation(&program, &key)? {
+                    println!(
+                        "  -> {} (effective at slot {}, recorded at slot {})",
+                        rotation.new_key, rotation.effective_slot, rotation.created_slot
+                    );
+                    key = rotation.new_key;
+                    if seen.contains(&key) {
+                        break;
+                    }
+                    seen.push(key);
+                }
+                Ok(())
+            }
+        },
+        Commands::Multisig(cmd) => match cmd {
+            MultisigCommand::Create(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let signature = multisig::create_multisig(&config, &cmd.members, cmd.threshold)?;
+                println!(
+                    "Created {}-of-{} multisig; revocations now need proposals\nSignature={}",
+                    cmd.threshold,
+                    cmd.members.len(),
+                    signature
+                );
+                Ok(())
+            }
+            MultisigCommand::Show(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let program = skelz::load_skelz_program(&config)?;
+                let signer = match cmd.signer {
+                    Some(signer) => signer,
+                    None => program.payer(),
+                };
+                let account = multisig::fetch_multisig(&program, &signer)?
+                    .ok_or_else(|| anyhow::anyhow!("{} is not governed by a multisig", signer))?;
+                println!("Multisig {}", multisig::multisig_pda(&program.id(), &signer));
+                println!("  signer: {}", account.signer);
+                println!("  threshold: {} of {}", account.threshold, account.members.len());
+                for member in &account.members {
+                    println!("  member: {}", member);
+                }
+                for co_signer in &account.co_signers {
+                    println!("  co-signer: {} (since slot {})", co_signer.key, co_signer.added_slot);
+                }
+                for proposal in multisig::list_proposals(&program, &signer, &account)? {
+                    let status = if proposal.executed { "executed" } else { "open" };
+                    println!(
+                        "  proposal {}: {} [{}, {}/{} approvals, by {}]",
+                        proposal.index,
+                        proposal.action,
+                        status,
+                        proposal.approvals.len(),
+                        account.threshold,
+                        proposal.proposer
+                    );
+                }
+                Ok(())
+            }
+            MultisigCommand::Propose(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let action = match &cmd.action {
+                    MultisigActionCmd::Revoke { image_reference } => multisig::revoke_action(image_reference)?,
+                    MultisigActionCmd::AddCoSigner { key } => MultisigAction::AddCoSigner { key: *key },
+                    MultisigActionCmd::RemoveCoSigner { key } => MultisigAction::RemoveCoSigner { key: *key },
+                };
+                let description = action.to_string();
+                let (signature, index) = multisig::propose(&config, &cmd.signer, action)?;
+                println!("Proposal {}: {}\nSignature={}", index, description, signature);
+                println!("Next: skelz multisig approve {} --signer {} (other members)", index, cmd.signer);
+                Ok(())
+            }
+            MultisigCommand::Approve(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let signature = multisig::approve(&config, &cmd.signer, cmd.index)?;
+                println!("Approved proposal {}\nSignature={}", cmd.index, signature);
+                Ok(())
+            }
+            MultisigCommand::Execute(cmd) => {
+                let config = load_config_with_overrides(&overrides)?;
+                let signature = multisig::execute(&config, &cmd.signer, cmd.index)?;
+                println!("Executed proposal {}\nSignature={}", cmd.index, signature);
+                Ok(())
+            }
+        },
         Commands::Registry(cmd) => match cmd {
             RegistryCommand::Login(cmd) => {
-                let cfg = skelz::read_config_file().unwrap_or_default();
-                let (mut login, pass) = resolve_ghcr_credentials(&cfg)?;
+                let cfg = load_config_with_overrides(&overrides)?;
+                let credentials::Credential { username: mut login, secret: pass } = resolve_registry_credentials(&cfg, &cmd.registry)?
+                    .ok_or_else(|| {
+                        let env = if credentials::registry_host(&cmd.registry) == skelz::GHCR_REGISTRY { "set GHCR_USER/GHCR_TOKEN or " } else { "" };
+                        anyhow::anyhow!(
+                            "no credentials found for {}; {}run `skelz registry store --registry {}`",
+                            cmd.registry,
+                            env,
+                            cmd.registry
+                        )
+                    })?;
                 if let Some(user_override) = cmd.username.as_deref() {
                     login = user_override.to_string();
                 }
@@ -234,7 +1500,7 @@ fn main() -> Result<()> {
                 // Non-interactive docker login: pass via stdin
                 let mut child = std::process::Command::new("docker")
                     .arg("login")
-                    .arg(cmd.registry)
+                    .arg(&cmd.registry)
                     .arg("-u")
                     .arg(login)
                     .arg("--password-stdin")
@@ -251,7 +1517,43 @@ fn main() -> Result<()> {
                 if !status.success() {
                     anyhow::bail!("docker login failed with status {}", status);
                 }
-                println!("ghcr login: success");
+                println!("{} login: success", cmd.registry);
+                Ok(())
+            }
+            RegistryCommand::Store(cmd) => {
+                let secret = if cmd.password_stdin {
+                    let mut secret = String::new();
+                    std::io::stdin().read_line(&mut secret)?;
+                    secret.trim_end_matches(['\r', '\n']).to_string()
+                } else {
+                    rpassword::prompt_password(format!("Token for {}: ", cmd.registry))?
+                };
+                if secret.is_empty() {
+                    anyhow::bail!("empty token");
+                }
+                let path = credentials::credential_store_path();
+                let mut store = credentials::CredentialStore::open(&path, &credentials::store_passphrase()?)?;
+                store.set(&cmd.registry, credentials::Credential { username: cmd.username, secret });
+                store.save()?;
+                println!("Stored credentials for {} in {}", credentials::registry_host(&cmd.registry), path.display());
+                Ok(())
+            }
+            RegistryCommand::Remove(cmd) => {
+                let mut store = credentials::CredentialStore::open_default()?
+                    .ok_or_else(|| anyhow::anyhow!("no credential store at {}", credentials::credential_store_path().display()))?;
+                if !store.remove(&cmd.registry) {
+                    anyhow::bail!("no credentials stored for {}", cmd.registry);
+                }
+                store.save()?;
+                println!("Removed credentials for {}", credentials::registry_host(&cmd.registry));
+                Ok(())
+            }
+            RegistryCommand::List => {
+                if let Some(store) = credentials::CredentialStore::open_default()? {
+                    for (registry, username) in store.list() {
+                        println!("{}\t{}", registry, username);
+                    }
+                }
                 Ok(())
             }
         },
@@ -0,0 +1,233 @@
+//! Multisig authority over a signer's records.
+//!
+//! A signing key can hand the authority over its records to an m-of-n set of
+//! members with `create_multisig` (PDA `["multisig", signer]`). From then on
+//! the key alone can no longer revoke its signatures: a member proposes the
+//! action (PDA `["proposal", multisig, index]`), members approve it, and once
+//! `threshold` approvals are recorded any member executes it.
+//!
+//! Besides revocations, the multisig manages co-signing keys: keys trusted to
+//! sign on behalf of the signer. [`crate::verify_signature_with_signers`]
+//! accepts a co-signer's signature wherever the signer is trusted, provided it
+//! was made after the co-signer was added.
+
+use std::fmt;
+
+use anchor_client::solana_sdk::system_program;
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+use tracing::info;
+
+use crate::skelz::accounts::{Multisig, Proposal};
+use crate::skelz::client::{accounts, args};
+pub use crate::skelz::types::{CoSigner, MultisigAction};
+use crate::{
+    extract_digest_from_reference, fetch_optional_account, load_skelz_program, signature_pda, SkelzConfig,
+    SkelzProgram,
+};
+
+/// Upper bound on proposals listed by [`list_proposals`]
+const MAX_LISTED_PROPOSALS: u64 = 256;
+
+/// Derive the multisig PDA governing `signer`
+pub fn multisig_pda(program_id: &Pubkey, signer: &Pubkey) -> Pubkey {
+    let (pda, _bump) = Pubkey::find_program_address(&[b"multisig", signer.as_ref()], program_id);
+    pda
+}
+
+/// Derive the PDA of proposal `index` of a multisig
+pub fn proposal_pda(program_id: &Pubkey, multisig: &Pubkey, index: u64) -> Pubkey {
+    let (pda, _bump) =
+        Pubkey::find_program_address(&[b"proposal", multisig.as_ref(), &index.to_le_bytes()], program_id);
+    pda
+}
+
+/// Fetch the multisig governing `signer`, if there is one
+pub fn fetch_multisig(program: &SkelzProgram, signer: &Pubkey) -> Result<Option<Multisig>> {
+    fetch_optional_account(program, &multisig_pda(&program.id(), signer))
+}
+
+/// Fetch proposal `index` of the multisig governing `signer`
+pub fn fetch_proposal(program: &SkelzProgram, signer: &Pubkey, index: u64) -> Result<Option<Proposal>> {
+    let multisig = multisig_pda(&program.id(), signer);
+    fetch_optional_account(program, &proposal_pda(&program.id(), &multisig, index))
+}
+
+/// Proposals of the multisig governing `signer`, newest last
+pub fn list_proposals(program: &SkelzProgram, signer: &Pubkey, multisig: &Multisig) -> Result<Vec<Proposal>> {
+    let first = multisig.proposal_count.saturating_sub(MAX_LISTED_PROPOSALS);
+    let mut proposals = Vec::new();
+    for index in first..multisig.proposal_count {
+        if let Some(proposal) = fetch_proposal(program, signer, index)? {
+            proposals.push(proposal);
+        }
+    }
+    Ok(proposals)
+}
+
+impl fmt::Display for MultisigAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultisigAction::RevokeSignature { digest } => write!(f, "revoke signature of {}", digest),
+            MultisigAction::AddCoSigner { key } => write!(f, "add co-signer {}", key),
+            MultisigAction::RemoveCoSigner { key } => write!(f, "remove co-signer {}", key),
+        }
+    }
+}
+
+/// Action revoking the signature of an image
+pub fn revoke_action(image_reference: &str) -> Result<MultisigAction> {
+    Ok(MultisigAction::RevokeSignature { digest: extract_digest_from_reference(image_reference)? })
+}
+
+/// Co-signers of each trusted key that may have produced a signature at `signed_slot`
+///
+/// `fetch` returns the co-signers of the multisig governing a key (none without a
+/// multisig). Returns `(co_signer, trusted_key)` pairs.
+pub fn co_signers_of<F>(trusted_signers: &[Pubkey], signed_slot: u64, mut fetch: F) -> Result<Vec<(Pubkey, Pubkey)>>
+where
+    F: FnMut(&Pubkey) -> Result<Vec<CoSigner>>,
+{
+    let mut co_signers = Vec::new();
+    for trusted in trusted_signers {
+        co_signers.extend(
+            fetch(trusted)?
+                .iter()
+                .filter(|c| c.added_slot <= signed_slot)
+                .map(|c| (c.key, *trusted)),
+        );
+    }
+    Ok(co_signers)
+}
+
+/// Hand the authority over the configured key's records to `members`
+pub fn create_multisig(cfg: &SkelzConfig, members: &[Pubkey], threshold: u8) -> Result<String> {
+    let program = load_skelz_program(cfg)?;
+    let signer = program.payer();
+    let multisig = multisig_pda(&program.id(), &signer);
+    info!(%signer, %multisig, members = members.len(), threshold, "creating multisig");
+    let signature = program
+        .request()
+        .accounts(accounts::CreateMultisig { signer, multisig, system_program: system_program::ID })
+        .args(args::CreateMultisig { members: members.to_vec(), threshold })
+        .send()
+        .map_err(|e| anyhow!("create multisig for {}: {}", signer, e))?;
+    Ok(signature.to_string())
+}
+
+/// Propose `action` on the multisig governing `signer`; the configured key must be a member
+///
+/// Returns the transaction signature and the proposal index.
+pub fn propose(cfg: &SkelzConfig, signer: &Pubkey, action: MultisigAction) -> Result<(String, u64)> {
+    let program = load_skelz_program(cfg)?;
+    let current = fetch_multisig(&program, signer)?.ok_or_else(|| anyhow!("{} is not governed by a multisig", signer))?;
+    let proposer = program.payer();
+    if !current.members.contains(&proposer) {
+        anyhow::bail!("{} is not a member of the multisig governing {}", proposer, signer);
+    }
+    let multisig = multisig_pda(&program.id(), signer);
+    let index = current.proposal_count;
+    info!(%multisig, index, %action, "proposing");
+    let signature = program
+        .request()
+        .accounts(accounts::Propose {
+            proposer,
+            multisig,
+            proposal: proposal_pda(&program.id(), &multisig, index),
+            system_program: system_program::ID,
+        })
+        .args(args::Propose { action })
+        .send()
+        .map_err(|e| anyhow!("propose on multisig {}: {}", multisig, e))?;
+    Ok((signature.to_string(), index))
+}
+
+/// Approve proposal `index` with the configured key
+pub fn approve(cfg: &SkelzConfig, signer: &Pubkey, index: u64) -> Result<String> {
+    let program = load_skelz_program(cfg)?;
+    let multisig = multisig_pda(&program.id(), signer);
+    let signature = program
+        .request()
+        .accounts(accounts::Approve {
+            member: program.payer(),
+            multisig,
+            proposal: proposal_pda(&program.id(), &multisig, index),
+        })
+        .args(args::Approve {})
+        .send()
+        .map_err(|e| anyhow!("approve proposal {} of {}: {}", index, multisig, e))?;
+    Ok(signature.to_string())
+}
+
+/// Execute proposal `index` once it has enough approvals
+pub fn execute(cfg: &SkelzConfig, signer: &Pubkey, index: u64) -> Result<String> {
+    let program = load_skelz_program(cfg)?;
+    let current = fetch_multisig(&program, signer)?.ok_or_else(|| anyhow!("{} is not governed by a multisig", signer))?;
+    let proposal =
+        fetch_proposal(&program, signer, index)?.ok_or_else(|| anyhow!("proposal {} of {} not found", index, signer))?;
+    if proposal.executed {
+        anyhow::bail!("proposal {} was already executed", index);
+    }
+    let approvals = proposal.approvals.len();
+    if approvals < usize::from(current.threshold) {
+        anyhow::bail!("proposal {} has {} of {} approvals", index, approvals, current.threshold);
+    }
+
+    let multisig = multisig_pda(&program.id(), signer);
+    let signature_account = match &proposal.action {
+        MultisigAction::RevokeSignature { digest } => Some(signature_pda(&program.id(), digest)),
+        _ => None,
+    };
+    info!(%multisig, index, action = %proposal.action, "executing proposal");
+    let signature = program
+        .request()
+        .accounts(accounts::ExecuteProposal {
+            executor: program.payer(),
+            multisig,
+            proposal: proposal_pda(&program.id(), &multisig, index),
+            signature: signature_account,
+            system_program: system_program::ID,
+        })
+        .args(args::ExecuteProposal {})
+        .send()
+        .map_err(|e| anyhow!("execute proposal {} of {}: {}", index, multisig, e))?;
+    Ok(signature.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multisig(signer: Pubkey, co_signers: &[(Pubkey, u64)]) -> Multisig {
+        Multisig {
+            signer,
+            members: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            threshold: 2,
+            co_signers: co_signers.iter().map(|&(key, added_slot)| CoSigner { key, added_slot }).collect(),
+            proposal_count: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn trusts_co_signers_added_before_signing() {
+        let (release, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (early, late) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let governed = multisig(release, &[(early, 10), (late, 100)]);
+        let fetch = |key: &Pubkey| Ok(if *key == release { governed.co_signers.clone() } else { Vec::new() });
+
+        assert_eq!(co_signers_of(&[other, release], 50, fetch).unwrap(), vec![(early, release)]);
+        assert_eq!(co_signers_of(&[release], 100, fetch).unwrap(), vec![(early, release), (late, release)]);
+        assert!(co_signers_of(&[other], 100, fetch).unwrap().is_empty());
+    }
+
+    #[test]
+    fn describes_actions() {
+        let key = Pubkey::new_unique();
+        assert_eq!(MultisigAction::AddCoSigner { key }.to_string(), format!("add co-signer {}", key));
+        assert_eq!(
+            revoke_action("ghcr.io/kevredlabs/skelz@sha256:abc").unwrap().to_string(),
+            "revoke signature of sha256:abc"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Durable transaction nonces.
+//!
+//! A transaction built on a nonce account's stored value instead of a recent
+//! blockhash stays valid until the nonce is advanced, which lets prepared signing
+//! transactions wait for offline signatures or manual approval.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use solana_client::nonce_utils;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::nonce::State;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use tracing::info;
+
+use crate::rpc::RpcPool;
+use crate::{load_config_signer, SkelzConfig};
+
+/// A nonce account used as the blockhash source of a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    /// Nonce account address
+    pub account: Pubkey,
+    /// Key allowed to advance the nonce; must sign the transaction
+    pub authority: Pubkey,
+}
+
+impl DurableNonce {
+    /// Instruction that must come first in a transaction using this nonce
+    pub fn advance_instruction(&self) -> Instruction {
+        system_instruction::advance_nonce_account(&self.account, &self.authority)
+    }
+}
+
+/// Current value of a nonce account, checking that `authority` controls it
+pub fn fetch_nonce_value(rpc: &RpcClient, nonce: &DurableNonce) -> Result<Hash> {
+    let account = nonce_utils::get_account_with_commitment(rpc, &nonce.account, CommitmentConfig::confirmed())
+        .map_err(|e| anyhow!("nonce account {}: {}", nonce.account, e))?;
+    let data = nonce_utils::data_from_account(&account)
+        .map_err(|e| anyhow!("nonce account {}: {}", nonce.account, e))?;
+    if data.authority != nonce.authority {
+        anyhow::bail!(
+            "nonce account {} is controlled by {}, not {}",
+            nonce.account,
+            data.authority,
+            nonce.authority
+        );
+    }
+    Ok(data.blockhash())
+}
+
+/// Nonce to sign with: flags override `nonce_account`/`nonce_authority` from the config
+///
+/// The authority defaults to `signer`, which is only called when a nonce account is set.
+pub fn resolve_nonce(
+    cfg: &SkelzConfig,
+    account: Option<Pubkey>,
+    authority: Option<Pubkey>,
+    signer: impl FnOnce() -> Result<Pubkey>,
+) -> Result<Option<DurableNonce>> {
+    let parse = |key: &str, value: &Option<String>| -> Result<Option<Pubkey>> {
+        value
+            .as_deref()
+            .map(|v| Pubkey::from_str(v).map_err(|e| anyhow!("invalid {} {}: {}", key, v, e)))
+            .transpose()
+    };
+    let account = match account {
+        Some(account) => account,
+        None => match parse("nonce_account", &cfg.nonce_account)? {
+            Some(account) => account,
+            None => return Ok(None),
+        },
+    };
+    let authority = match authority.or(parse("nonce_authority", &cfg.nonce_authority)?) {
+        Some(authority) => authority,
+        None => signer()?,
+    };
+    Ok(Some(DurableNonce { account, authority }))
+}
+
+/// A nonce account created by [`create_nonce_account`]
+#[derive(Debug, Clone)]
+pub struct CreatedNonce {
+    pub nonce: DurableNonce,
+    /// Rent-exempt balance moved into the account
+    pub lamports: u64,
+    /// Creating transaction
+    pub signature: String,
+}
+
+/// Create and fund a nonce account from the configured signer
+///
+/// `authority` defaults to the configured signer.
+pub fn create_nonce_account(cfg: &SkelzConfig, authority: Option<Pubkey>) -> Result<CreatedNonce> {
+    let payer = load_config_signer(cfg)?;
+    let account = Keypair::new();
+    let nonce = DurableNonce { account: account.pubkey(), authority: authority.unwrap_or(payer.pubkey()) };
+
+    let pool = RpcPool::from_config(cfg)?;
+    pool.select_healthy()?;
+    let lamports = pool.call("getMinimumBalanceForRentExemption", |rpc| {
+        Ok(rpc.get_minimum_balance_for_rent_exemption(State::size())?)
+    })?;
+    let instructions = system_instruction::create_nonce_account(&payer.pubkey(), &nonce.account, &nonce.authority, lamports);
+    let signature = pool.call("send create_nonce_account", |rpc| {
+        let blockhash = rpc.get_latest_blockhash()?;
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        transaction.try_sign(&[&payer as &dyn Signer, &account], blockhash)?;
+        Ok(rpc.send_and_confirm_transaction(&transaction)?)
+    })?;
+    info!(account = %nonce.account, authority = %nonce.authority, %signature, "created nonce account");
+    Ok(CreatedNonce { nonce, lamports, signature: signature.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_nonce_from_flags_and_config() {
+        let signer = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut cfg = SkelzConfig::default();
+        let no_signer = || -> Result<Pubkey> { panic!("signer not needed") };
+
+        assert_eq!(resolve_nonce(&cfg, None, Some(authority), no_signer).unwrap(), None);
+        assert_eq!(
+            resolve_nonce(&cfg, Some(account), None, || Ok(signer)).unwrap(),
+            Some(DurableNonce { account, authority: signer })
+        );
+
+        cfg.nonce_account = Some(account.to_string());
+        cfg.nonce_authority = Some(authority.to_string());
+        assert_eq!(
+            resolve_nonce(&cfg, None, None, no_signer).unwrap(),
+            Some(DurableNonce { account, authority })
+        );
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            resolve_nonce(&cfg, Some(other), Some(signer), no_signer).unwrap(),
+            Some(DurableNonce { account: other, authority: signer })
+        );
+
+        cfg.nonce_account = Some("not-a-key".to_string());
+        assert!(resolve_nonce(&cfg, None, None, no_signer).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! Detached signing for keys that never touch an online host.
+//!
+//! `skelz sign --prepare` builds the unsigned signing transaction on an online
+//! host and writes it to a JSON file. `skelz tx sign` adds signatures on the
+//! offline host (no RPC needed), and `skelz tx submit` sends the fully signed
+//! transaction and attaches the OCI proof. With a durable nonce the file stays
+//! valid until it is submitted; otherwise the blockhash expires after ~60-90s.
+
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use solana_sdk::compute_budget;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Signature as TxSignature, Signer};
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use tracing::info;
+
+use crate::nonce::fetch_nonce_value;
+use crate::rpc::RpcPool;
+use crate::skelz::client::args;
+use crate::{
+    extract_digest_from_reference, fees, fetch_signature, format_timestamp, signature_pda,
+    skelz_program_with_payer, write_signature_instructions, SignOptions, SkelzConfig, SkelzError,
+};
+
+/// Format version of prepared transaction files
+pub const PREPARED_TX_VERSION: u32 = 1;
+
+/// A signing transaction travelling between the online and offline hosts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreparedTransaction {
+    pub version: u32,
+    pub image_reference: String,
+    pub digest: String,
+    pub cluster: String,
+    pub program_id: String,
+    /// Key recorded as the image signer
+    pub signer: String,
+    /// Account paying fees and rent, when it is not the signer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<String>,
+    pub not_after: Option<i64>,
+    /// Durable nonce account the transaction is built on, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_account: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_authority: Option<String>,
+    /// Recent blockhash or durable nonce value
+    pub blockhash: String,
+    /// Base64 bincode transaction; signatures are filled in as keys sign
+    pub transaction: String,
+}
+
+impl PreparedTransaction {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("read {}", path.display()))?;
+        let prepared: Self = serde_json::from_slice(&bytes)
+            .with_context(|| format!("parse prepared transaction {}", path.display()))?;
+        if prepared.version != PREPARED_TX_VERSION {
+            anyhow::bail!(
+                "{} has format version {}, expected {}",
+                path.display(),
+                prepared.version,
+                PREPARED_TX_VERSION
+            );
+        }
+        Ok(prepared)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json).with_context(|| format!("write {}", path.display()))
+    }
+
+    pub fn decode_transaction(&self) -> Result<Transaction> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&self.transaction)
+            .context("transaction is not valid base64")?;
+        bincode::deserialize(&bytes).context("transaction does not decode")
+    }
+
+    fn encode_transaction(&mut self, transaction: &Transaction) -> Result<()> {
+        self.transaction = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(transaction)?);
+        Ok(())
+    }
+
+    /// Required signers that have not signed yet
+    pub fn missing_signers(&self) -> Result<Vec<Pubkey>> {
+        let transaction = self.decode_transaction()?;
+        let required = transaction.message.header.num_required_signatures as usize;
+        Ok(transaction.message.account_keys[..required]
+            .iter()
+            .zip(&transaction.signatures)
+            .filter(|(_, signature)| **signature == TxSignature::default())
+            .map(|(key, _)| *key)
+            .collect())
+    }
+
+    /// Check that the transaction only records `digest` for `signer`, as the file claims
+    ///
+    /// Run before signing on the offline host, which cannot ask the cluster.
+    pub fn check_contents(&self) -> Result<()> {
+        let transaction = self.decode_transaction()?;
+        let program_id = Pubkey::from_str(&self.program_id)?;
+        let signer = Pubkey::from_str(&self.signer)?;
+        let payer = self.fee_payer.as_deref().map(Pubkey::from_str).transpose()?.unwrap_or(signer);
+        if transaction.message.account_keys.first() != Some(&payer) {
+            anyhow::bail!("transaction fee payer does not match the file");
+        }
+        if transaction.message.recent_blockhash != Hash::from_str(&self.blockhash)? {
+            anyhow::bail!("transaction blockhash does not match the file");
+        }
+
+        let mut writes = 0;
+        for (position, instruction) in transaction.message.instructions.iter().enumerate() {
+            let program = transaction.message.account_keys[instruction.program_id_index as usize];
+            let key = |position: usize| -> Result<Pubkey> {
+                let index = *instruction
+                    .accounts
+                    .get(position)
+                    .ok_or_else(|| anyhow!("instruction is missing account {}", position))?;
+                Ok(transaction.message.account_keys[index as usize])
+            };
+            if program == compute_budget::id() {
+                continue;
+            }
+            if program == system_program::id() && position == 0 {
+                // Only advance_nonce_account of the declared nonce, which must come first
+                let advances = matches!(
+                    bincode::deserialize(&instruction.data),
+                    Ok(SystemInstruction::AdvanceNonceAccount)
+                );
+                let account = self.nonce_account.as_deref().map(Pubkey::from_str).transpose()?;
+                if !advances || Some(key(0)?) != account {
+                    anyhow::bail!("unexpected system instruction (only advancing the declared nonce is allowed)");
+                }
+                continue;
+            }
+            if program != program_id {
+                anyhow::bail!("unexpected instruction for program {}", program);
+            }
+            let data = instruction
+                .data
+                .strip_prefix(&args::WriteSignature::DISCRIMINATOR[..])
+                .ok_or_else(|| anyhow!("unexpected skelz instruction (not write_signature)"))?;
+            let decoded = args::WriteSignature::deserialize(&mut &data[..])
+                .context("write_signature arguments do not decode")?;
+            if decoded.digest != self.digest || decoded.not_after != self.not_after {
+                anyhow::bail!(
+                    "transaction signs {} (not_after {:?}), but the file claims {} (not_after {:?})",
+                    decoded.digest,
+                    decoded.not_after,
+                    self.digest,
+                    self.not_after
+                );
+            }
+            if key(0)? != signer || key(1)? != payer || key(2)? != signature_pda(&program_id, &self.digest) {
+                anyhow::bail!("write_signature accounts do not match the signer, fee payer and digest");
+            }
+            writes += 1;
+        }
+        if writes != 1 {
+            anyhow::bail!("expected exactly one write_signature instruction, found {}", writes);
+        }
+        Ok(())
+    }
+
+    /// Human-readable summary shown before signing
+    pub fn describe(&self) -> String {
+        let mut lines = vec![
+            format!("Image: {}", self.image_reference),
+            format!("Digest: {}", self.digest),
+            format!("Signer: {}", self.signer),
+            format!("Fee payer: {}", self.fee_payer.as_deref().unwrap_or(&self.signer)),
+            format!("Cluster: {} (program {})", self.cluster, self.program_id),
+        ];
+        if let Some(not_after) = self.not_after {
+            lines.push(format!("Expires: {}", format_timestamp(not_after)));
+        }
+        match (&self.nonce_account, &self.nonce_authority) {
+            (Some(account), Some(authority)) => {
+                lines.push(format!("Durable nonce: {} (authority {})", account, authority))
+            }
+            _ => lines.push(format!("Recent blockhash: {} (expires in ~60-90s)", self.blockhash)),
+        }
+        lines.join("\n")
+    }
+}
+
+/// Build the unsigned signing transaction for `signer` without its keypair
+///
+/// `fee_payer` defaults to the signer; `options.fee_payer` is not used.
+pub fn prepare_signing_transaction(
+    image_reference: &str,
+    cfg: &SkelzConfig,
+    signer: &Pubkey,
+    fee_payer: Option<&Pubkey>,
+    options: &SignOptions,
+) -> Result<PreparedTransaction> {
+    let nonce = options.nonce.as_ref();
+    let digest = extract_digest_from_reference(image_reference)?;
+    // Only used to build instructions; the accounts name the real signer
+    let program = skelz_program_with_payer(cfg, Rc::new(Keypair::new().into()))?;
+
+    if let Some(existing) = fetch_signature(&program, &digest)? {
+        if existing.signer != *signer {
+            return Err(SkelzError::AlreadySignedByOther { digest, signer: existing.signer.to_string() }.into());
+        }
+        anyhow::bail!("{} is already signed by {}; nothing to prepare", digest, signer);
+    }
+
+    let payer = fee_payer.unwrap_or(signer);
+    let instructions = write_signature_instructions(&program, signer, payer, &digest, options)?;
+    let pool = program.pool();
+    let budget = pool.call("compute budget", |rpc| {
+        fees::compute_budget_instructions(rpc, &instructions, payer, options.priority_fee, options.compute_unit_limit)
+    })?;
+
+    let mut all = Vec::new();
+    if let Some(nonce) = nonce {
+        all.push(nonce.advance_instruction());
+    }
+    all.extend(budget);
+    all.extend(instructions);
+
+    let blockhash = match nonce {
+        Some(nonce) => pool.call("nonce account", |rpc| fetch_nonce_value(rpc, nonce))?,
+        None => pool.call("getLatestBlockhash", |rpc| Ok(rpc.get_latest_blockhash()?))?,
+    };
+    let mut transaction = Transaction::new_with_payer(&all, Some(payer));
+    transaction.message.recent_blockhash = blockhash;
+
+    let mut prepared = PreparedTransaction {
+        version: PREPARED_TX_VERSION,
+        image_reference: image_reference.to_string(),
+        digest,
+        cluster: cfg.cluster.clone(),
+        program_id: program.id().to_string(),
+        signer: signer.to_string(),
+        fee_payer: fee_payer.filter(|p| *p != signer).map(|p| p.to_string()),
+        not_after: options.not_after,
+        nonce_account: nonce.map(|n| n.account.to_string()),
+        nonce_authority: nonce.map(|n| n.authority.to_string()),
+        blockhash: blockhash.to_string(),
+        transaction: String::new(),
+    };
+    prepared.encode_transaction(&transaction)?;
+    info!(digest = %prepared.digest, %signer, "prepared signing transaction");
+    Ok(prepared)
+}
+
+/// Add `keypair`'s signature; returns the signers still missing
+pub fn sign_prepared_transaction(prepared: &mut PreparedTransaction, keypair: &dyn Signer) -> Result<Vec<Pubkey>> {
+    prepared.check_contents()?;
+    let mut transaction = prepared.decode_transaction()?;
+    let key = keypair.pubkey();
+    let required = transaction.message.header.num_required_signatures as usize;
+    if !transaction.message.account_keys[..required].contains(&key) {
+        anyhow::bail!("{} is not a required signer of this transaction", key);
+    }
+    let blockhash = transaction.message.recent_blockhash;
+    transaction
+        .try_partial_sign(&[keypair], blockhash)
+        .map_err(|e| anyhow!("sign with {}: {}", key, e))?;
+    prepared.encode_transaction(&transaction)?;
+    prepared.missing_signers()
+}
+
+/// Send a fully signed prepared transaction; returns its signature
+pub fn submit_prepared_transaction(prepared: &PreparedTransaction, cfg: &SkelzConfig) -> Result<String> {
+    if prepared.cluster != cfg.cluster {
+        anyhow::bail!("transaction was prepared for {}, but the configured cluster is {}", prepared.cluster, cfg.cluster);
+    }
+    prepared.check_contents()?;
+    let missing = prepared.missing_signers()?;
+    if !missing.is_empty() {
+        let missing: Vec<String> = missing.iter().map(|k| k.to_string()).collect();
+        anyhow::bail!("transaction is missing signatures from: {}", missing.join(", "));
+    }
+    let transaction = prepared.decode_transaction()?;
+    transaction.verify().context("transaction signatures do not verify")?;
+
+    // No retries across blockhashes: the transaction is fixed, resending it is idempotent
+    let pool = RpcPool::from_config(cfg)?;
+    pool.select_healthy()?;
+    let signature = pool.call("send prepared transaction", |rpc| Ok(rpc.send_and_confirm_transaction(&transaction)?))?;
+    info!(%signature, digest = %prepared.digest, "submitted prepared signing transaction");
+    Ok(signature.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::InstructionData;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+
+    fn prepared(signer: &Keypair, digest: &str, claimed_digest: &str) -> PreparedTransaction {
+        prepared_with(signer, digest, claimed_digest, vec![])
+    }
+
+    fn prepared_with(signer: &Keypair, digest: &str, claimed_digest: &str, mut leading: Vec<Instruction>) -> PreparedTransaction {
+        let program_id = crate::skelz::ID;
+        let instruction = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(signer.pubkey(), true),
+                AccountMeta::new(signer.pubkey(), true),
+                AccountMeta::new(signature_pda(&program_id, digest), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: args::WriteSignature { digest: digest.to_string(), not_after: None }.data(),
+        };
+        let blockhash = Hash::new_unique();
+        leading.extend([compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(20_000), instruction]);
+        let mut transaction = Transaction::new_with_payer(&leading, Some(&signer.pubkey()));
+        transaction.message.recent_blockhash = blockhash;
+        let mut prepared = PreparedTransaction {
+            version: PREPARED_TX_VERSION,
+            image_reference: format!("ghcr.io/acme/app@{}", claimed_digest),
+            digest: claimed_digest.to_string(),
+            cluster: "devnet".to_string(),
+            program_id: program_id.to_string(),
+            signer: signer.pubkey().to_string(),
+            fee_payer: None,
+            not_after: None,
+            nonce_account: None,
+            nonce_authority: None,
+            blockhash: blockhash.to_string(),
+            transaction: String::new(),
+        };
+        prepared.encode_transaction(&transaction).unwrap();
+        prepared
+    }
+
+    #[test]
+    fn signs_offline_and_roundtrips() {
+        let signer = Keypair::new();
+        let mut prepared = prepared(&signer, "sha256:abc", "sha256:abc");
+        assert_eq!(prepared.missing_signers().unwrap(), vec![signer.pubkey()]);
+
+        let path = std::env::temp_dir().join(format!("skelz-prepared-{}.json", std::process::id()));
+        prepared.save(&path).unwrap();
+        let mut loaded = PreparedTransaction::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(sign_prepared_transaction(&mut loaded, &signer).unwrap().is_empty());
+        loaded.decode_transaction().unwrap().verify().unwrap();
+
+        let stranger = Keypair::new();
+        assert!(sign_prepared_transaction(&mut prepared, &stranger).is_err());
+    }
+
+    #[test]
+    fn allows_only_declared_nonce_advance() {
+        let signer = Keypair::new();
+        let nonce = crate::nonce::DurableNonce { account: Pubkey::new_unique(), authority: signer.pubkey() };
+        let mut prepared = prepared_with(&signer, "sha256:abc", "sha256:abc", vec![nonce.advance_instruction()]);
+        prepared.nonce_account = Some(nonce.account.to_string());
+        prepared.check_contents().unwrap();
+
+        prepared.nonce_account = Some(Pubkey::new_unique().to_string());
+        assert!(prepared.check_contents().is_err());
+
+        let transfer = solana_sdk::system_instruction::transfer(&signer.pubkey(), &Pubkey::new_unique(), 1);
+        let mut prepared = prepared_with(&signer, "sha256:abc", "sha256:abc", vec![transfer]);
+        prepared.nonce_account = Some(nonce.account.to_string());
+        assert!(prepared.check_contents().is_err());
+    }
+
+    #[test]
+    fn rejects_transaction_that_does_not_match_file() {
+        let signer = Keypair::new();
+        let mut prepared = prepared(&signer, "sha256:evil", "sha256:abc");
+        let err = sign_prepared_transaction(&mut prepared, &signer).unwrap_err();
+        assert!(err.to_string().contains("but the file claims sha256:abc"));
+
+        let mut prepared = self::prepared(&signer, "sha256:abc", "sha256:abc");
+        prepared.fee_payer = Some(Pubkey::new_unique().to_string());
+        let err = prepared.check_contents().unwrap_err();
+        assert!(err.to_string().contains("fee payer does not match"));
+    }
+}
@@ -0,0 +1,404 @@
+//! OIDC ID token verification for keyless signing.
+//!
+//! ID tokens are JWTs signed by their issuer (e.g. GitHub Actions,
+//! `https://token.actions.githubusercontent.com`). [`OidcVerifier`] fetches the
+//! issuer's JWKS, found through `/.well-known/openid-configuration` or given
+//! explicitly, checks the RS256 or ES256 signature, then `iss`, `aud`, `exp`
+//! and `nbf`.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use ring::signature::{self, RsaPublicKeyComponents, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Issuer of GitHub Actions ID tokens
+pub const GITHUB_ACTIONS_ISSUER: &str = "https://token.actions.githubusercontent.com";
+// Tolerated clock difference with the issuer when checking `exp` and `nbf`
+const CLOCK_SKEW_SECS: i64 = 60;
+
+/// `aud` claim: a single audience or a list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    pub fn contains(&self, audience: &str) -> bool {
+        match self {
+            Audience::One(aud) => aud == audience,
+            Audience::Many(auds) => auds.iter().any(|aud| aud == audience),
+        }
+    }
+}
+
+/// Claims of an ID token used by keyless signing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OidcClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: Audience,
+    pub exp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    /// GitHub Actions: workflow file and ref that requested the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_workflow_ref: Option<String>,
+}
+
+impl OidcClaims {
+    /// Workflow reference recorded on-chain (empty when the issuer has none)
+    pub fn workflow_ref(&self) -> &str {
+        self.job_workflow_ref.as_deref().unwrap_or_default()
+    }
+
+    /// Identity matched by `--identity-regexp`, see [`identity_for`]
+    pub fn identity(&self) -> String {
+        identity_for(&self.iss, &self.sub, self.workflow_ref())
+    }
+}
+
+/// The identity cosign would put in the certificate SAN for these claims
+///
+/// GitHub Actions tokens map to `https://github.com/<job_workflow_ref>`; other
+/// issuers to the subject.
+pub fn identity_for(issuer: &str, subject: &str, workflow_ref: &str) -> String {
+    if issuer == GITHUB_ACTIONS_ISSUER && !workflow_ref.is_empty() {
+        format!("https://github.com/{}", workflow_ref)
+    } else {
+        subject.to_string()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+/// A public key of the issuer (RSA or P-256 EC)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+/// JSON Web Key Set served at the issuer's `jwks_uri`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    jwks_uri: String,
+}
+
+fn base64url(part: &str) -> Result<Vec<u8>> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(part.trim_end_matches('='))
+        .context("invalid base64url")
+}
+
+fn split_token(token: &str) -> Result<(&str, &str, &str)> {
+    let mut parts = token.trim().split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), Some(signature), None) => Ok((header, payload, signature)),
+        _ => Err(anyhow!("ID token is not a JWT (expected header.payload.signature)")),
+    }
+}
+
+/// Read the claims of a token without checking it (to fill in transaction arguments)
+pub fn decode_unverified(token: &str) -> Result<OidcClaims> {
+    let (_, payload, _) = split_token(token)?;
+    serde_json::from_slice(&base64url(payload)?).context("ID token claims do not decode")
+}
+
+impl Jwk {
+    fn component(value: &Option<String>, name: &str) -> Result<Vec<u8>> {
+        base64url(value.as_deref().ok_or_else(|| anyhow!("JWK has no `{}`", name))?)
+    }
+
+    fn supports(&self, alg: &str) -> bool {
+        match alg {
+            "RS256" => self.kty == "RSA",
+            "ES256" => self.kty == "EC" && self.crv.as_deref() == Some("P-256"),
+            _ => false,
+        }
+    }
+
+    fn verify(&self, alg: &str, message: &[u8], signature: &[u8]) -> Result<()> {
+        let result = match alg {
+            "RS256" => RsaPublicKeyComponents { n: Self::component(&self.n, "n")?, e: Self::component(&self.e, "e")? }
+                .verify(&signature::RSA_PKCS1_2048_8192_SHA256, message, signature),
+            "ES256" => {
+                let mut point = vec![0x04];
+                point.extend(Self::component(&self.x, "x")?);
+                point.extend(Self::component(&self.y, "y")?);
+                UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_FIXED, point).verify(message, signature)
+            }
+            _ => anyhow::bail!("unsupported JWT algorithm {}", alg),
+        };
+        result.map_err(|_| anyhow!("ID token signature is invalid"))
+    }
+}
+
+/// Verifies ID tokens of one issuer for one audience
+pub struct OidcVerifier {
+    issuer: String,
+    audience: String,
+    jwks_url: Option<String>,
+    keys: Option<JwkSet>,
+    client: reqwest::blocking::Client,
+}
+
+impl OidcVerifier {
+    pub fn new(issuer: &str, audience: &str) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(30)).build()?;
+        Ok(OidcVerifier {
+            issuer: issuer.to_string(),
+            audience: audience.to_string(),
+            jwks_url: None,
+            keys: None,
+            client,
+        })
+    }
+
+    /// Fetch keys from `url` instead of discovering them from the issuer
+    pub fn with_jwks_url(mut self, url: &str) -> Self {
+        self.jwks_url = Some(url.to_string());
+        self
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    pub fn audience(&self) -> &str {
+        &self.audience
+    }
+
+    fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.client
+            .get(url)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .with_context(|| format!("fetch {}", url))
+    }
+
+    fn refresh_keys(&mut self) -> Result<&JwkSet> {
+        let url = match &self.jwks_url {
+            Some(url) => url.clone(),
+            None => {
+                let discovery = format!("{}/.well-known/openid-configuration", self.issuer.trim_end_matches('/'));
+                self.get_json::<Discovery>(&discovery)?.jwks_uri
+            }
+        };
+        let keys: JwkSet = self.get_json(&url)?;
+        debug!(%url, keys = keys.keys.len(), "fetched issuer keys");
+        Ok(self.keys.insert(keys))
+    }
+
+    fn find_key(&self, header: &JwtHeader) -> Option<Jwk> {
+        self.keys.as_ref()?.keys.iter().find(|key| {
+            key.supports(&header.alg) && (header.kid.is_none() || key.kid == header.kid)
+        }).cloned()
+    }
+
+    /// Check the token's signature and claims at unix time `now`
+    pub fn verify(&mut self, token: &str, now: i64) -> Result<OidcClaims> {
+        let (header_part, payload_part, signature_part) = split_token(token)?;
+        let header: JwtHeader =
+            serde_json::from_slice(&base64url(header_part)?).context("ID token header does not decode")?;
+        if header.alg != "RS256" && header.alg != "ES256" {
+            anyhow::bail!("unsupported JWT algorithm {} (expected RS256 or ES256)", header.alg);
+        }
+
+        // An unknown key id may mean the issuer rotated its keys: refetch once
+        let key = match self.find_key(&header) {
+            Some(key) => key,
+            None => {
+                self.refresh_keys()?;
+                self.find_key(&header).ok_or_else(|| {
+                    anyhow!("issuer {} has no {} key {}", self.issuer, header.alg, header.kid.as_deref().unwrap_or("(no kid)"))
+                })?
+            }
+        };
+        let message = format!("{}.{}", header_part, payload_part);
+        key.verify(&header.alg, message.as_bytes(), &base64url(signature_part)?)?;
+
+        let claims: OidcClaims =
+            serde_json::from_slice(&base64url(payload_part)?).context("ID token claims do not decode")?;
+        if claims.iss != self.issuer {
+            anyhow::bail!("ID token issuer {} is not {}", claims.iss, self.issuer);
+        }
+        if !claims.aud.contains(&self.audience) {
+            anyhow::bail!("ID token is not intended for audience {}", self.audience);
+        }
+        if now > claims.exp + CLOCK_SKEW_SECS {
+            anyhow::bail!("ID token expired at {}", crate::format_timestamp(claims.exp));
+        }
+        if let Some(nbf) = claims.nbf.filter(|&nbf| now + CLOCK_SKEW_SECS < nbf) {
+            anyhow::bail!("ID token is not valid before {}", crate::format_timestamp(nbf));
+        }
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, RsaKeyPair};
+    use std::net::TcpListener;
+
+    const RSA_TEST_KEY: &str = include_str!("../tests/fixtures/oidc-test-rsa.pem");
+
+    fn encode(bytes: &[u8]) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Test issuer signing tokens with an ES256 and an RS256 key
+    pub(crate) struct TestIssuer {
+        ec: EcdsaKeyPair,
+        rsa: RsaKeyPair,
+        pub jwks: JwkSet,
+    }
+
+    impl TestIssuer {
+        pub(crate) fn new() -> Self {
+            let rng = SystemRandom::new();
+            let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+            let ec = EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+            let point = ec.public_key().as_ref();
+            let der: String = RSA_TEST_KEY.lines().filter(|line| !line.starts_with("-----")).collect();
+            let der = base64::engine::general_purpose::STANDARD.decode(der).unwrap();
+            let rsa = RsaKeyPair::from_pkcs8(&der).unwrap();
+            let components = RsaPublicKeyComponents::<Vec<u8>>::from(rsa.public());
+            let jwks = JwkSet {
+                keys: vec![
+                    Jwk {
+                        kty: "EC".to_string(),
+                        kid: Some("ec".to_string()),
+                        crv: Some("P-256".to_string()),
+                        x: Some(encode(&point[1..33])),
+                        y: Some(encode(&point[33..])),
+                        ..Jwk::default()
+                    },
+                    Jwk {
+                        kty: "RSA".to_string(),
+                        kid: Some("rsa".to_string()),
+                        n: Some(encode(&components.n)),
+                        e: Some(encode(&components.e)),
+                        ..Jwk::default()
+                    },
+                ],
+            };
+            TestIssuer { ec, rsa, jwks }
+        }
+
+        pub(crate) fn token(&self, alg: &str, claims: &OidcClaims) -> String {
+            let kid = if alg == "ES256" { "ec" } else { "rsa" };
+            let header = encode(serde_json::json!({ "alg": alg, "kid": kid, "typ": "JWT" }).to_string().as_bytes());
+            let payload = encode(&serde_json::to_vec(claims).unwrap());
+            let message = format!("{}.{}", header, payload);
+            let rng = SystemRandom::new();
+            let signature = if alg == "ES256" {
+                self.ec.sign(&rng, message.as_bytes()).unwrap().as_ref().to_vec()
+            } else {
+                let mut signature = vec![0; self.rsa.public().modulus_len()];
+                self.rsa.sign(&signature::RSA_PKCS1_SHA256, &rng, message.as_bytes(), &mut signature).unwrap();
+                signature
+            };
+            format!("{}.{}", message, encode(&signature))
+        }
+
+        /// Serve discovery and the JWKS for `requests` requests; returns the issuer URL
+        pub(crate) fn serve(&self, requests: usize) -> String {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let discovery = serde_json::json!({ "issuer": url, "jwks_uri": format!("{}/jwks", url) });
+            let jwks = serde_json::to_value(&self.jwks).unwrap();
+            std::thread::spawn(move || {
+                for stream in listener.incoming().take(requests) {
+                    crate::http::handle_json(&mut stream.unwrap(), |_, path, _| match path {
+                        "/.well-known/openid-configuration" => (200, discovery.clone()),
+                        "/jwks" => (200, jwks.clone()),
+                        _ => (404, serde_json::json!({})),
+                    })
+                    .unwrap();
+                }
+            });
+            url
+        }
+    }
+
+    pub(crate) fn claims(issuer: &str, audience: &str, now: i64) -> OidcClaims {
+        OidcClaims {
+            iss: issuer.to_string(),
+            sub: "repo:octo/app:ref:refs/heads/main".to_string(),
+            aud: Audience::One(audience.to_string()),
+            exp: now + 300,
+            nbf: Some(now - 5),
+            job_workflow_ref: Some("octo/app/.github/workflows/release.yml@refs/heads/main".to_string()),
+        }
+    }
+
+    #[test]
+    fn verifies_tokens_against_a_local_jwks() {
+        let issuer = TestIssuer::new();
+        // Discovery and JWKS are fetched once, then the keys are cached
+        let url = issuer.serve(2);
+        let now = 1_800_000_000;
+        let mut verifier = OidcVerifier::new(&url, "skelz").unwrap();
+
+        let expected = claims(&url, "skelz", now);
+        assert_eq!(verifier.verify(&issuer.token("ES256", &expected), now).unwrap(), expected);
+        assert_eq!(verifier.verify(&issuer.token("RS256", &expected), now).unwrap(), expected);
+
+        let wrong_audience = claims(&url, "sigstore", now);
+        let err = verifier.verify(&issuer.token("ES256", &wrong_audience), now).unwrap_err();
+        assert!(err.to_string().contains("audience"), "{}", err);
+
+        let wrong_issuer = claims("https://issuer.example", "skelz", now);
+        let err = verifier.verify(&issuer.token("ES256", &wrong_issuer), now).unwrap_err();
+        assert!(err.to_string().contains("issuer"), "{}", err);
+
+        let err = verifier.verify(&issuer.token("ES256", &expected), now + 600).unwrap_err();
+        assert!(err.to_string().contains("expired"), "{}", err);
+
+        // Claims swapped under a valid signature
+        let token = issuer.token("RS256", &expected);
+        let (header, _, signature) = split_token(&token).unwrap();
+        let forged = OidcClaims { sub: "repo:mallory/app:ref:refs/heads/main".to_string(), ..expected.clone() };
+        let forged = format!("{}.{}.{}", header, encode(&serde_json::to_vec(&forged).unwrap()), signature);
+        let err = verifier.verify(&forged, now).unwrap_err();
+        assert!(err.to_string().contains("signature is invalid"), "{}", err);
+        assert_eq!(decode_unverified(&forged).unwrap().sub, "repo:mallory/app:ref:refs/heads/main");
+    }
+
+    #[test]
+    fn maps_claims_to_identities() {
+        let github = claims(GITHUB_ACTIONS_ISSUER, "skelz", 0);
+        assert_eq!(github.identity(), "https://github.com/octo/app/.github/workflows/release.yml@refs/heads/main");
+        assert_eq!(identity_for("https://accounts.example", "dev@example.com", ""), "dev@example.com");
+    }
+}
@@ -0,0 +1,756 @@
+//! Declarative verification policies.
+//!
+//! A policy is a TOML document listing rules keyed by repository glob. The first
+//! rule whose `repository` matches the image decides which signers are trusted and
+//! which extra requirements apply:
+//!
+//! ```toml
+//! [[rule]]
+//! repository = "ghcr.io/kevredlabs/*"
+//! publishers = ["kevredlabs"]
+//! signers = ["9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"]
+//! threshold = 1
+//! attestations = ["application/spdx+json"]
+//! max_age = "180d"
+//! clusters = ["devnet", "mainnet-beta"]
+//! revocation = "deny"
+//! ```
+//!
+//! Evaluation is split in two steps so that callers such as the admission
+//! controller can reuse it: [`collect_image_evidence`] gathers on-chain and
+//! registry facts about an image, and [`Policy::evaluate`] turns them into a
+//! [`Decision`] without any I/O.
+//!
+//! A digest holds a single on-chain signature record, so `threshold` must be 1
+//! until several signers can sign the same digest. Keys a trusted signer's
+//! multisig added as co-signers are trusted like the signer, as with
+//! `skelz verify --signer`.
+//!
+//! Rules can also be stored on-chain as `Policy` accounts of the skelz program
+//! (repository pattern, required signers, threshold, version) so every cluster
+//! evaluates the same tamper-evident rule; see [`push_onchain_policy`] and
+//! [`Policy::from_onchain`].
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::system_program;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use tracing::info;
+
+use crate::credentials::Credential;
+use crate::multisig::{co_signers_of, fetch_multisig};
+use crate::skelz::accounts::Multisig;
+use crate::skelz::{accounts::Policy as PolicyAccount, client::accounts, client::args};
+use crate::{
+    discover_oci_artifacts, extract_digest_from_reference, fetch_optional_account,
+    fetch_publisher, fetch_rotation, fetch_signature, follow_rotation_chain, format_timestamp,
+    glob_match, image_repository, load_skelz_program, parse_validity_duration, SkelzConfig, SkelzProgram,
+    MAX_ROTATION_DEPTH,
+};
+
+/// How revoked signatures are treated by a rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevocationMode {
+    /// Revoked signatures do not count towards the threshold
+    #[default]
+    Deny,
+    /// Revoked signatures count but are reported as warnings
+    Warn,
+}
+
+/// A verification rule applying to repositories matching `repository`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Repository glob (e.g., ghcr.io/kevredlabs/*), `*` matches any characters
+    pub repository: String,
+    /// Trusted signer public keys (base58)
+    #[serde(default)]
+    pub signers: Vec<String>,
+    /// Trusted on-chain publishers; their keys are trusted for their namespace
+    #[serde(default)]
+    pub publishers: Vec<String>,
+    /// Minimum number of distinct trusted signers with a valid signature (must be 1)
+    #[serde(default = "default_threshold")]
+    pub threshold: usize,
+    /// OCI artifact types that must be attached to the image (e.g., SBOM, provenance)
+    #[serde(default)]
+    pub attestations: Vec<String>,
+    /// Maximum signature age (e.g., 180d); older signatures do not count
+    #[serde(default)]
+    pub max_age: Option<String>,
+    /// Clusters the signatures may be read from; empty allows any cluster
+    #[serde(default)]
+    pub clusters: Vec<String>,
+    /// Treatment of revoked signatures
+    #[serde(default)]
+    pub revocation: RevocationMode,
+    /// Count expired signatures (reported as warnings)
+    #[serde(default)]
+    pub allow_expired: bool,
+}
+
+fn default_threshold() -> usize {
+    1
+}
+
+/// An ordered list of rules; the first rule matching an image applies
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Policy {
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<PolicyRule>,
+}
+
+/// A signature record as seen by the policy engine
+#[derive(Debug, Clone)]
+pub struct SignatureEvidence {
+    pub signer: Pubkey,
+    pub slot: u64,
+    pub created_at: i64,
+    pub not_after: Option<i64>,
+    pub revoked_at: Option<i64>,
+}
+
+/// A publisher record as seen by the policy engine
+#[derive(Debug, Clone)]
+pub struct PublisherEvidence {
+    pub namespace: String,
+    pub keys: Vec<Pubkey>,
+}
+
+/// Facts about an image gathered from chain and registry
+#[derive(Debug, Clone, Default)]
+pub struct ImageEvidence {
+    /// Canonical image reference (registry/repo@sha256:...)
+    pub reference: String,
+    /// Cluster the evidence was read from
+    pub cluster: String,
+    /// Unix time at which the evidence is evaluated
+    pub now: i64,
+    /// On-chain signature records for the digest
+    pub signatures: Vec<SignatureEvidence>,
+    /// Rotation records (old key -> successor, effective slot) reachable from the signers
+    pub rotations: HashMap<Pubkey, (Pubkey, u64)>,
+    /// Multisigs governing the trusted keys of the matching rule
+    pub multisigs: HashMap<Pubkey, Multisig>,
+    /// Publishers referenced by the matching rule
+    pub publishers: HashMap<String, PublisherEvidence>,
+    /// Artifact types attached to the image in the registry
+    pub attestation_types: Vec<String>,
+}
+
+/// Outcome of a policy evaluation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Decision {
+    /// Whether the image is admitted
+    pub allow: bool,
+    /// Repository pattern of the rule that applied, if any
+    pub rule: Option<String>,
+    /// Why the image was denied (empty when allowed)
+    pub reasons: Vec<String>,
+    /// Non-blocking findings (expired or revoked signatures accepted by the rule)
+    pub warnings: Vec<String>,
+}
+
+impl Decision {
+    fn deny(rule: Option<String>, reason: String) -> Self {
+        Self { allow: false, rule, reasons: vec![reason], warnings: Vec::new() }
+    }
+}
+
+impl Policy {
+    /// Build a single-rule policy from an on-chain policy account
+    pub fn from_onchain(account: &PolicyAccount) -> Self {
+        Self {
+            rules: vec![PolicyRule {
+                repository: account.repository.clone(),
+                signers: account.signers.iter().map(|k| k.to_string()).collect(),
+                publishers: Vec::new(),
+                threshold: usize::from(account.threshold),
+                attestations: Vec::new(),
+                max_age: None,
+                clusters: Vec::new(),
+                revocation: RevocationMode::Deny,
+                allow_expired: false,
+            }],
+        }
+    }
+
+    /// Load and validate a policy file (TOML)
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("read policy {}", path.display()))?;
+        let policy: Policy = toml::from_str(&text)
+            .with_context(|| format!("parse policy TOML at {}", path.display()))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Check that every rule is usable
+    pub fn validate(&self) -> Result<()> {
+        if self.rules.is_empty() {
+            anyhow::bail!("policy has no [[rule]] entries");
+        }
+        for rule in &self.rules {
+            if rule.signers.is_empty() && rule.publishers.is_empty() {
+                anyhow::bail!("rule '{}' lists no signers or publishers", rule.repository);
+            }
+            check_threshold(rule.threshold).with_context(|| format!("rule '{}'", rule.repository))?;
+            for signer in &rule.signers {
+                Pubkey::from_str(signer).map_err(|e| {
+                    anyhow!("rule '{}': invalid signer {}: {}", rule.repository, signer, e)
+                })?;
+            }
+            if let Some(max_age) = rule.max_age.as_deref() {
+                parse_validity_duration(max_age)
+                    .with_context(|| format!("rule '{}': invalid max_age", rule.repository))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// First rule whose repository glob matches the image
+    pub fn rule_for(&self, image_reference: &str) -> Option<&PolicyRule> {
+        let repository = image_repository(image_reference);
+        self.rules.iter().find(|rule| glob_match(&rule.repository, repository))
+    }
+
+    /// Decide whether an image is admitted given the gathered evidence
+    pub fn evaluate(&self, image: &ImageEvidence) -> Decision {
+        let Some(rule) = self.rule_for(&image.reference) else {
+            return Decision::deny(None, format!("no policy rule matches {}", image.reference));
+        };
+        let rule_name = Some(rule.repository.clone());
+        let mut reasons = Vec::new();
+        let mut warnings = Vec::new();
+
+        if !rule.clusters.is_empty() && !rule.clusters.contains(&image.cluster) {
+            reasons.push(format!(
+                "cluster {} is not allowed (allowed: {})",
+                image.cluster,
+                rule.clusters.join(", ")
+            ));
+        }
+
+        for required in &rule.attestations {
+            if !image.attestation_types.contains(required) {
+                reasons.push(format!("missing attestation of type {}", required));
+            }
+        }
+
+        // Findings about individual signatures only matter if the threshold is missed
+        let mut rejected = Vec::new();
+        let trusted = trusted_signers(rule, image, &mut rejected);
+        let max_age = rule
+            .max_age
+            .as_deref()
+            .and_then(|age| parse_validity_duration(age).ok())
+            .map(|age| age.num_seconds());
+
+        let mut satisfied: Vec<Pubkey> = Vec::new();
+        for signature in &image.signatures {
+            let co_signers = co_signers_of(&trusted, signature.slot, |key| {
+                Ok(image.multisigs.get(key).map(|m| m.co_signers.clone()).unwrap_or_default())
+            })
+            .unwrap_or_default();
+            let mut accepted = trusted.clone();
+            accepted.extend(co_signers.iter().map(|(co_signer, _)| *co_signer));
+            let chain = follow_rotation_chain(signature.signer, signature.slot, &accepted, |key| {
+                Ok(image.rotations.get(key).copied())
+            });
+            let trusted_key = match chain {
+                // A co-signer counts as the key it signs for
+                Ok(chain) => {
+                    let key = *chain.last().unwrap_or(&signature.signer);
+                    co_signers.iter().find(|(co_signer, _)| *co_signer == key).map_or(key, |(_, signer)| *signer)
+                }
+                Err(e) => {
+                    rejected.push(format!("signature by {} not trusted: {}", signature.signer, e));
+                    continue;
+                }
+            };
+
+            if let Some(revoked_at) = signature.revoked_at {
+                let message = format!(
+                    "signature by {} was revoked at {}",
+                    signature.signer,
+                    format_timestamp(revoked_at)
+                );
+                match rule.revocation {
+                    RevocationMode::Deny => {
+                        rejected.push(message);
+                        continue;
+                    }
+                    RevocationMode::Warn => warnings.push(message),
+                }
+            }
+
+            if let Some(not_after) = signature.not_after.filter(|t| image.now > *t) {
+                let message = format!(
+                    "signature by {} expired at {}",
+                    signature.signer,
+                    format_timestamp(not_after)
+                );
+                if !rule.allow_expired {
+                    rejected.push(message);
+                    continue;
+                }
+                warnings.push(message);
+            }
+
+            if let Some(max_age) = max_age {
+                let age = image.now - signature.created_at;
+                if age > max_age {
+                    rejected.push(format!(
+                        "signature by {} is {} days old (max {})",
+                        signature.signer,
+                        age / 86_400,
+                        rule.max_age.as_deref().unwrap_or_default()
+                    ));
+                    continue;
+                }
+            }
+
+            if !satisfied.contains(&trusted_key) {
+                satisfied.push(trusted_key);
+            }
+        }
+
+        if satisfied.len() < rule.threshold {
+            reasons.extend(rejected);
+            reasons.push(format!(
+                "{} of {} required trusted signatures",
+                satisfied.len(),
+                rule.threshold
+            ));
+        }
+
+        Decision { allow: reasons.is_empty(), rule: rule_name, reasons, warnings }
+    }
+}
+
+/// Refuse thresholds no digest can meet: each digest has a single signature record
+fn check_threshold(threshold: usize) -> Result<()> {
+    match threshold {
+        0 => anyhow::bail!("threshold must be at least 1"),
+        1 => Ok(()),
+        n => anyhow::bail!(
+            "threshold {} can never be met: a digest holds a single signature, so the threshold must be 1",
+            n
+        ),
+    }
+}
+
+/// Trusted keys of a rule: explicit signers plus keys of publishers covering the image
+fn trusted_signers(rule: &PolicyRule, image: &ImageEvidence, findings: &mut Vec<String>) -> Vec<Pubkey> {
+    let mut trusted: Vec<Pubkey> = rule
+        .signers
+        .iter()
+        .filter_map(|s| Pubkey::from_str(s).ok())
+        .collect();
+    let repository = image_repository(&image.reference);
+    for name in &rule.publishers {
+        match image.publishers.get(name) {
+            Some(publisher) if glob_match(&publisher.namespace, repository) => {
+                trusted.extend(publisher.keys.iter().copied());
+            }
+            Some(publisher) => findings.push(format!(
+                "publisher {} does not cover {} (namespace {})",
+                name, repository, publisher.namespace
+            )),
+            None => findings.push(format!("publisher {} is not registered", name)),
+        }
+    }
+    trusted
+}
+
+/// Gather the evidence needed to evaluate `policy` for an image
+///
+/// The registry is only queried, with `credential` or anonymously, when the matching
+/// rule requires attestations.
+pub fn collect_image_evidence(
+    program: &SkelzProgram,
+    policy: &Policy,
+    image_reference: &str,
+    cluster: &str,
+    now: i64,
+    credential: Option<&Credential>,
+) -> Result<ImageEvidence> {
+    let digest = extract_digest_from_reference(image_reference)?;
+    let mut evidence = ImageEvidence {
+        reference: image_reference.to_string(),
+        cluster: cluster.to_string(),
+        now,
+        ..Default::default()
+    };
+    let Some(rule) = policy.rule_for(image_reference) else {
+        return Ok(evidence);
+    };
+
+    if let Some(record) = fetch_signature(program, &digest)? {
+        // Collect the rotation chain leaving the signer
+        let mut key = record.signer;
+        for _ in 0..MAX_ROTATION_DEPTH {
+            let Some(rotation) = fetch_rotation(program, &key)? else { break };
+            if evidence.rotations.insert(key, (rotation.new_key, rotation.effective_slot)).is_some() {
+                break;
+            }
+            key = rotation.new_key;
+        }
+        evidence.signatures.push(SignatureEvidence {
+            signer: record.signer,
+            slot: record.slot,
+            created_at: record.created_at,
+            not_after: record.not_after,
+            revoked_at: record.revoked_at,
+        });
+    }
+
+    for name in &rule.publishers {
+        // A missing publisher is reported by the evaluation, not as an error
+        if let Ok(publisher) = fetch_publisher(program, name) {
+            evidence.publishers.insert(
+                name.clone(),
+                PublisherEvidence { namespace: publisher.namespace, keys: publisher.keys },
+            );
+        }
+    }
+
+    for key in trusted_signers(rule, &evidence, &mut Vec::new()) {
+        if let Some(multisig) = fetch_multisig(program, &key)? {
+            evidence.multisigs.insert(key, multisig);
+        }
+    }
+
+    if !rule.attestations.is_empty() {
+        evidence.attestation_types = discover_oci_artifacts(image_reference, credential)?
+            .into_iter()
+            .map(|artifact| artifact.artifact_type)
+            .collect();
+    }
+
+    info!(
+        signatures = evidence.signatures.len(),
+        publishers = evidence.publishers.len(),
+        multisigs = evidence.multisigs.len(),
+        attestations = evidence.attestation_types.len(),
+        "collected policy evidence"
+    );
+    Ok(evidence)
+}
+
+/// Rule fields stored in an on-chain policy account
+#[derive(Debug, Clone)]
+pub struct OnchainPolicyParams {
+    /// Repository pattern (e.g., ghcr.io/kevredlabs/*)
+    pub repository: String,
+    /// Required signers
+    pub signers: Vec<Pubkey>,
+    /// Distinct signers required among `signers` (must be 1)
+    pub threshold: u8,
+}
+
+/// Derive the PDA of an on-chain policy
+pub fn policy_pda(program_id: &Pubkey, owner: &Pubkey, name: &str) -> Pubkey {
+    let (pda, _bump) =
+        Pubkey::find_program_address(&[b"policy", owner.as_ref(), name.as_bytes()], program_id);
+    pda
+}
+
+/// Fetch an on-chain policy, if it exists
+pub fn fetch_onchain_policy(program: &SkelzProgram, owner: &Pubkey, name: &str) -> Result<Option<PolicyAccount>> {
+    fetch_optional_account(program, &policy_pda(&program.id(), owner, name))
+}
+
+/// Create the policy owned by the configured keypair, or update it if it exists
+///
+/// Returns the transaction signature and whether the policy was created.
+pub fn push_onchain_policy(
+    cfg: &SkelzConfig,
+    name: &str,
+    params: &OnchainPolicyParams,
+) -> Result<(String, bool)> {
+    check_threshold(usize::from(params.threshold))?;
+    let program = load_skelz_program(cfg)?;
+    let owner = program.payer();
+    let policy = policy_pda(&program.id(), &owner, name);
+    let existing = fetch_onchain_policy(&program, &owner, name)?;
+
+    let request = program.request();
+    let request = match &existing {
+        Some(current) if current.frozen => {
+            anyhow::bail!("policy {} is frozen at version {}", name, current.version)
+        }
+        Some(_) => request
+            .accounts(accounts::UpdatePolicy { owner, policy })
+            .args(args::UpdatePolicy {
+                repository: params.repository.clone(),
+                signers: params.signers.clone(),
+                threshold: params.threshold,
+            }),
+        None => request
+            .accounts(accounts::CreatePolicy {
+                owner,
+                policy,
+                system_program: system_program::ID,
+            })
+            .args(args::CreatePolicy {
+                name: name.to_string(),
+                repository: params.repository.clone(),
+                signers: params.signers.clone(),
+                threshold: params.threshold,
+            }),
+    };
+    info!(%policy, %name, created = existing.is_none(), "pushing policy");
+    let signature = request
+        .send()
+        .map_err(|e| anyhow!("push policy {}: {}", name, e))?;
+    Ok((signature.to_string(), existing.is_none()))
+}
+
+/// Freeze the policy owned by the configured keypair; frozen policies are immutable
+pub fn freeze_onchain_policy(cfg: &SkelzConfig, name: &str) -> Result<String> {
+    let program = load_skelz_program(cfg)?;
+    let owner = program.payer();
+    let policy = policy_pda(&program.id(), &owner, name);
+    let signature = program
+        .request()
+        .accounts(accounts::UpdatePolicy { owner, policy })
+        .args(args::FreezePolicy {})
+        .send()
+        .map_err(|e| anyhow!("freeze policy {}: {}", name, e))?;
+    Ok(signature.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skelz::types::CoSigner;
+
+    const NOW: i64 = 1_760_000_000;
+
+    fn rule(signers: &[Pubkey]) -> PolicyRule {
+        PolicyRule {
+            repository: "ghcr.io/kevredlabs/*".to_string(),
+            signers: signers.iter().map(|k| k.to_string()).collect(),
+            publishers: Vec::new(),
+            threshold: 1,
+            attestations: Vec::new(),
+            max_age: None,
+            clusters: Vec::new(),
+            revocation: RevocationMode::Deny,
+            allow_expired: false,
+        }
+    }
+
+    fn signed_by(signer: Pubkey) -> ImageEvidence {
+        ImageEvidence {
+            reference: "ghcr.io/kevredlabs/skelz@sha256:abc".to_string(),
+            cluster: "devnet".to_string(),
+            now: NOW,
+            signatures: vec![SignatureEvidence {
+                signer,
+                slot: 10,
+                created_at: NOW - 3_600,
+                not_after: None,
+                revoked_at: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_policy_toml() {
+        let policy: Policy = toml::from_str(
+            r#"
+            [[rule]]
+            repository = "ghcr.io/kevredlabs/*"
+            publishers = ["kevredlabs"]
+            attestations = ["application/spdx+json"]
+            max_age = "180d"
+            revocation = "warn"
+            "#,
+        )
+        .unwrap();
+        policy.validate().unwrap();
+        let rule = &policy.rules[0];
+        assert_eq!(rule.threshold, 1);
+        assert_eq!(rule.revocation, RevocationMode::Warn);
+        assert_eq!(rule.max_age.as_deref(), Some("180d"));
+    }
+
+    #[test]
+    fn validate_rejects_rule_without_signers() {
+        let mut no_signers = rule(&[]);
+        no_signers.publishers.clear();
+        let policy = Policy { rules: vec![no_signers] };
+        assert!(policy.validate().is_err());
+    }
+
+    #[test]
+    fn allows_trusted_signer() {
+        let key = Pubkey::new_unique();
+        let policy = Policy { rules: vec![rule(&[key])] };
+        let decision = policy.evaluate(&signed_by(key));
+        assert!(decision.allow, "{:?}", decision.reasons);
+        assert_eq!(decision.rule.as_deref(), Some("ghcr.io/kevredlabs/*"));
+    }
+
+    #[test]
+    fn denies_unmatched_repository() {
+        let key = Pubkey::new_unique();
+        let policy = Policy { rules: vec![rule(&[key])] };
+        let mut image = signed_by(key);
+        image.reference = "ghcr.io/other/app@sha256:abc".to_string();
+        let decision = policy.evaluate(&image);
+        assert!(!decision.allow);
+        assert!(decision.rule.is_none());
+    }
+
+    #[test]
+    fn denies_untrusted_signer_and_unsigned_image() {
+        let policy = Policy { rules: vec![rule(&[Pubkey::new_unique()])] };
+        assert!(!policy.evaluate(&signed_by(Pubkey::new_unique())).allow);
+        let mut unsigned = signed_by(Pubkey::new_unique());
+        unsigned.signatures.clear();
+        assert!(!policy.evaluate(&unsigned).allow);
+    }
+
+    #[test]
+    fn trusts_publisher_keys_within_namespace() {
+        let key = Pubkey::new_unique();
+        let mut publisher_rule = rule(&[]);
+        publisher_rule.publishers = vec!["kevredlabs".to_string()];
+        let policy = Policy { rules: vec![publisher_rule] };
+        let mut image = signed_by(key);
+        image.publishers.insert(
+            "kevredlabs".to_string(),
+            PublisherEvidence { namespace: "ghcr.io/kevredlabs/*".to_string(), keys: vec![key] },
+        );
+        assert!(policy.evaluate(&image).allow);
+
+        image.publishers.get_mut("kevredlabs").unwrap().namespace = "ghcr.io/elsewhere/*".to_string();
+        assert!(!policy.evaluate(&image).allow);
+    }
+
+    #[test]
+    fn rejects_unreachable_thresholds() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut two_of_two = rule(&[a, b]);
+        two_of_two.threshold = 2;
+        let err = Policy { rules: vec![two_of_two.clone()] }.validate().unwrap_err();
+        assert!(format!("{:#}", err).contains("threshold 2 can never be met"), "{:#}", err);
+        two_of_two.threshold = 0;
+        assert!(Policy { rules: vec![two_of_two] }.validate().is_err());
+        assert!(Policy { rules: vec![rule(&[a, b])] }.validate().is_ok());
+    }
+
+    #[test]
+    fn trusts_co_signers_added_before_signing() {
+        let (release, co_signer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let policy = Policy { rules: vec![rule(&[release])] };
+        let mut image = signed_by(co_signer);
+        assert!(!policy.evaluate(&image).allow);
+
+        let mut governed = Multisig {
+            signer: release,
+            members: vec![Pubkey::new_unique()],
+            threshold: 1,
+            co_signers: vec![CoSigner { key: co_signer, added_slot: 5 }],
+            proposal_count: 0,
+            bump: 255,
+        };
+        image.multisigs.insert(release, governed.clone());
+        assert!(policy.evaluate(&image).allow);
+
+        // Signed before the co-signer was added
+        governed.co_signers[0].added_slot = 20;
+        image.multisigs.insert(release, governed);
+        assert!(!policy.evaluate(&image).allow);
+    }
+
+    #[test]
+    fn revocation_modes() {
+        let key = Pubkey::new_unique();
+        let mut image = signed_by(key);
+        image.signatures[0].revoked_at = Some(NOW - 60);
+
+        let policy = Policy { rules: vec![rule(&[key])] };
+        assert!(!policy.evaluate(&image).allow);
+
+        let mut warn = rule(&[key]);
+        warn.revocation = RevocationMode::Warn;
+        let decision = Policy { rules: vec![warn] }.evaluate(&image);
+        assert!(decision.allow);
+        assert_eq!(decision.warnings.len(), 1);
+    }
+
+    #[test]
+    fn expiry_and_max_age() {
+        let key = Pubkey::new_unique();
+        let mut expired = signed_by(key);
+        expired.signatures[0].not_after = Some(NOW - 1);
+        let mut strict = rule(&[key]);
+        assert!(!Policy { rules: vec![strict.clone()] }.evaluate(&expired).allow);
+        strict.allow_expired = true;
+        assert!(Policy { rules: vec![strict] }.evaluate(&expired).allow);
+
+        let mut old = signed_by(key);
+        old.signatures[0].created_at = NOW - 200 * 86_400;
+        let mut aged = rule(&[key]);
+        aged.max_age = Some("180d".to_string());
+        assert!(!Policy { rules: vec![aged] }.evaluate(&old).allow);
+    }
+
+    #[test]
+    fn clusters_and_attestations() {
+        let key = Pubkey::new_unique();
+        let mut strict = rule(&[key]);
+        strict.clusters = vec!["mainnet-beta".to_string()];
+        strict.attestations = vec!["application/spdx+json".to_string()];
+        let policy = Policy { rules: vec![strict] };
+
+        let mut image = signed_by(key);
+        let decision = policy.evaluate(&image);
+        assert!(!decision.allow);
+        assert_eq!(decision.reasons.len(), 2);
+
+        image.cluster = "mainnet-beta".to_string();
+        image.attestation_types = vec!["application/spdx+json".to_string()];
+        assert!(policy.evaluate(&image).allow);
+    }
+
+    #[test]
+    fn onchain_policy_becomes_single_rule() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let account = PolicyAccount {
+            owner: Pubkey::new_unique(),
+            name: "prod".to_string(),
+            repository: "ghcr.io/kevredlabs/*".to_string(),
+            signers: vec![a, b],
+            threshold: 1,
+            version: 3,
+            frozen: true,
+            updated_slot: 42,
+            bump: 255,
+        };
+        let policy = Policy::from_onchain(&account);
+        policy.validate().unwrap();
+        assert!(policy.evaluate(&signed_by(b)).allow);
+        assert!(!policy.evaluate(&signed_by(Pubkey::new_unique())).allow);
+    }
+
+    #[test]
+    fn follows_rotation_to_trusted_key() {
+        let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let policy = Policy { rules: vec![rule(&[new])] };
+        let mut image = signed_by(old);
+        image.rotations.insert(old, (new, 100));
+        assert!(policy.evaluate(&image).allow);
+    }
+}
@@ -0,0 +1,529 @@
+//! OCI distribution client for the proof artifacts skelz attaches to images.
+//!
+//! Requests start anonymous or with the stored credential and follow the registry's
+//! `WWW-Authenticate` challenge: `Basic` resends the credential, `Bearer` exchanges it
+//! (or nothing, for anonymous pulls) at the token endpoint for a token scoped to the
+//! repository. Referrers are listed with the OCI 1.1 referrers API, falling back to the
+//! `sha256-<hex>` tag schema on registries without it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{ACCEPT, CONTENT_TYPE, LOCATION, WWW_AUTHENTICATE};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::credentials::Credential;
+
+pub const OCI_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+pub const OCI_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+pub const OCI_EMPTY: &str = "application/vnd.oci.empty.v1+json";
+const DOCKER_MANIFEST: &str = "application/vnd.docker.distribution.manifest.v2+json";
+const DOCKER_MANIFEST_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+
+const DOCKER_HUB: &str = "docker.io";
+const DOCKER_HUB_API: &str = "registry-1.docker.io";
+
+/// A canonical `registry/repository@sha256:...` reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReference {
+    /// Registry host, e.g. `ghcr.io` or `localhost:5000`
+    pub registry: String,
+    pub repository: String,
+    pub digest: String,
+}
+
+impl ImageReference {
+    /// Parse a reference pinned by digest; a first component without `.` or `:` (other
+    /// than `localhost`) is a Docker Hub repository
+    pub fn parse(reference: &str) -> Result<Self> {
+        let (name, digest) = reference
+            .split_once('@')
+            .ok_or_else(|| anyhow!("image reference {} must be pinned by digest (@sha256:...)", reference))?;
+        if !digest.starts_with("sha256:") {
+            anyhow::bail!("image reference {} must use a sha256 digest", reference);
+        }
+        let (first, rest) = name.split_once('/').unwrap_or(("", name));
+        let (registry, repository) = if first.contains('.') || first.contains(':') || first == "localhost" {
+            (first.to_ascii_lowercase(), rest.to_string())
+        } else if first.is_empty() {
+            (DOCKER_HUB.to_string(), format!("library/{}", rest))
+        } else {
+            (DOCKER_HUB.to_string(), name.to_string())
+        };
+        // A tag before the digest is ignored: the digest is what gets resolved
+        let repository = match repository.rsplit_once(':') {
+            Some((repository, tag)) if !tag.contains('/') => repository.to_string(),
+            _ => repository,
+        };
+        if repository.is_empty() {
+            anyhow::bail!("image reference {} has no repository", reference);
+        }
+        Ok(ImageReference { registry, repository, digest: digest.to_string() })
+    }
+}
+
+/// Content descriptor of a manifest or blob
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Descriptor {
+    #[serde(rename = "mediaType")]
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(rename = "artifactType", default, skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+/// An authentication challenge from `WWW-Authenticate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    /// Lower-cased scheme, `basic` or `bearer`
+    pub scheme: String,
+    pub params: BTreeMap<String, String>,
+}
+
+/// Parse `Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:a/b:pull"`
+pub fn parse_challenge(header: &str) -> Option<Challenge> {
+    let header = header.trim();
+    let (scheme, mut rest) = header.split_once(' ').unwrap_or((header, ""));
+    let mut params = BTreeMap::new();
+    loop {
+        rest = rest.trim_start_matches([' ', ',']);
+        let Some((name, after)) = rest.split_once('=') else { break };
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => after.split_at(after.find(',').unwrap_or(after.len())),
+        };
+        params.insert(name.trim().to_ascii_lowercase(), value.to_string());
+        rest = after;
+    }
+    if scheme.is_empty() {
+        return None;
+    }
+    Some(Challenge { scheme: scheme.to_ascii_lowercase(), params })
+}
+
+/// Registry API client for one registry host
+pub struct RegistryClient {
+    registry: String,
+    base: String,
+    credential: Option<Credential>,
+    http: Client,
+    /// Bearer tokens by scope
+    tokens: HashMap<String, String>,
+    /// The registry asked for Basic authentication
+    basic: bool,
+}
+
+impl RegistryClient {
+    /// Client for `registry`, anonymous when `credential` is None; `localhost` and
+    /// `127.0.0.1` are reached over plain HTTP
+    pub fn new(registry: &str, credential: Option<Credential>) -> Result<Self> {
+        let host = if registry == DOCKER_HUB { DOCKER_HUB_API } else { registry };
+        let scheme = match host.split(':').next() {
+            Some("localhost") | Some("127.0.0.1") => "http",
+            _ => "https",
+        };
+        Ok(RegistryClient {
+            registry: registry.to_string(),
+            base: format!("{}://{}", scheme, host),
+            credential,
+            http: Client::builder().timeout(Duration::from_secs(60)).build()?,
+            tokens: HashMap::new(),
+            basic: false,
+        })
+    }
+
+    /// Send a request built by `build`, answering one authentication challenge
+    fn send(&mut self, scope: &str, build: impl Fn(&Client) -> RequestBuilder) -> Result<Response> {
+        let response = self.authorize(build(&self.http), scope).send()?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        let challenge = response
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_challenge)
+            .ok_or_else(|| anyhow!("registry {} refused the request without an authentication challenge", self.registry))?;
+        match challenge.scheme.as_str() {
+            "basic" => {
+                if self.credential.is_none() {
+                    anyhow::bail!("registry {} requires credentials (see `skelz registry store`)", self.registry);
+                }
+                self.basic = true;
+            }
+            "bearer" => {
+                let token = self.fetch_token(&challenge, scope)?;
+                self.tokens.insert(scope.to_string(), token);
+            }
+            other => anyhow::bail!("registry {} asks for unsupported authentication scheme {}", self.registry, other),
+        }
+        let response = self.authorize(build(&self.http), scope).send()?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            anyhow::bail!("registry {} rejected {} for {}", self.registry, self.identity(), scope);
+        }
+        Ok(response)
+    }
+
+    fn authorize(&self, request: RequestBuilder, scope: &str) -> RequestBuilder {
+        match (self.tokens.get(scope), &self.credential) {
+            (Some(token), _) => request.bearer_auth(token),
+            (None, Some(credential)) if self.basic => request.basic_auth(&credential.username, Some(&credential.secret)),
+            _ => request,
+        }
+    }
+
+    fn identity(&self) -> String {
+        match &self.credential {
+            Some(credential) => format!("the credentials of {}", credential.username),
+            None => "anonymous access".to_string(),
+        }
+    }
+
+    /// Exchange the credential (or nothing) for a bearer token at the challenge's realm
+    fn fetch_token(&self, challenge: &Challenge, scope: &str) -> Result<String> {
+        let realm = challenge
+            .params
+            .get("realm")
+            .ok_or_else(|| anyhow!("registry {} bearer challenge has no realm", self.registry))?;
+        let mut query = Vec::new();
+        if let Some(service) = challenge.params.get("service") {
+            query.push(("service", service.as_str()));
+        }
+        query.push(("scope", challenge.params.get("scope").map_or(scope, String::as_str)));
+        let mut request = self.http.get(realm).query(&query);
+        if let Some(credential) = &self.credential {
+            request = request.basic_auth(&credential.username, Some(&credential.secret));
+        }
+        debug!(realm = %realm, scope, "fetching registry token");
+        let response = request.send().with_context(|| format!("registry token endpoint {}", realm))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "registry {} token endpoint refused {} (HTTP {})",
+                self.registry,
+                self.identity(),
+                response.status().as_u16()
+            );
+        }
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            token: Option<String>,
+            access_token: Option<String>,
+        }
+        let body: TokenResponse = response.json().context("registry token response is not JSON")?;
+        body.token
+            .or(body.access_token)
+            .ok_or_else(|| anyhow!("registry {} token response has no token", self.registry))
+    }
+
+    fn url(&self, repository: &str, path: &str) -> String {
+        format!("{}/v2/{}/{}", self.base, repository, path)
+    }
+
+    /// Descriptor of the manifest `reference` (a digest or tag); None when it does not exist
+    pub fn manifest(&mut self, repository: &str, reference: &str) -> Result<Option<(Descriptor, Vec<u8>)>> {
+        let url = self.url(repository, &format!("manifests/{}", reference));
+        let accept = [OCI_MANIFEST, OCI_INDEX, DOCKER_MANIFEST, DOCKER_MANIFEST_LIST].join(", ");
+        let response = self.send(&pull_scope(repository), |http| http.get(&url).header(ACCEPT, &accept))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = check(response, "get manifest")?;
+        let media_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or(OCI_MANIFEST)
+            .to_string();
+        let body = response.bytes()?.to_vec();
+        let descriptor = Descriptor {
+            media_type,
+            digest: sha256_digest(&body),
+            size: body.len() as u64,
+            artifact_type: None,
+            annotations: BTreeMap::new(),
+        };
+        Ok(Some((descriptor, body)))
+    }
+
+    /// Upload `content` unless the registry already has it; returns its digest
+    pub fn push_blob(&mut self, repository: &str, content: &[u8]) -> Result<String> {
+        let digest = sha256_digest(content);
+        let scope = push_scope(repository);
+        let blob_url = self.url(repository, &format!("blobs/{}", digest));
+        if self.send(&scope, |http| http.head(&blob_url))?.status().is_success() {
+            return Ok(digest);
+        }
+        let uploads = self.url(repository, "blobs/uploads/");
+        let response = check(self.send(&scope, |http| http.post(&uploads))?, "start blob upload")?;
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| anyhow!("registry {} did not return an upload location", self.registry))?;
+        let mut upload = reqwest::Url::parse(&self.base)?.join(location)?;
+        upload.query_pairs_mut().append_pair("digest", &digest);
+        let content = content.to_vec();
+        let response = self.send(&scope, |http| {
+            http.request(Method::PUT, upload.clone())
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .body(content.clone())
+        })?;
+        check(response, "upload blob")?;
+        Ok(digest)
+    }
+
+    /// Store `manifest` under `reference`; true when the registry processed its subject
+    /// (the `OCI-Subject` response header of registries with the referrers API)
+    pub fn put_manifest(&mut self, repository: &str, reference: &str, media_type: &str, manifest: &[u8]) -> Result<bool> {
+        let url = self.url(repository, &format!("manifests/{}", reference));
+        let manifest = manifest.to_vec();
+        let response = self.send(&push_scope(repository), |http| {
+            http.put(&url).header(CONTENT_TYPE, media_type).body(manifest.clone())
+        })?;
+        let response = check(response, "put manifest")?;
+        Ok(response.headers().contains_key("oci-subject"))
+    }
+
+    /// Artifacts whose subject is `digest`
+    pub fn referrers(&mut self, repository: &str, digest: &str) -> Result<Vec<Descriptor>> {
+        let url = self.url(repository, &format!("referrers/{}", digest));
+        let response = self.send(&pull_scope(repository), |http| http.get(&url).header(ACCEPT, OCI_INDEX))?;
+        if response.status() != StatusCode::NOT_FOUND {
+            let index: ImageIndex = check(response, "list referrers")?.json().context("referrers response is not an image index")?;
+            return Ok(index.manifests);
+        }
+        debug!(registry = %self.registry, "no referrers API, reading the fallback tag");
+        match self.manifest(repository, &referrers_tag(digest))? {
+            Some((_, body)) => Ok(serde_json::from_slice::<ImageIndex>(&body).context("referrers tag is not an image index")?.manifests),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Push the blobs and manifest of an artifact referring to `subject`
+    ///
+    /// `manifest` gets the subject descriptor and the empty config; on registries
+    /// without the referrers API the `sha256-<hex>` tag index is updated as well.
+    pub fn attach(&mut self, subject: &ImageReference, mut manifest: serde_json::Value, layers: &[&[u8]]) -> Result<Descriptor> {
+        let (subject_descriptor, _) = self
+            .manifest(&subject.repository, &subject.digest)?
+            .ok_or_else(|| anyhow!("image {}/{}@{} not found", subject.registry, subject.repository, subject.digest))?;
+        for layer in layers {
+            self.push_blob(&subject.repository, layer)?;
+        }
+        let config = self.push_blob(&subject.repository, b"{}")?;
+        manifest["config"] = json!({ "mediaType": OCI_EMPTY, "digest": config, "size": 2 });
+        manifest["subject"] = json!({
+            "mediaType": subject_descriptor.media_type,
+            "digest": subject_descriptor.digest,
+            "size": subject_descriptor.size,
+        });
+        let body = serde_json::to_vec(&manifest)?;
+        let descriptor = Descriptor {
+            media_type: OCI_MANIFEST.to_string(),
+            digest: sha256_digest(&body),
+            size: body.len() as u64,
+            artifact_type: manifest["artifactType"].as_str().map(str::to_string),
+            annotations: serde_json::from_value(manifest["annotations"].clone()).unwrap_or_default(),
+        };
+        let processed = self.put_manifest(&subject.repository, &descriptor.digest, OCI_MANIFEST, &body)?;
+        if !processed {
+            let tag = referrers_tag(&subject.digest);
+            let mut index = match self.manifest(&subject.repository, &tag)? {
+                Some((_, body)) => serde_json::from_slice(&body).context("referrers tag is not an image index")?,
+                None => ImageIndex { schema_version: 2, media_type: OCI_INDEX.to_string(), manifests: Vec::new() },
+            };
+            if !index.manifests.iter().any(|existing| existing.digest == descriptor.digest) {
+                index.manifests.push(descriptor.clone());
+                self.put_manifest(&subject.repository, &tag, OCI_INDEX, &serde_json::to_vec(&index)?)?;
+            }
+        }
+        Ok(descriptor)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ImageIndex {
+    #[serde(rename = "schemaVersion")]
+    schema_version: u32,
+    #[serde(rename = "mediaType", default)]
+    media_type: String,
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+}
+
+fn pull_scope(repository: &str) -> String {
+    format!("repository:{}:pull", repository)
+}
+
+fn push_scope(repository: &str) -> String {
+    format!("repository:{}:pull,push", repository)
+}
+
+/// Tag listing the referrers of `digest` on registries without the referrers API
+fn referrers_tag(digest: &str) -> String {
+    digest.replace(':', "-")
+}
+
+fn sha256_digest(content: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(content)))
+}
+
+fn check(response: Response, action: &str) -> Result<Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let url = response.url().clone();
+    let body = response.text().unwrap_or_default();
+    Err(anyhow!("{} at {} failed (HTTP {}): {}", action, url, status.as_u16(), body.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn parses_references_and_challenges() {
+        let reference = ImageReference::parse("ghcr.io/acme/app:v1@sha256:abc").unwrap();
+        assert_eq!(reference, ImageReference { registry: "ghcr.io".into(), repository: "acme/app".into(), digest: "sha256:abc".into() });
+        assert_eq!(ImageReference::parse("nginx@sha256:abc").unwrap().repository, "library/nginx");
+        assert_eq!(ImageReference::parse("localhost:5000/app@sha256:abc").unwrap().registry, "localhost:5000");
+        assert!(ImageReference::parse("ghcr.io/acme/app:v1").is_err());
+
+        let challenge = parse_challenge(r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:acme/app:pull,push""#).unwrap();
+        assert_eq!(challenge.scheme, "bearer");
+        assert_eq!(challenge.params["realm"], "https://ghcr.io/token");
+        assert_eq!(challenge.params["scope"], "repository:acme/app:pull,push");
+        assert_eq!(parse_challenge(r#"Basic realm="registry""#).unwrap().scheme, "basic");
+    }
+
+    /// Stored content by path: media type and body
+    type Contents = Arc<Mutex<HashMap<String, (String, Vec<u8>)>>>;
+
+    /// A registry that requires bearer tokens and has no referrers API
+    fn serve_registry(allow_anonymous: bool) -> (String, Contents) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = listener.local_addr().unwrap().to_string();
+        let store: Contents = Arc::default();
+        let image = br#"{"schemaVersion":2}"#.to_vec();
+        store.lock().unwrap().insert(format!("/v2/acme/app/manifests/{}", sha256_digest(&image)), (OCI_MANIFEST.into(), image));
+        let (shared, realm) = (store.clone(), format!("http://{}/token", host));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let (method, target) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+                let (mut auth, mut content_type, mut length) = (String::new(), String::new(), 0usize);
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    let (name, value) = header.split_once(':').unwrap();
+                    match name.to_ascii_lowercase().as_str() {
+                        "authorization" => auth = value.trim().to_string(),
+                        "content-type" => content_type = value.trim().to_string(),
+                        "content-length" => length = value.trim().parse().unwrap(),
+                        _ => {}
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let path = target.split('?').next().unwrap().to_string();
+
+                let basic = format!("Basic {}", base64::Engine::encode(&base64::engine::general_purpose::STANDARD, "bot:secret"));
+                let (status, headers, reply): (u16, Vec<String>, Vec<u8>) = if path == "/token" {
+                    let push = target.contains("push");
+                    if auth == basic || (allow_anonymous && auth.is_empty() && !push) {
+                        (200, vec![], br#"{"token":"granted"}"#.to_vec())
+                    } else {
+                        (401, vec![], b"{}".to_vec())
+                    }
+                } else if auth != "Bearer granted" {
+                    (401, vec![format!("WWW-Authenticate: Bearer realm=\"{}\",service=\"test\"", realm)], b"{}".to_vec())
+                } else if path.contains("/referrers/") {
+                    (404, vec![], b"{}".to_vec())
+                } else if method == "POST" {
+                    (202, vec!["Location: /v2/acme/app/blobs/uploads/1".into()], vec![])
+                } else if method == "PUT" && path.contains("/blobs/uploads/") {
+                    let digest = target.split("digest=").nth(1).unwrap().replace("%3A", ":");
+                    shared.lock().unwrap().insert(format!("/v2/acme/app/blobs/{}", digest), (String::new(), body));
+                    (201, vec![], vec![])
+                } else if method == "PUT" {
+                    shared.lock().unwrap().insert(path, (content_type, body));
+                    (201, vec![], vec![])
+                } else {
+                    match shared.lock().unwrap().get(&path) {
+                        Some((content_type, body)) => (200, vec![format!("Content-Type: {}", content_type)], body.clone()),
+                        None => (404, vec![], b"{}".to_vec()),
+                    }
+                };
+                let mut response = format!("HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n", status, reply.len());
+                for header in headers {
+                    response.push_str(&header);
+                    response.push_str("\r\n");
+                }
+                response.push_str("\r\n");
+                stream.write_all(response.as_bytes()).unwrap();
+                if method != "HEAD" {
+                    stream.write_all(&reply).unwrap();
+                }
+            }
+        });
+        (host, store)
+    }
+
+    #[test]
+    fn attaches_and_lists_referrers_through_bearer_challenges() {
+        let (host, _) = serve_registry(false);
+        let image = ImageReference::parse(&format!("{}/acme/app@{}", host, sha256_digest(br#"{"schemaVersion":2}"#))).unwrap();
+        let bot = Credential { username: "bot".into(), secret: "secret".into() };
+
+        let mut anonymous = RegistryClient::new(&host, None).unwrap();
+        let err = anonymous.referrers(&image.repository, &image.digest).unwrap_err();
+        assert!(err.to_string().contains("refused anonymous access"), "{}", err);
+
+        let mut client = RegistryClient::new(&host, Some(bot)).unwrap();
+        let manifest = json!({
+            "schemaVersion": 2,
+            "mediaType": OCI_MANIFEST,
+            "artifactType": "application/vnd.skelz.proof.v1+json",
+            "layers": [],
+            "annotations": { "skelz.signature": "5sig" },
+        });
+        let descriptor = client.attach(&image, manifest, &[b"proof"]).unwrap();
+        let referrers = client.referrers(&image.repository, &image.digest).unwrap();
+        assert_eq!(referrers, vec![descriptor]);
+        assert_eq!(referrers[0].annotations["skelz.signature"], "5sig");
+    }
+
+    #[test]
+    fn pulls_anonymously_when_the_registry_allows_it() {
+        let (host, _) = serve_registry(true);
+        let digest = sha256_digest(br#"{"schemaVersion":2}"#);
+        let mut client = RegistryClient::new(&host, None).unwrap();
+        let (descriptor, _) = client.manifest("acme/app", &digest).unwrap().unwrap();
+        assert_eq!(descriptor.digest, digest);
+        assert!(client.referrers("acme/app", &digest).unwrap().is_empty());
+        assert!(client.push_blob("acme/app", b"proof").is_err());
+    }
+}
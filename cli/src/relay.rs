@@ -0,0 +1,672 @@
+//! Fee relay for signing without a funded wallet.
+//!
+//! `skelz relay` runs a small HTTP service holding a funded fee payer. Clients
+//! (`skelz sign --relay-url`) build the `write_signature` transaction with the
+//! relay's key as fee payer, sign it with their identity key only and post it.
+//! The relay checks that the transaction does nothing but record one signature
+//! (plus compute budget within limits), enforces a per-identity quota and a
+//! spend limit across all identities, co-signs as fee payer and submits.
+//!
+//! API (JSON):
+//! - `GET /v1/info` → `{"fee_payer", "program_id", "quota", "quota_window_secs", "spend_limit"}`
+//! - `POST /v1/relay` `{"transaction": "<base64 bincode>"}` →
+//!   `{"signature", "signer", "digest", "remaining"}`; errors are `{"error"}` with
+//!   status 400 (rejected), 403 (signer not allowed), 429 (quota or spend limit) or
+//!   502 (submit failed)
+
+use std::collections::{HashMap, VecDeque};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use solana_sdk::compute_budget;
+use solana_sdk::sanitize::Sanitize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Signature as TxSignature, Signer};
+use solana_sdk::transaction::Transaction;
+use tracing::{info, warn};
+
+use crate::http;
+use crate::rpc::RpcPool;
+use crate::skelz::client::args;
+use crate::{signature_pda, SkelzConfig, SkelzSigner, SIGNATURE_ACCOUNT_SPACE};
+
+// ComputeBudgetInstruction tags
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+/// Base fee per transaction signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Compute-unit limit of an instruction when the transaction sets none
+const DEFAULT_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+/// What the relay is willing to pay for
+#[derive(Debug, Clone)]
+pub struct RelayPolicy {
+    /// Transactions relayed per signer within `quota_window`
+    pub quota: u32,
+    pub quota_window: Duration,
+    /// Lamports spent on fees and rent within `quota_window`, across all signers
+    pub spend_limit: u64,
+    /// Identity keys allowed to use the relay
+    pub allowed_signers: Vec<Pubkey>,
+    /// Relay for any identity key; `allowed_signers` is ignored
+    pub allow_any_signer: bool,
+    /// Highest compute-unit price accepted, in micro-lamports
+    pub max_priority_fee: u64,
+    /// Highest compute-unit limit accepted
+    pub max_compute_unit_limit: u32,
+}
+
+impl Default for RelayPolicy {
+    fn default() -> Self {
+        RelayPolicy {
+            quota: 100,
+            quota_window: Duration::from_secs(24 * 3600),
+            spend_limit: 1_000_000_000,
+            allowed_signers: Vec::new(),
+            allow_any_signer: false,
+            max_priority_fee: 100_000,
+            max_compute_unit_limit: 200_000,
+        }
+    }
+}
+
+/// Why a transaction was not relayed
+#[derive(Debug, thiserror::Error)]
+pub enum RelayError {
+    #[error("{0}")]
+    Rejected(String),
+    #[error("signer {0} is not allowed to use this relay")]
+    NotAllowed(Pubkey),
+    #[error("quota exceeded for {signer}: {quota} transactions per {window_secs}s")]
+    QuotaExceeded { signer: Pubkey, quota: u32, window_secs: u64 },
+    #[error("relay spend limit reached: {limit} lamports per {window_secs}s")]
+    SpendLimitExceeded { limit: u64, window_secs: u64 },
+    #[error("submit failed: {0}")]
+    Submit(String),
+}
+
+impl RelayError {
+    fn status(&self) -> u16 {
+        match self {
+            RelayError::Rejected(_) => 400,
+            RelayError::NotAllowed(_) => 403,
+            RelayError::QuotaExceeded { .. } | RelayError::SpendLimitExceeded { .. } => 429,
+            RelayError::Submit(_) => 502,
+        }
+    }
+}
+
+fn rejected(message: impl Into<String>) -> RelayError {
+    RelayError::Rejected(message.into())
+}
+
+/// A transaction that passed [`validate_relay_transaction`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedRelay {
+    pub signer: Pubkey,
+    pub digest: String,
+}
+
+/// Check that `transaction` only records one image signature paid by `fee_payer`
+///
+/// Every signature except the fee payer's must already be present and valid.
+pub fn validate_relay_transaction(
+    transaction: &Transaction,
+    program_id: &Pubkey,
+    fee_payer: &Pubkey,
+    policy: &RelayPolicy,
+) -> Result<ValidatedRelay, RelayError> {
+    let budget = (policy.max_compute_unit_limit, policy.max_priority_fee);
+    let instructions =
+        sponsored_instructions(transaction, program_id, fee_payer, "relay", budget).map_err(RelayError::Rejected)?;
+
+    let mut write = None;
+    for instruction in instructions {
+        let data = instruction
+            .data
+            .strip_prefix(&args::WriteSignature::DISCRIMINATOR[..])
+            .ok_or_else(|| rejected("only write_signature is relayed"))?;
+        let decoded = args::WriteSignature::deserialize(&mut &data[..])
+            .map_err(|e| rejected(format!("write_signature arguments do not decode: {}", e)))?;
+        let key = |position| instruction.account(position).map_err(RelayError::Rejected);
+        let (signer, payer, pda) = (key(0)?, key(1)?, key(2)?);
+        if payer != *fee_payer {
+            return Err(rejected("write_signature payer must be the relay key"));
+        }
+        if signer == *fee_payer {
+            return Err(rejected("the relay key cannot be the image signer"));
+        }
+        if pda != signature_pda(program_id, &decoded.digest) {
+            return Err(rejected("signature account does not match the digest"));
+        }
+        if write.replace(ValidatedRelay { signer, digest: decoded.digest }).is_some() {
+            return Err(rejected("only one write_signature per transaction is relayed"));
+        }
+    }
+    let write = write.ok_or_else(|| rejected("transaction does not contain write_signature"))?;
+    check_cosignatures(transaction).map_err(RelayError::Rejected)?;
+
+    if !policy.allow_any_signer && !policy.allowed_signers.contains(&write.signer) {
+        return Err(RelayError::NotAllowed(write.signer));
+    }
+    Ok(write)
+}
+
+/// A program instruction of a sanitized transaction
+pub(crate) struct ProgramInstruction<'a> {
+    pub data: &'a [u8],
+    accounts: &'a [u8],
+    keys: &'a [Pubkey],
+}
+
+impl ProgramInstruction<'_> {
+    /// The account at `position` in the instruction's account list
+    pub fn account(&self, position: usize) -> Result<Pubkey, String> {
+        let index = self.accounts.get(position).ok_or_else(|| format!("instruction is missing account {}", position))?;
+        Ok(self.keys[*index as usize])
+    }
+}
+
+/// Sanitize a transaction paid for by `sponsor` and return its `program_id` instructions
+///
+/// Compute-budget instructions are checked against `budget` (unit limit, priority
+/// fee) and dropped; an instruction for any other program is refused. `role`
+/// names the sponsor in errors.
+pub(crate) fn sponsored_instructions<'a>(
+    transaction: &'a Transaction,
+    program_id: &Pubkey,
+    sponsor: &Pubkey,
+    role: &str,
+    budget: (u32, u64),
+) -> Result<Vec<ProgramInstruction<'a>>, String> {
+    // Account indexes are only guaranteed in range for a sanitized message
+    transaction.sanitize().map_err(|e| format!("malformed transaction: {}", e))?;
+    let message = &transaction.message;
+    if message.account_keys.first() != Some(sponsor) {
+        return Err(format!("fee payer must be the {} key {}", role, sponsor));
+    }
+    let mut instructions = Vec::new();
+    for instruction in &message.instructions {
+        let program = message.account_keys[instruction.program_id_index as usize];
+        if program == compute_budget::id() {
+            check_compute_budget(&instruction.data, budget.0, budget.1)?;
+        } else if program == *program_id {
+            instructions.push(ProgramInstruction {
+                data: &instruction.data,
+                accounts: &instruction.accounts,
+                keys: &message.account_keys,
+            });
+        } else {
+            return Err(format!("instruction for program {} is not accepted by the {}", program, role));
+        }
+    }
+    Ok(instructions)
+}
+
+/// Check that every signature but the fee payer's is present and valid
+pub(crate) fn check_cosignatures(transaction: &Transaction) -> Result<(), String> {
+    let required = transaction.message.header.num_required_signatures as usize;
+    if transaction.signatures.len() != required {
+        return Err("transaction has the wrong number of signatures".to_string());
+    }
+    let valid = transaction.verify_with_results();
+    for (position, ok) in valid.iter().enumerate().skip(1) {
+        if !ok {
+            return Err(format!("missing or invalid signature for {}", transaction.message.account_keys[position]));
+        }
+    }
+    Ok(())
+}
+
+/// Accept only compute-unit limit and price instructions within the given caps
+fn check_compute_budget(data: &[u8], max_compute_unit_limit: u32, max_priority_fee: u64) -> Result<(), String> {
+    match data.split_first() {
+        Some((&SET_COMPUTE_UNIT_LIMIT, value)) => {
+            let limit = u32::from_le_bytes(value.try_into().map_err(|_| "malformed compute-unit limit")?);
+            if limit > max_compute_unit_limit {
+                return Err(format!("compute-unit limit {} exceeds {}", limit, max_compute_unit_limit));
+            }
+        }
+        Some((&SET_COMPUTE_UNIT_PRICE, value)) => {
+            let price = u64::from_le_bytes(value.try_into().map_err(|_| "malformed compute-unit price")?);
+            if price > max_priority_fee {
+                return Err(format!("priority fee {} exceeds {} micro-lamports", price, max_priority_fee));
+            }
+        }
+        _ => return Err("only compute-unit limit and price instructions are accepted".to_string()),
+    }
+    Ok(())
+}
+
+/// Lamports the fee payer spends on `transaction`: signature fees, priority fee and `rent`
+///
+/// Expects a transaction that passed [`validate_relay_transaction`].
+pub fn relay_cost(transaction: &Transaction, rent: u64) -> u64 {
+    let message = &transaction.message;
+    let (mut limit, mut price) = (None, 0u64);
+    let mut other_instructions = 0u64;
+    for instruction in &message.instructions {
+        if message.account_keys[instruction.program_id_index as usize] != compute_budget::id() {
+            other_instructions += 1;
+            continue;
+        }
+        match instruction.data.split_first() {
+            Some((&SET_COMPUTE_UNIT_LIMIT, value)) => {
+                limit = value.try_into().ok().map(|v| u32::from_le_bytes(v) as u64);
+            }
+            Some((&SET_COMPUTE_UNIT_PRICE, value)) => {
+                price = value.try_into().map(u64::from_le_bytes).unwrap_or(0);
+            }
+            _ => {}
+        }
+    }
+    let limit = limit.unwrap_or(DEFAULT_COMPUTE_UNIT_LIMIT * other_instructions);
+    let priority_fee = (price as u128 * limit as u128).div_ceil(1_000_000) as u64;
+    LAMPORTS_PER_SIGNATURE * message.header.num_required_signatures as u64 + priority_fee + rent
+}
+
+/// Sliding-window count of relayed transactions per signer (in memory)
+#[derive(Debug, Default)]
+pub struct QuotaTracker {
+    used: HashMap<Pubkey, VecDeque<i64>>,
+}
+
+impl QuotaTracker {
+    /// Transactions `signer` may still relay at `now`
+    pub fn remaining(&mut self, signer: &Pubkey, now: i64, policy: &RelayPolicy) -> u32 {
+        let window_start = now - policy.quota_window.as_secs() as i64;
+        let used = self.used.entry(*signer).or_default();
+        while used.front().is_some_and(|&t| t <= window_start) {
+            used.pop_front();
+        }
+        policy.quota.saturating_sub(used.len() as u32)
+    }
+
+    pub fn record(&mut self, signer: &Pubkey, now: i64) {
+        self.used.entry(*signer).or_default().push_back(now);
+    }
+}
+
+/// Sliding-window total of lamports spent by the relay, whoever signed (in memory)
+#[derive(Debug, Default)]
+pub struct SpendTracker {
+    spent: VecDeque<(i64, u64)>,
+}
+
+impl SpendTracker {
+    /// Lamports the relay may still spend at `now`
+    pub fn remaining(&mut self, now: i64, policy: &RelayPolicy) -> u64 {
+        let window_start = now - policy.quota_window.as_secs() as i64;
+        while self.spent.front().is_some_and(|&(t, _)| t <= window_start) {
+            self.spent.pop_front();
+        }
+        policy.spend_limit.saturating_sub(self.spent.iter().map(|&(_, lamports)| lamports).sum())
+    }
+
+    pub fn record(&mut self, now: i64, lamports: u64) {
+        self.spent.push_back((now, lamports));
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayInfo {
+    pub fee_payer: String,
+    pub program_id: String,
+    pub quota: u32,
+    pub quota_window_secs: u64,
+    /// Lamports the relay spends per window across all signers
+    #[serde(default)]
+    pub spend_limit: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RelayRequest {
+    /// Base64 bincode transaction, signed by everyone but the fee payer
+    transaction: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayReceipt {
+    pub signature: String,
+    pub signer: String,
+    pub digest: String,
+    /// Transactions the signer may still relay in the current window
+    pub remaining: u32,
+}
+
+/// The relay service state
+pub struct Relay {
+    program_id: Pubkey,
+    fee_payer: SkelzSigner,
+    pool: RpcPool,
+    policy: RelayPolicy,
+    quotas: QuotaTracker,
+    spend: SpendTracker,
+    /// Rent-exempt balance of a signature account, paid by the relay
+    rent: u64,
+}
+
+impl Relay {
+    /// Refuses a policy that names no signer unless it opts into relaying for any key
+    pub fn new(cfg: &SkelzConfig, fee_payer: SkelzSigner, policy: RelayPolicy) -> Result<Self> {
+        if policy.allowed_signers.is_empty() && !policy.allow_any_signer {
+            anyhow::bail!("the relay needs at least one allowed signer, or allow_any_signer to pay for any key");
+        }
+        let pool = RpcPool::from_config(cfg)?;
+        Ok(Relay {
+            program_id: cfg.program_id()?,
+            fee_payer,
+            pool,
+            policy,
+            quotas: QuotaTracker::default(),
+            spend: SpendTracker::default(),
+            rent: Rent::default().minimum_balance(SIGNATURE_ACCOUNT_SPACE),
+        })
+    }
+
+    pub fn info(&self) -> RelayInfo {
+        RelayInfo {
+            fee_payer: self.fee_payer.pubkey().to_string(),
+            program_id: self.program_id.to_string(),
+            quota: self.policy.quota,
+            quota_window_secs: self.policy.quota_window.as_secs(),
+            spend_limit: self.policy.spend_limit,
+        }
+    }
+
+    /// Validate, check the quota, co-sign and submit
+    pub fn relay(&mut self, mut transaction: Transaction, now: i64) -> Result<RelayReceipt, RelayError> {
+        let validated = validate_relay_transaction(&transaction, &self.program_id, &self.fee_payer.pubkey(), &self.policy)?;
+        if self.quotas.remaining(&validated.signer, now, &self.policy) == 0 {
+            return Err(RelayError::QuotaExceeded {
+                signer: validated.signer,
+                quota: self.policy.quota,
+                window_secs: self.policy.quota_window.as_secs(),
+            });
+        }
+        let cost = relay_cost(&transaction, self.rent);
+        if cost > self.spend.remaining(now, &self.policy) {
+            return Err(RelayError::SpendLimitExceeded {
+                limit: self.policy.spend_limit,
+                window_secs: self.policy.quota_window.as_secs(),
+            });
+        }
+
+        let blockhash = transaction.message.recent_blockhash;
+        transaction
+            .try_partial_sign(&[&self.fee_payer], blockhash)
+            .map_err(|e| RelayError::Submit(format!("co-sign: {}", e)))?;
+        // The transaction is fixed, so retrying the send cannot double-spend
+        let signature = self
+            .pool
+            .call("send relayed transaction", |rpc| Ok(rpc.send_and_confirm_transaction(&transaction)?))
+            .map_err(|e| RelayError::Submit(e.to_string()))?;
+
+        self.quotas.record(&validated.signer, now);
+        self.spend.record(now, cost);
+        let remaining = self.quotas.remaining(&validated.signer, now, &self.policy);
+        info!(%signature, signer = %validated.signer, digest = %validated.digest, remaining, "relayed signature");
+        Ok(RelayReceipt {
+            signature: signature.to_string(),
+            signer: validated.signer.to_string(),
+            digest: validated.digest,
+            remaining,
+        })
+    }
+
+    /// Serve requests one at a time until the listener fails
+    pub fn serve(&mut self, listener: TcpListener) -> Result<()> {
+        info!(addr = %listener.local_addr()?, fee_payer = %self.fee_payer.pubkey(), "relay listening");
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!(error = %e, "accept failed");
+                    continue;
+                }
+            };
+            if let Err(e) = self.handle_connection(&mut stream) {
+                warn!(error = %e, "request failed");
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_connection(&mut self, stream: &mut TcpStream) -> Result<()> {
+        http::handle_json(stream, |method, path, body| self.route(method, path, body))
+    }
+
+    fn route(&mut self, method: &str, path: &str, body: &[u8]) -> (u16, serde_json::Value) {
+        match (method, path) {
+            ("GET", "/v1/info") => (200, serde_json::to_value(self.info()).unwrap_or_default()),
+            ("POST", "/v1/relay") => {
+                let transaction = serde_json::from_slice::<RelayRequest>(body)
+                    .map_err(|e| rejected(format!("invalid request: {}", e)))
+                    .and_then(|request| decode_transaction(&request.transaction).map_err(|e| rejected(e.to_string())));
+                let result = transaction.and_then(|tx| self.relay(tx, chrono::Utc::now().timestamp()));
+                match result {
+                    Ok(receipt) => (200, serde_json::to_value(receipt).unwrap_or_default()),
+                    Err(e) => {
+                        warn!(error = %e, "relay refused transaction");
+                        (e.status(), serde_json::json!({ "error": e.to_string() }))
+                    }
+                }
+            }
+            _ => (404, serde_json::json!({ "error": format!("no route for {} {}", method, path) })),
+        }
+    }
+}
+
+pub(crate) fn decode_transaction(encoded: &str) -> Result<Transaction> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .context("transaction is not valid base64")?;
+    let transaction: Transaction = bincode::deserialize(&bytes).context("transaction does not decode")?;
+    transaction.sanitize().map_err(|e| anyhow!("malformed transaction: {}", e))?;
+    Ok(transaction)
+}
+
+/// Client side of a relay, used by `skelz sign --relay-url`
+pub struct RelayClient {
+    url: String,
+    client: reqwest::blocking::Client,
+    pub info: RelayInfo,
+    pub fee_payer: Pubkey,
+}
+
+impl RelayClient {
+    /// Fetch the relay's fee payer and limits; the relay must serve `program_id`
+    pub fn connect(url: &str, program_id: &Pubkey) -> Result<Self> {
+        let url = url.trim_end_matches('/').to_string();
+        let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(90)).build()?;
+        let info: RelayInfo = client
+            .get(format!("{}/v1/info", url))
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .with_context(|| format!("relay {}: fetch info", url))?;
+        if info.program_id != program_id.to_string() {
+            anyhow::bail!("relay {} serves program {}, not {}", url, info.program_id, program_id);
+        }
+        let fee_payer = info
+            .fee_payer
+            .parse()
+            .map_err(|e| anyhow!("relay {} returned an invalid fee payer: {}", url, e))?;
+        Ok(RelayClient { url, client, info, fee_payer })
+    }
+
+    /// Post a transaction signed by everyone but the relay
+    pub fn submit(&self, transaction: &Transaction) -> Result<RelayReceipt> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(transaction)?);
+        let response = self
+            .client
+            .post(format!("{}/v1/relay", self.url))
+            .json(&RelayRequest { transaction: encoded })
+            .send()
+            .with_context(|| format!("relay {}: submit", self.url))?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().with_context(|| format!("relay {}: read response", self.url))?;
+        if !status.is_success() {
+            let error = body["error"].as_str().unwrap_or("no details");
+            anyhow::bail!("relay {} refused the transaction ({}): {}", self.url, status, error);
+        }
+        let receipt: RelayReceipt = serde_json::from_value(body)?;
+        receipt
+            .signature
+            .parse::<TxSignature>()
+            .map_err(|e| anyhow!("relay returned an invalid signature: {}", e))?;
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use anchor_lang::InstructionData;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::signature::Keypair;
+
+    /// A `write_signature` instruction paid by `payer` (the relay or attester key)
+    pub(crate) fn write_instruction(signer: &Pubkey, payer: &Pubkey, digest: &str) -> Instruction {
+        Instruction {
+            program_id: crate::skelz::ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*signer, true),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new(signature_pda(&crate::skelz::ID, digest), false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            data: args::WriteSignature { digest: digest.to_string(), not_after: None }.data(),
+        }
+    }
+
+    /// A transaction paid by `payer`, signed by `signer` only
+    pub(crate) fn signed(instructions: &[Instruction], payer: &Pubkey, signer: &Keypair) -> Transaction {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(payer));
+        transaction.partial_sign(&[signer], Hash::new_unique());
+        transaction
+    }
+
+    #[test]
+    fn validates_relayed_transactions() {
+        let relay = Keypair::new().pubkey();
+        let signer = Keypair::new();
+        let policy = RelayPolicy { allow_any_signer: true, ..RelayPolicy::default() };
+        let write = write_instruction(&signer.pubkey(), &relay, "sha256:abc");
+        let budget = ComputeBudgetInstruction::set_compute_unit_limit(20_000);
+
+        let ok = signed(&[budget.clone(), write.clone()], &relay, &signer);
+        assert_eq!(
+            validate_relay_transaction(&ok, &crate::skelz::ID, &relay, &policy).unwrap(),
+            ValidatedRelay { signer: signer.pubkey(), digest: "sha256:abc".to_string() }
+        );
+
+        let other_payer = Keypair::new().pubkey();
+        let wrong_payer = signed(std::slice::from_ref(&write), &other_payer, &signer);
+        assert!(matches!(validate_relay_transaction(&wrong_payer, &crate::skelz::ID, &relay, &policy), Err(RelayError::Rejected(_))));
+
+        let transfer = solana_sdk::system_instruction::transfer(&relay, &signer.pubkey(), 1_000_000);
+        let drain = signed(&[write.clone(), transfer], &relay, &signer);
+        assert!(matches!(validate_relay_transaction(&drain, &crate::skelz::ID, &relay, &policy), Err(RelayError::Rejected(_))));
+
+        let pricey = ComputeBudgetInstruction::set_compute_unit_price(policy.max_priority_fee + 1);
+        let pricey = signed(&[pricey, write.clone()], &relay, &signer);
+        assert!(matches!(validate_relay_transaction(&pricey, &crate::skelz::ID, &relay, &policy), Err(RelayError::Rejected(_))));
+
+        let unsigned = Transaction::new_with_payer(std::slice::from_ref(&write), Some(&relay));
+        assert!(matches!(validate_relay_transaction(&unsigned, &crate::skelz::ID, &relay, &policy), Err(RelayError::Rejected(_))));
+
+        let restricted = RelayPolicy { allowed_signers: vec![Pubkey::new_unique()], ..RelayPolicy::default() };
+        assert!(matches!(validate_relay_transaction(&ok, &crate::skelz::ID, &relay, &restricted), Err(RelayError::NotAllowed(_))));
+        let allowed = RelayPolicy { allowed_signers: vec![signer.pubkey()], ..RelayPolicy::default() };
+        assert!(validate_relay_transaction(&ok, &crate::skelz::ID, &relay, &allowed).is_ok());
+
+        let mut out_of_range = ok.clone();
+        out_of_range.message.instructions[1].program_id_index = 200;
+        assert!(matches!(validate_relay_transaction(&out_of_range, &crate::skelz::ID, &relay, &policy), Err(RelayError::Rejected(_))));
+        let mut bad_account = ok.clone();
+        bad_account.message.instructions[1].accounts[0] = 200;
+        assert!(matches!(validate_relay_transaction(&bad_account, &crate::skelz::ID, &relay, &policy), Err(RelayError::Rejected(_))));
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(&out_of_range).unwrap());
+        assert!(decode_transaction(&encoded).is_err());
+    }
+
+    #[test]
+    fn enforces_quota_window() {
+        let policy = RelayPolicy { quota: 2, quota_window: Duration::from_secs(60), ..RelayPolicy::default() };
+        let signer = Pubkey::new_unique();
+        let mut quotas = QuotaTracker::default();
+        assert_eq!(quotas.remaining(&signer, 1000, &policy), 2);
+        quotas.record(&signer, 1000);
+        quotas.record(&signer, 1030);
+        assert_eq!(quotas.remaining(&signer, 1030, &policy), 0);
+        assert_eq!(quotas.remaining(&Pubkey::new_unique(), 1030, &policy), 2);
+        assert_eq!(quotas.remaining(&signer, 1061, &policy), 1);
+        assert_eq!(quotas.remaining(&signer, 1100, &policy), 2);
+    }
+
+    #[test]
+    fn enforces_spend_limit_across_signers() {
+        let relay = Pubkey::new_unique();
+        let signer = Keypair::new();
+        let write = write_instruction(&signer.pubkey(), &relay, "sha256:abc");
+        let plain = signed(std::slice::from_ref(&write), &relay, &signer);
+        assert_eq!(relay_cost(&plain, 1_000), 2 * LAMPORTS_PER_SIGNATURE + 1_000);
+        let budget = [
+            ComputeBudgetInstruction::set_compute_unit_limit(20_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1_500),
+        ];
+        let priced = signed(&[budget[0].clone(), budget[1].clone(), write], &relay, &signer);
+        assert_eq!(relay_cost(&priced, 1_000), 2 * LAMPORTS_PER_SIGNATURE + 30 + 1_000);
+
+        let policy = RelayPolicy { spend_limit: 50_000, quota_window: Duration::from_secs(60), ..RelayPolicy::default() };
+        let mut spend = SpendTracker::default();
+        spend.record(1000, 30_000);
+        spend.record(1030, 15_000);
+        assert_eq!(spend.remaining(1030, &policy), 5_000);
+        assert_eq!(spend.remaining(1061, &policy), 35_000);
+        assert_eq!(spend.remaining(1100, &policy), 50_000);
+    }
+
+    #[test]
+    fn refuses_to_start_without_allowed_signers() {
+        let cfg = SkelzConfig { rpc_url: "http://127.0.0.1:1".to_string(), ..SkelzConfig::default() };
+        let err = Relay::new(&cfg, Keypair::new().into(), RelayPolicy::default()).err().unwrap();
+        assert!(err.to_string().contains("allowed signer"), "{}", err);
+        let open = RelayPolicy { allow_any_signer: true, ..RelayPolicy::default() };
+        assert!(Relay::new(&cfg, Keypair::new().into(), open).is_ok());
+    }
+
+    #[test]
+    fn serves_info_and_rejects_over_http() {
+        let fee_payer = Keypair::new();
+        let relay_key = fee_payer.pubkey();
+        let cfg = SkelzConfig { rpc_url: "http://127.0.0.1:1".to_string(), ..SkelzConfig::default() };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let policy = RelayPolicy { allow_any_signer: true, ..RelayPolicy::default() };
+            let mut relay = Relay::new(&cfg, fee_payer.into(), policy).unwrap();
+            for stream in listener.incoming().take(2) {
+                relay.handle_connection(&mut stream.unwrap()).unwrap();
+            }
+        });
+
+        let client = RelayClient::connect(&url, &crate::skelz::ID).unwrap();
+        assert_eq!(client.fee_payer, relay_key);
+
+        // Signed by the identity key but paid by someone else: refused before any RPC call
+        let signer = Keypair::new();
+        let other = Pubkey::new_unique();
+        let transaction = signed(&[write_instruction(&signer.pubkey(), &other, "sha256:abc")], &other, &signer);
+        let err = client.submit(&transaction).unwrap_err().to_string();
+        assert!(err.contains("400") && err.contains("fee payer must be the relay key"), "{}", err);
+    }
+}
@@ -0,0 +1,343 @@
+//! Resilient RPC access: endpoint failover, retries with backoff and timeouts.
+//!
+//! `rpc_url` is the primary endpoint and `rpc_fallback_urls` lists the others.
+//! [`RpcPool::call`] retries transient failures (I/O, timeouts, 5xx, unhealthy
+//! nodes) with exponential backoff, backs off harder on HTTP 429 and moves to the
+//! next endpoint once an endpoint has used up its retries or keeps rate limiting.
+
+use std::cell::Cell;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use solana_client::client_error::ClientErrorKind;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcError;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tracing::{debug, warn};
+
+use crate::{parse_validity_duration, SkelzConfig};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const BASE_DELAY: Duration = Duration::from_millis(250);
+const MAX_DELAY: Duration = Duration::from_secs(8);
+const RATE_LIMIT_DELAY: Duration = Duration::from_secs(2);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// JSON-RPC error codes that indicate a lagging or unhealthy node rather than a bad request
+const NODE_UNHEALTHY: i64 = -32005;
+const BLOCK_NOT_AVAILABLE: i64 = -32004;
+const MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Endpoints and retry behaviour, from the `rpc_*` config keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcSettings {
+    /// Primary endpoint first, then fallbacks
+    pub endpoints: Vec<String>,
+    /// Per-request timeout
+    pub timeout: Duration,
+    /// Retries per endpoint after the first attempt
+    pub max_retries: u32,
+}
+
+impl Default for RpcSettings {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+impl RpcSettings {
+    /// Read `rpc_url`, `rpc_fallback_urls`, `rpc_timeout` and `rpc_max_retries`
+    pub fn from_config(cfg: &SkelzConfig) -> Result<Self> {
+        let mut endpoints = vec![cfg.rpc_url.clone()];
+        for url in &cfg.rpc_fallback_urls {
+            if !endpoints.contains(url) {
+                endpoints.push(url.clone());
+            }
+        }
+        let timeout = match cfg.rpc_timeout.as_deref() {
+            Some(value) => parse_validity_duration(value)
+                .and_then(|d| Ok(d.to_std()?))
+                .context("invalid rpc_timeout")?,
+            None => DEFAULT_TIMEOUT,
+        };
+        Ok(Self {
+            endpoints,
+            timeout,
+            max_retries: cfg.rpc_max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+        })
+    }
+}
+
+/// How an RPC failure should be handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retry {
+    /// The request itself is wrong or was rejected; retrying will not help
+    Never,
+    /// Network or node trouble; retry with backoff
+    Transient,
+    /// HTTP 429; back off longer and prefer another endpoint
+    RateLimited,
+}
+
+fn classify_kind(kind: &ClientErrorKind) -> Retry {
+    match kind {
+        ClientErrorKind::Io(_) => Retry::Transient,
+        ClientErrorKind::Reqwest(e) => match e.status() {
+            Some(status) if status.as_u16() == 429 => Retry::RateLimited,
+            Some(status) if status.is_server_error() => Retry::Transient,
+            Some(_) => Retry::Never,
+            None => Retry::Transient,
+        },
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if [NODE_UNHEALTHY, BLOCK_NOT_AVAILABLE, MIN_CONTEXT_SLOT_NOT_REACHED].contains(code) =>
+        {
+            Retry::Transient
+        }
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(message)) | ClientErrorKind::Custom(message)
+            if message.contains("429") || message.contains("Too Many Requests") =>
+        {
+            Retry::RateLimited
+        }
+        _ => Retry::Never,
+    }
+}
+
+/// Classify an error by the RPC client failure it wraps, if any
+pub fn classify(error: &anyhow::Error) -> Retry {
+    for cause in error.chain() {
+        if let Some(kind) = cause.downcast_ref::<ClientErrorKind>() {
+            return classify_kind(kind);
+        }
+        if let Some(e) = cause.downcast_ref::<solana_client::client_error::ClientError>() {
+            return classify_kind(&e.kind);
+        }
+        if let Some(anchor_client::ClientError::SolanaClientError(e)) =
+            cause.downcast_ref::<anchor_client::ClientError>()
+        {
+            return classify_kind(&e.kind);
+        }
+    }
+    Retry::Never
+}
+
+/// Delay before retry number `attempt` (0-based): exponential from 250ms, capped at 8s
+pub fn backoff_delay(attempt: u32, retry: Retry) -> Duration {
+    let delay = BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt)).min(MAX_DELAY);
+    match retry {
+        Retry::RateLimited => delay.max(RATE_LIMIT_DELAY),
+        _ => delay,
+    }
+}
+
+/// RPC clients for every configured endpoint, tried in order
+pub struct RpcPool {
+    settings: RpcSettings,
+    clients: Vec<RpcClient>,
+    current: Cell<usize>,
+}
+
+impl RpcPool {
+    pub fn new(settings: RpcSettings) -> Self {
+        let clients = settings
+            .endpoints
+            .iter()
+            .map(|url| {
+                RpcClient::new_with_timeout_and_commitment(
+                    url.clone(),
+                    settings.timeout,
+                    CommitmentConfig::confirmed(),
+                )
+            })
+            .collect();
+        Self { settings, clients, current: Cell::new(0) }
+    }
+
+    pub fn from_config(cfg: &SkelzConfig) -> Result<Self> {
+        Ok(Self::new(RpcSettings::from_config(cfg)?))
+    }
+
+    /// Pool starting at `url` (e.g. a program client's endpoint), then the other endpoints of `settings`
+    pub fn for_url(mut settings: RpcSettings, url: &str) -> Self {
+        settings.endpoints.retain(|endpoint| endpoint != url);
+        settings.endpoints.insert(0, url.to_string());
+        Self::new(settings)
+    }
+
+    /// Endpoint currently in use
+    pub fn url(&self) -> String {
+        self.clients[self.current.get()].url()
+    }
+
+    /// Client for the endpoint currently in use
+    pub fn client(&self) -> &RpcClient {
+        &self.clients[self.current.get()]
+    }
+
+    /// Move to the first endpoint answering `getHealth`
+    ///
+    /// With a single endpoint there is nothing to fail over to and no check is made.
+    pub fn select_healthy(&self) -> Result<String> {
+        if self.clients.len() <= 1 {
+            return Ok(self.url());
+        }
+        let mut failures = Vec::new();
+        for (position, endpoint) in self.settings.endpoints.iter().enumerate() {
+            let probe = RpcClient::new_with_timeout(endpoint.clone(), HEALTH_TIMEOUT.min(self.settings.timeout));
+            match probe.get_health() {
+                Ok(()) => {
+                    self.current.set(position);
+                    return Ok(endpoint.clone());
+                }
+                // Providers without getHealth are assumed healthy
+                Err(e) if matches!(
+                    &e.kind,
+                    ClientErrorKind::RpcError(RpcError::RpcResponseError { code: METHOD_NOT_FOUND, .. })
+                ) =>
+                {
+                    self.current.set(position);
+                    return Ok(endpoint.clone());
+                }
+                Err(e) => {
+                    warn!(%endpoint, "RPC endpoint unhealthy: {}", e);
+                    failures.push(format!("{}: {}", endpoint, e));
+                }
+            }
+        }
+        anyhow::bail!("no healthy RPC endpoint:\n  {}", failures.join("\n  "))
+    }
+
+    /// Run `op` against the current endpoint, retrying and failing over as needed
+    pub fn call<T, F>(&self, label: &str, mut op: F) -> Result<T>
+    where
+        F: FnMut(&RpcClient) -> Result<T>,
+    {
+        let endpoints = self.clients.len();
+        let mut last_error = None;
+        for _ in 0..endpoints {
+            let client = self.client();
+            let mut attempt = 0;
+            loop {
+                match op(client) {
+                    Ok(value) => return Ok(value),
+                    Err(e) => {
+                        let retry = classify(&e);
+                        if retry == Retry::Never {
+                            return Err(e);
+                        }
+                        let exhausted = attempt >= self.settings.max_retries
+                            // Another endpoint is a better bet than waiting out a rate limit
+                            || (retry == Retry::RateLimited && endpoints > 1 && attempt >= 1);
+                        if exhausted {
+                            warn!(endpoint = %client.url(), "{} failed after {} attempt(s): {}", label, attempt + 1, e);
+                            last_error = Some(e);
+                            break;
+                        }
+                        let delay = backoff_delay(attempt, retry);
+                        debug!(endpoint = %client.url(), ?retry, ?delay, "{} failed, retrying: {}", label, e);
+                        thread::sleep(delay);
+                        attempt += 1;
+                    }
+                }
+            }
+            self.current.set((self.current.get() + 1) % endpoints);
+        }
+        Err(last_error.expect("at least one endpoint was tried"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::client_error::ClientError;
+
+    fn rpc_error(kind: ClientErrorKind) -> anyhow::Error {
+        anyhow::Error::new(ClientError { request: None, kind }).context("fetch account")
+    }
+
+    fn response_error(code: i64) -> anyhow::Error {
+        rpc_error(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code,
+            message: String::new(),
+            data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+        }))
+    }
+
+    #[test]
+    fn classifies_client_errors() {
+        let io = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        assert_eq!(classify(&rpc_error(ClientErrorKind::Io(io))), Retry::Transient);
+        assert_eq!(classify(&response_error(NODE_UNHEALTHY)), Retry::Transient);
+        assert_eq!(classify(&response_error(-32602)), Retry::Never);
+        assert_eq!(
+            classify(&rpc_error(ClientErrorKind::Custom("HTTP status client error (429 Too Many Requests)".into()))),
+            Retry::RateLimited
+        );
+        assert_eq!(classify(&anyhow::anyhow!("Signer mismatch")), Retry::Never);
+    }
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        assert_eq!(backoff_delay(0, Retry::Transient), Duration::from_millis(250));
+        assert_eq!(backoff_delay(2, Retry::Transient), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(10, Retry::Transient), MAX_DELAY);
+        assert_eq!(backoff_delay(0, Retry::RateLimited), RATE_LIMIT_DELAY);
+    }
+
+    #[test]
+    fn fails_over_to_next_endpoint() {
+        let pool = RpcPool::new(RpcSettings {
+            endpoints: vec!["http://primary.invalid".into(), "http://fallback.invalid".into()],
+            timeout: Duration::from_secs(1),
+            max_retries: 0,
+        });
+        let mut seen = Vec::new();
+        let result = pool.call("test", |client| {
+            seen.push(client.url());
+            if seen.len() == 1 {
+                Err(response_error(NODE_UNHEALTHY))
+            } else {
+                Ok(42)
+            }
+        });
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(seen, ["http://primary.invalid", "http://fallback.invalid"]);
+        assert_eq!(pool.url(), "http://fallback.invalid");
+
+        // Non-retryable errors are returned immediately
+        let mut calls = 0;
+        let result: Result<()> = pool.call("test", |_| {
+            calls += 1;
+            Err(response_error(-32602))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn settings_from_config() {
+        let cfg = SkelzConfig {
+            rpc_url: "https://a.example".into(),
+            rpc_fallback_urls: vec!["https://b.example".into(), "https://a.example".into()],
+            rpc_timeout: Some("10s".into()),
+            rpc_max_retries: Some(5),
+            ..SkelzConfig::default()
+        };
+        let settings = RpcSettings::from_config(&cfg).unwrap();
+        assert_eq!(settings.endpoints, ["https://a.example", "https://b.example"]);
+        assert_eq!(settings.timeout, Duration::from_secs(10));
+        assert_eq!(settings.max_retries, 5);
+
+        // A program client's endpoint goes first; the others and the retry settings are kept
+        let pool = RpcPool::for_url(settings, "https://b.example");
+        assert_eq!(pool.url(), "https://b.example");
+        assert_eq!(pool.settings.endpoints, ["https://b.example", "https://a.example"]);
+        assert_eq!(pool.settings.max_retries, 5);
+    }
+}
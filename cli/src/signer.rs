@@ -0,0 +1,387 @@
+//! Signing key backends selected by the `keypair_path` location.
+//!
+//! - `/path/id.json` or `file:///path/id.json`: Solana CLI keypair file
+//! - `env://VAR`: secret key in an environment variable, base58 or a JSON byte array
+//! - `http://…` / `https://…`: remote signing service (see [`RemoteSigner`])
+//! - `prompt://` or `prompt://?key=0/0`: seed phrase read from the terminal, optionally
+//!   derived along `m/44'/501'/<key>'`, as in the Solana CLI
+//!
+//! All backends are used through [`SkelzSigner`], which implements
+//! [`solana_sdk::signature::Signer`] and can pay for and sign any skelz transaction.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use solana_sdk::derivation_path::DerivationPath;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{
+    generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed_and_derivation_path,
+    keypair_from_seed_phrase_and_passphrase, read_keypair_file, Keypair, Signature, Signer,
+};
+use solana_sdk::signer::SignerError;
+use tracing::{debug, warn};
+
+use crate::expand_tilde;
+
+/// Environment variable holding a bearer token for the remote signer
+pub const REMOTE_SIGNER_TOKEN_ENV: &str = "SKELZ_REMOTE_SIGNER_TOKEN";
+
+/// Where a signing key comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerSource {
+    File(PathBuf),
+    Env(String),
+    Remote(String),
+    Prompt { key: Option<String> },
+}
+
+impl SignerSource {
+    /// Parse a `keypair_path` value; anything without a scheme is a file path
+    pub fn parse(location: &str) -> Result<Self> {
+        let location = location.trim();
+        let Some((scheme, rest)) = location.split_once("://") else {
+            return Ok(SignerSource::File(expand_tilde(location.as_ref())));
+        };
+        match scheme {
+            "file" => Ok(SignerSource::File(expand_tilde(rest.as_ref()))),
+            "env" if !rest.is_empty() => Ok(SignerSource::Env(rest.to_string())),
+            "env" => Err(anyhow!("env:// needs a variable name, e.g. env://SKELZ_SIGNER_KEY")),
+            "http" | "https" => Ok(SignerSource::Remote(location.trim_end_matches('/').to_string())),
+            "prompt" => {
+                let key = match rest.strip_prefix('?') {
+                    None if rest.is_empty() => None,
+                    Some(query) => {
+                        let key = query
+                            .split('&')
+                            .find_map(|pair| pair.strip_prefix("key="))
+                            .ok_or_else(|| anyhow!("unsupported prompt:// query '{}': expected key=<ACCOUNT>[/<CHANGE>]", query))?;
+                        DerivationPath::from_key_str(key).map_err(|e| anyhow!("invalid derivation key '{}': {}", key, e))?;
+                        Some(key.to_string())
+                    }
+                    None => anyhow::bail!("unexpected prompt:// location '{}'", location),
+                };
+                Ok(SignerSource::Prompt { key })
+            }
+            other => Err(anyhow!("unsupported signer scheme '{}://' (expected a path, file://, env://, http(s):// or prompt://)", other)),
+        }
+    }
+}
+
+impl fmt::Display for SignerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerSource::File(path) => write!(f, "{}", path.display()),
+            SignerSource::Env(var) => write!(f, "env://{}", var),
+            SignerSource::Remote(url) => write!(f, "{}", url),
+            SignerSource::Prompt { key: None } => write!(f, "prompt://"),
+            SignerSource::Prompt { key: Some(key) } => write!(f, "prompt://?key={}", key),
+        }
+    }
+}
+
+/// A signing key from any backend
+pub struct SkelzSigner {
+    inner: Box<dyn Signer>,
+    /// None for in-memory keys
+    source: Option<SignerSource>,
+}
+
+impl SkelzSigner {
+    /// Load the signer named by a `keypair_path` location
+    pub fn load(location: &str) -> Result<Self> {
+        let source = SignerSource::parse(location)?;
+        let inner: Box<dyn Signer> = match &source {
+            SignerSource::File(path) => {
+                let keypair =
+                    read_keypair_file(path).map_err(|e| anyhow!("read keypair at {}: {}", path.display(), e))?;
+                if let Err(e) = crate::check_keypair_file(path) {
+                    warn!("{:#}", e);
+                }
+                Box::new(keypair)
+            }
+            SignerSource::Env(var) => {
+                let value = std::env::var(var).with_context(|| format!("read signing key from ${}", var))?;
+                Box::new(keypair_from_secret(&value).with_context(|| format!("signing key in ${}", var))?)
+            }
+            SignerSource::Remote(url) => Box::new(RemoteSigner::connect(url)?),
+            SignerSource::Prompt { key } => {
+                let phrase = rpassword::prompt_password("Seed phrase: ").context("read seed phrase")?;
+                let passphrase = rpassword::prompt_password("Passphrase (empty for none): ").context("read passphrase")?;
+                Box::new(keypair_from_phrase(&phrase, &passphrase, key.as_deref())?)
+            }
+        };
+        debug!(source = %source, pubkey = %inner.pubkey(), "loaded signer");
+        Ok(SkelzSigner { inner, source: Some(source) })
+    }
+
+    /// Where the key was loaded from
+    pub fn source(&self) -> Option<&SignerSource> {
+        self.source.as_ref()
+    }
+}
+
+impl From<Keypair> for SkelzSigner {
+    fn from(keypair: Keypair) -> Self {
+        SkelzSigner { inner: Box::new(keypair), source: None }
+    }
+}
+
+impl fmt::Debug for SkelzSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SkelzSigner")
+            .field("source", &self.source.as_ref().map(|s| s.to_string()))
+            .field("pubkey", &self.inner.try_pubkey().ok())
+            .finish()
+    }
+}
+
+impl Signer for SkelzSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        self.inner.try_pubkey()
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.inner.try_sign_message(message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.inner.is_interactive()
+    }
+}
+
+/// Keypair from a base58 string or a JSON byte array (the keypair file format)
+pub fn keypair_from_secret(value: &str) -> Result<Keypair> {
+    let value = value.trim();
+    let bytes = if value.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(value).context("not a JSON byte array")?
+    } else {
+        bs58::decode(value).into_vec().context("not base58")?
+    };
+    Keypair::from_bytes(&bytes).map_err(|e| anyhow!("invalid secret key: {}", e))
+}
+
+/// Keypair from a seed phrase, derived along `key` (e.g. `0/0`) when given
+pub fn keypair_from_phrase(phrase: &str, passphrase: &str, key: Option<&str>) -> Result<Keypair> {
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let keypair = match key {
+        None => keypair_from_seed_phrase_and_passphrase(&phrase, passphrase),
+        Some(key) => {
+            let path = DerivationPath::from_key_str(key).map_err(|e| anyhow!("invalid derivation key '{}': {}", key, e))?;
+            let seed = generate_seed_from_seed_phrase_and_passphrase(&phrase, passphrase);
+            keypair_from_seed_and_derivation_path(&seed, Some(path))
+        }
+    };
+    keypair.map_err(|e| anyhow!("derive keypair from seed phrase: {}", e))
+}
+
+#[derive(Debug, Deserialize)]
+struct PubkeyResponse {
+    pubkey: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SignRequest<'a> {
+    pubkey: String,
+    /// Base64 serialized transaction message
+    message: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignResponse {
+    /// Base58 ed25519 signature
+    signature: String,
+}
+
+/// Key held by a remote signing service
+///
+/// The service exposes `GET <url>/pubkey` returning `{"pubkey": "<base58>"}` and
+/// `POST <url>/sign` taking `{"pubkey": "<base58>", "message": "<base64>"}` and
+/// returning `{"signature": "<base58>"}`. If `SKELZ_REMOTE_SIGNER_TOKEN` is set it
+/// is sent as a bearer token. Returned signatures are verified before use.
+pub struct RemoteSigner {
+    url: String,
+    pubkey: Pubkey,
+    token: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+impl RemoteSigner {
+    /// Fetch the service's public key
+    pub fn connect(url: &str) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+        let token = std::env::var(REMOTE_SIGNER_TOKEN_ENV).ok().filter(|t| !t.is_empty());
+        let url = url.trim_end_matches('/').to_string();
+        let mut request = client.get(format!("{}/pubkey", url));
+        if let Some(token) = &token {
+            request = request.bearer_auth(token);
+        }
+        let response: PubkeyResponse = request
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .with_context(|| format!("remote signer {}: fetch public key", url))?;
+        let pubkey = response
+            .pubkey
+            .parse()
+            .map_err(|e| anyhow!("remote signer {} returned an invalid public key: {}", url, e))?;
+        Ok(RemoteSigner { url, pubkey, token, client })
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(message);
+        let mut request = self
+            .client
+            .post(format!("{}/sign", self.url))
+            .json(&SignRequest { pubkey: self.pubkey.to_string(), message: &encoded });
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response: SignResponse = request
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(|e| SignerError::Connection(format!("remote signer {}: {}", self.url, e)))?;
+        let signature: Signature = response
+            .signature
+            .parse()
+            .map_err(|e| SignerError::Protocol(format!("remote signer returned an invalid signature: {}", e)))?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(SignerError::Protocol(format!(
+                "remote signer {} returned a signature that does not verify for {}",
+                self.url, self.pubkey
+            )));
+        }
+        Ok(signature)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.sign(message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Minimal remote signer: answers `requests` HTTP requests with `keypair`
+    fn serve_stub(keypair: Keypair, requests: usize, tamper: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/signer", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let reply = if request_line.starts_with("GET /signer/pubkey") {
+                    serde_json::json!({ "pubkey": keypair.pubkey().to_string() })
+                } else {
+                    let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let mut message = base64::engine::general_purpose::STANDARD
+                        .decode(request["message"].as_str().unwrap())
+                        .unwrap();
+                    if tamper {
+                        message.push(0);
+                    }
+                    serde_json::json!({ "signature": keypair.sign_message(&message).to_string() })
+                };
+                let reply = reply.to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn parses_signer_locations() {
+        assert_eq!(SignerSource::parse("/tmp/id.json").unwrap(), SignerSource::File("/tmp/id.json".into()));
+        assert_eq!(SignerSource::parse("file:///tmp/id.json").unwrap(), SignerSource::File("/tmp/id.json".into()));
+        assert_eq!(SignerSource::parse("env://SKELZ_KEY").unwrap(), SignerSource::Env("SKELZ_KEY".into()));
+        assert_eq!(
+            SignerSource::parse("https://signer.internal/v1/").unwrap(),
+            SignerSource::Remote("https://signer.internal/v1".into())
+        );
+        assert_eq!(SignerSource::parse("prompt://").unwrap(), SignerSource::Prompt { key: None });
+        assert_eq!(SignerSource::parse("prompt://?key=0/0").unwrap(), SignerSource::Prompt { key: Some("0/0".into()) });
+        assert!(SignerSource::parse("prompt://?key=x").is_err());
+        assert!(SignerSource::parse("usb://ledger").is_err());
+        assert!(SignerSource::parse("env://").is_err());
+    }
+
+    #[test]
+    fn loads_key_from_environment() {
+        let keypair = Keypair::new();
+        std::env::set_var("SKELZ_TEST_SIGNER_B58", keypair.to_base58_string());
+        std::env::set_var("SKELZ_TEST_SIGNER_JSON", serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap());
+        for var in ["SKELZ_TEST_SIGNER_B58", "SKELZ_TEST_SIGNER_JSON"] {
+            let signer = SkelzSigner::load(&format!("env://{}", var)).unwrap();
+            assert_eq!(signer.pubkey(), keypair.pubkey());
+        }
+        assert!(SkelzSigner::load("env://SKELZ_TEST_SIGNER_UNSET").is_err());
+        std::env::set_var("SKELZ_TEST_SIGNER_BAD", "not a key");
+        assert!(SkelzSigner::load("env://SKELZ_TEST_SIGNER_BAD").is_err());
+    }
+
+    #[test]
+    fn derives_seed_phrase_keys() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let plain = keypair_from_phrase(phrase, "", None).unwrap();
+        let spaced = keypair_from_phrase(&format!("  {}\n", phrase.replace(' ', "  ")), "", None).unwrap();
+        assert_eq!(plain.pubkey(), spaced.pubkey());
+        let derived = keypair_from_phrase(phrase, "", Some("0/0")).unwrap();
+        assert_ne!(plain.pubkey(), derived.pubkey());
+        assert_ne!(derived.pubkey(), keypair_from_phrase(phrase, "", Some("1/0")).unwrap().pubkey());
+    }
+
+    #[test]
+    fn signs_with_remote_stub() {
+        let keypair = Keypair::new();
+        let expected = keypair.pubkey();
+        let url = serve_stub(keypair, 2, false);
+        let signer = SkelzSigner::load(&url).unwrap();
+        assert_eq!(signer.pubkey(), expected);
+        let signature = signer.try_sign_message(b"message").unwrap();
+        assert!(signature.verify(expected.as_ref(), b"message"));
+
+        let url = serve_stub(Keypair::new(), 2, true);
+        let signer = SkelzSigner::load(&url).unwrap();
+        assert!(matches!(signer.try_sign_message(b"message"), Err(SignerError::Protocol(_))));
+    }
+}
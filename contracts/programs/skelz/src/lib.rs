@@ -5,36 +5,927 @@ use sha2::{Sha256, Digest};
 
 declare_id!("4uw8DwTRdUMwGmbNrK5GZ5kgdVtco4aUaTGDnEUBrYKt");
 
+/// Maximum length of a publisher handle (also used as PDA seed).
+pub const MAX_PUBLISHER_NAME_LEN: usize = 32;
+/// Maximum length of a publisher namespace pattern (e.g. `ghcr.io/kevredlabs/*`).
+pub const MAX_NAMESPACE_LEN: usize = 128;
+/// Maximum length of a publisher display name.
+pub const MAX_DISPLAY_NAME_LEN: usize = 64;
+/// Maximum length of a publisher contact (email or URL).
+pub const MAX_CONTACT_LEN: usize = 128;
+/// Maximum number of authorized keys per publisher.
+pub const MAX_PUBLISHER_KEYS: usize = 8;
+/// Maximum length of a policy name (also used as PDA seed).
+pub const MAX_POLICY_NAME_LEN: usize = 32;
+/// Maximum length of a policy repository pattern.
+pub const MAX_REPOSITORY_PATTERN_LEN: usize = 128;
+/// Maximum number of required signers per policy.
+pub const MAX_POLICY_SIGNERS: usize = 16;
+/// Maximum length of an OIDC issuer URL.
+pub const MAX_OIDC_ISSUER_LEN: usize = 128;
+/// Maximum length of an OIDC subject.
+pub const MAX_OIDC_SUBJECT_LEN: usize = 256;
+/// Maximum length of a CI workflow reference.
+pub const MAX_WORKFLOW_REF_LEN: usize = 256;
+/// Maximum length of an image digest (`sha256:` and 64 hex characters fit).
+pub const MAX_DIGEST_LEN: usize = 96;
+/// Maximum number of members of a multisig.
+pub const MAX_MULTISIG_MEMBERS: usize = 10;
+/// Maximum number of co-signing keys a multisig can trust for its signer.
+pub const MAX_CO_SIGNERS: usize = 8;
+/// Size of a `Signature` account: discriminator, digest (4 + 96), signer, slot,
+/// created_at, not_after and revoked_at.
+pub const SIGNATURE_SPACE: usize = 8 + 100 + 32 + 8 + 8 + 9 + 9;
+/// Size of signature accounts written before slots, expiry and revocation were
+/// recorded: discriminator, digest (4 + 96) and signer. Revoking one grows it to
+/// `SIGNATURE_SPACE`, paid by the revoking signer or executor.
+pub const LEGACY_SIGNATURE_SPACE: usize = 8 + 100 + 32;
+
 #[program]
 pub mod skelz {
     use super::*;
 
-    pub fn write_signature(ctx: Context<WriteSignature>, digest: String) -> Result<()> {
+    pub fn write_signature(
+        ctx: Context<WriteSignature>,
+        digest: String,
+        not_after: Option<i64>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        if let Some(not_after) = not_after {
+            require!(not_after > clock.unix_timestamp, SkelzError::ExpiryInThePast);
+        }
+
         let signature = &mut ctx.accounts.signature;
         signature.digest = digest;
         signature.signer = ctx.accounts.signer.key();
+        signature.slot = clock.slot;
+        signature.created_at = clock.unix_timestamp;
+        signature.not_after = not_after;
+        signature.revoked_at = None;
+
+        emit!(SignatureWritten {
+            digest: signature.digest.clone(),
+            signer: signature.signer,
+            slot: signature.slot,
+            not_after,
+        });
+        Ok(())
+    }
+
+    pub fn revoke_signature(ctx: Context<RevokeSignature>, digest: String) -> Result<()> {
+        msg!("Revoking signature for {}", digest);
+        let signature = &mut ctx.accounts.signature;
+        require!(signature.revoked_at.is_none(), SkelzError::AlreadyRevoked);
+        let revoked_at = Clock::get()?.unix_timestamp;
+        signature.revoked_at = Some(revoked_at);
+
+        emit!(SignatureRevoked {
+            digest,
+            signer: signature.signer,
+            revoked_at,
+        });
+        Ok(())
+    }
+
+    pub fn rotate_key(ctx: Context<RotateKey>, effective_slot: Option<u64>) -> Result<()> {
+        let old_key = ctx.accounts.old_key.key();
+        let new_key = ctx.accounts.new_key.key();
+        require_keys_neq!(old_key, new_key, SkelzError::RotationToSelf);
+
+        let current_slot = Clock::get()?.slot;
+        let effective_slot = effective_slot.unwrap_or(current_slot);
+        // Rotations cannot be backdated: signatures made before now stay valid
+        require!(effective_slot >= current_slot, SkelzError::RotationInThePast);
+
+        let rotation = &mut ctx.accounts.rotation;
+        rotation.old_key = old_key;
+        rotation.new_key = new_key;
+        rotation.effective_slot = effective_slot;
+        rotation.created_slot = current_slot;
+        rotation.bump = ctx.bumps.rotation;
+
+        emit!(KeyRotated {
+            old_key,
+            new_key,
+            effective_slot,
+        });
+        Ok(())
+    }
+
+    pub fn register_publisher(
+        ctx: Context<RegisterPublisher>,
+        name: String,
+        namespace: String,
+        display_name: String,
+        contact: String,
+        keys: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= MAX_PUBLISHER_NAME_LEN,
+            SkelzError::InvalidPublisherName
+        );
+        validate_publisher_fields(&namespace, &display_name, &contact, &keys)?;
+
+        let publisher = &mut ctx.accounts.publisher;
+        publisher.authority = ctx.accounts.authority.key();
+        publisher.name = name;
+        publisher.namespace = namespace;
+        publisher.display_name = display_name;
+        publisher.contact = contact;
+        publisher.keys = keys;
+        publisher.bump = ctx.bumps.publisher;
+        Ok(())
+    }
+
+    pub fn update_publisher(
+        ctx: Context<UpdatePublisher>,
+        namespace: String,
+        display_name: String,
+        contact: String,
+        keys: Vec<Pubkey>,
+    ) -> Result<()> {
+        validate_publisher_fields(&namespace, &display_name, &contact, &keys)?;
+
+        let publisher = &mut ctx.accounts.publisher;
+        publisher.namespace = namespace;
+        publisher.display_name = display_name;
+        publisher.contact = contact;
+        publisher.keys = keys;
+        Ok(())
+    }
+
+    pub fn create_policy(
+        ctx: Context<CreatePolicy>,
+        name: String,
+        repository: String,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= MAX_POLICY_NAME_LEN,
+            SkelzError::InvalidPolicyName
+        );
+        validate_policy_fields(&repository, &signers, threshold)?;
+
+        let policy = &mut ctx.accounts.policy;
+        policy.owner = ctx.accounts.owner.key();
+        policy.name = name;
+        policy.repository = repository;
+        policy.signers = signers;
+        policy.threshold = threshold;
+        policy.version = 1;
+        policy.frozen = false;
+        policy.updated_slot = Clock::get()?.slot;
+        policy.bump = ctx.bumps.policy;
+
+        emit_policy_changed(policy.key(), policy);
+        Ok(())
+    }
+
+    pub fn update_policy(
+        ctx: Context<UpdatePolicy>,
+        repository: String,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_policy_fields(&repository, &signers, threshold)?;
+
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.frozen, SkelzError::PolicyFrozen);
+        policy.repository = repository;
+        policy.signers = signers;
+        policy.threshold = threshold;
+        policy.version = policy.version.checked_add(1).ok_or(SkelzError::PolicyVersionOverflow)?;
+        policy.updated_slot = Clock::get()?.slot;
+
+        emit_policy_changed(policy.key(), policy);
+        Ok(())
+    }
+
+    pub fn freeze_policy(ctx: Context<UpdatePolicy>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(!policy.frozen, SkelzError::PolicyFrozen);
+        policy.frozen = true;
+        policy.updated_slot = Clock::get()?.slot;
+
+        emit_policy_changed(policy.key(), policy);
+        Ok(())
+    }
+
+    pub fn bind_identity(
+        ctx: Context<BindIdentity>,
+        issuer: String,
+        subject: String,
+        workflow_ref: String,
+    ) -> Result<()> {
+        require!(
+            !issuer.is_empty() && issuer.len() <= MAX_OIDC_ISSUER_LEN,
+            SkelzError::InvalidOidcIssuer
+        );
+        require!(
+            !subject.is_empty() && subject.len() <= MAX_OIDC_SUBJECT_LEN,
+            SkelzError::InvalidOidcSubject
+        );
+        require!(workflow_ref.len() <= MAX_WORKFLOW_REF_LEN, SkelzError::WorkflowRefTooLong);
+
+        let clock = Clock::get()?;
+        let binding = &mut ctx.accounts.binding;
+        binding.signer = ctx.accounts.signer.key();
+        binding.attester = ctx.accounts.attester.key();
+        binding.issuer = issuer;
+        binding.subject = subject;
+        binding.workflow_ref = workflow_ref;
+        binding.slot = clock.slot;
+        binding.created_at = clock.unix_timestamp;
+        binding.bump = ctx.bumps.binding;
+
+        emit!(IdentityBound {
+            signer: binding.signer,
+            attester: binding.attester,
+            issuer: binding.issuer.clone(),
+            subject: binding.subject.clone(),
+            workflow_ref: binding.workflow_ref.clone(),
+        });
+        Ok(())
+    }
+
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            !members.is_empty() && members.len() <= MAX_MULTISIG_MEMBERS,
+            SkelzError::InvalidMultisigMembers
+        );
+        require!(
+            members.iter().enumerate().all(|(i, m)| !members[..i].contains(m)),
+            SkelzError::InvalidMultisigMembers
+        );
+        require!(
+            threshold >= 1 && usize::from(threshold) <= members.len(),
+            SkelzError::InvalidThreshold
+        );
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.signer = ctx.accounts.signer.key();
+        multisig.members = members;
+        multisig.threshold = threshold;
+        multisig.co_signers = Vec::new();
+        multisig.proposal_count = 0;
+        multisig.bump = ctx.bumps.multisig;
+        Ok(())
+    }
+
+    pub fn propose(ctx: Context<Propose>, action: MultisigAction) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let proposer = ctx.accounts.proposer.key();
+        require!(multisig.members.contains(&proposer), SkelzError::NotMultisigMember);
+        validate_multisig_action(multisig, &action)?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = multisig.key();
+        proposal.index = multisig.proposal_count;
+        proposal.proposer = proposer;
+        proposal.action = action;
+        // Proposing counts as the proposer's approval
+        proposal.approvals = vec![proposer];
+        proposal.executed = false;
+        proposal.created_slot = Clock::get()?.slot;
+        proposal.bump = ctx.bumps.proposal;
+        multisig.proposal_count = multisig.proposal_count.checked_add(1).ok_or(SkelzError::ProposalCountOverflow)?;
+        Ok(())
+    }
+
+    pub fn approve(ctx: Context<Approve>) -> Result<()> {
+        let member = ctx.accounts.member.key();
+        require!(ctx.accounts.multisig.members.contains(&member), SkelzError::NotMultisigMember);
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, SkelzError::ProposalExecuted);
+        require!(!proposal.approvals.contains(&member), SkelzError::AlreadyApproved);
+        proposal.approvals.push(member);
+        Ok(())
+    }
+
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        require!(
+            multisig.members.contains(&ctx.accounts.executor.key()),
+            SkelzError::NotMultisigMember
+        );
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, SkelzError::ProposalExecuted);
+        require!(
+            proposal.approvals.len() >= usize::from(multisig.threshold),
+            SkelzError::ThresholdNotReached
+        );
+        validate_multisig_action(multisig, &proposal.action)?;
+
+        let clock = Clock::get()?;
+        match &proposal.action {
+            MultisigAction::RevokeSignature { digest } => {
+                let signature = ctx
+                    .accounts
+                    .signature
+                    .as_mut()
+                    .ok_or(SkelzError::SignatureAccountMissing)?;
+                require!(signature.digest == *digest, SkelzError::SignatureAccountMissing);
+                require_keys_eq!(signature.signer, multisig.signer, SkelzError::Unauthorized);
+                require!(signature.revoked_at.is_none(), SkelzError::AlreadyRevoked);
+                signature.revoked_at = Some(clock.unix_timestamp);
+                emit!(SignatureRevoked {
+                    digest: digest.clone(),
+                    signer: signature.signer,
+                    revoked_at: clock.unix_timestamp,
+                });
+            }
+            MultisigAction::AddCoSigner { key } => multisig.co_signers.push(CoSigner {
+                key: *key,
+                added_slot: clock.slot,
+            }),
+            MultisigAction::RemoveCoSigner { key } => multisig.co_signers.retain(|c| c.key != *key),
+        }
+        proposal.executed = true;
+
+        emit!(ProposalExecuted {
+            multisig: multisig.key(),
+            signer: multisig.signer,
+            index: proposal.index,
+            co_signers: multisig.co_signers.iter().map(|c| c.key).collect(),
+        });
         Ok(())
     }
 }
 
+fn validate_multisig_action(multisig: &Multisig, action: &MultisigAction) -> Result<()> {
+    match action {
+        MultisigAction::RevokeSignature { digest } => {
+            require!(
+                !digest.is_empty() && digest.len() <= MAX_DIGEST_LEN,
+                SkelzError::InvalidDigest
+            )
+        }
+        MultisigAction::AddCoSigner { key } => {
+            require_keys_neq!(*key, multisig.signer, SkelzError::InvalidCoSigner);
+            require!(
+                !multisig.co_signers.iter().any(|c| c.key == *key),
+                SkelzError::InvalidCoSigner
+            );
+            require!(multisig.co_signers.len() < MAX_CO_SIGNERS, SkelzError::TooManyCoSigners);
+        }
+        MultisigAction::RemoveCoSigner { key } => {
+            require!(
+                multisig.co_signers.iter().any(|c| c.key == *key),
+                SkelzError::InvalidCoSigner
+            );
+        }
+    }
+    Ok(())
+}
+
+fn emit_policy_changed(address: Pubkey, policy: &Policy) {
+    emit!(PolicyChanged {
+        policy: address,
+        owner: policy.owner,
+        name: policy.name.clone(),
+        repository: policy.repository.clone(),
+        version: policy.version,
+        frozen: policy.frozen,
+    });
+}
+
+fn validate_policy_fields(repository: &str, signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !repository.is_empty() && repository.len() <= MAX_REPOSITORY_PATTERN_LEN,
+        SkelzError::InvalidRepositoryPattern
+    );
+    require!(
+        !signers.is_empty() && signers.len() <= MAX_POLICY_SIGNERS,
+        SkelzError::InvalidPolicySigners
+    );
+    require!(
+        threshold >= 1 && usize::from(threshold) <= signers.len(),
+        SkelzError::InvalidThreshold
+    );
+    // A digest has a single Signature account, so no image can meet a higher threshold
+    require!(threshold == 1, SkelzError::UnsupportedPolicyThreshold);
+    Ok(())
+}
+
+fn validate_publisher_fields(
+    namespace: &str,
+    display_name: &str,
+    contact: &str,
+    keys: &[Pubkey],
+) -> Result<()> {
+    require!(
+        !namespace.is_empty() && namespace.len() <= MAX_NAMESPACE_LEN,
+        SkelzError::InvalidNamespace
+    );
+    require!(display_name.len() <= MAX_DISPLAY_NAME_LEN, SkelzError::DisplayNameTooLong);
+    require!(contact.len() <= MAX_CONTACT_LEN, SkelzError::ContactTooLong);
+    require!(
+        !keys.is_empty() && keys.len() <= MAX_PUBLISHER_KEYS,
+        SkelzError::InvalidPublisherKeys
+    );
+    Ok(())
+}
+
 #[derive(Accounts)]
 #[instruction(digest: String)]
 pub struct WriteSignature<'info> {
-    // Keep one signer to be explicit and future-proof if needed
-    #[account(mut,signer)]
+    // Identity recorded on the signature; may hold no SOL
     pub signer: Signer<'info>,
+    // Pays rent and fees; can be the signer itself or a separate treasury wallet
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
     init,
-    payer = signer,
-    space = 8 + 100 + 32,
+    payer = payer,
+    space = SIGNATURE_SPACE,
     seeds = [b"signature", &Sha256::digest(digest.as_bytes())[..]],
     bump)]
     pub signature: Account<'info, Signature>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(digest: String)]
+pub struct RevokeSignature<'info> {
+    // Only the key recorded on the signature may revoke it; pays to grow legacy accounts
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+    mut,
+    has_one = signer @ SkelzError::Unauthorized,
+    seeds = [b"signature", &Sha256::digest(digest.as_bytes())[..]],
+    bump,
+    realloc = SIGNATURE_SPACE,
+    realloc::payer = signer,
+    realloc::zero = false)]
+    pub signature: Account<'info, Signature>,
+    // Signers governed by a multisig revoke through proposals instead
+    /// CHECK: only checked to be empty
+    #[account(
+    seeds = [b"multisig", signer.key().as_ref()],
+    bump,
+    constraint = multisig.data_is_empty() @ SkelzError::MultisigRequired)]
+    pub multisig: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct RegisterPublisher<'info> {
+    // Pays rent and becomes the only key allowed to update the publisher
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+    init,
+    payer = authority,
+    space = 8 + Publisher::INIT_SPACE,
+    seeds = [b"publisher", name.as_bytes()],
+    bump)]
+    pub publisher: Account<'info, Publisher>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePublisher<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+    mut,
+    has_one = authority @ SkelzError::Unauthorized,
+    seeds = [b"publisher", publisher.name.as_bytes()],
+    bump = publisher.bump)]
+    pub publisher: Account<'info, Publisher>,
+}
+
+#[derive(Accounts)]
+pub struct RotateKey<'info> {
+    // The retiring key signs the handover and pays for the record
+    #[account(mut)]
+    pub old_key: Signer<'info>,
+    // The successor consents too: otherwise any key could claim to hand over to a trusted one
+    pub new_key: Signer<'info>,
+    #[account(
+    init,
+    payer = old_key,
+    space = 8 + Rotation::INIT_SPACE,
+    seeds = [b"rotation", old_key.key().as_ref()],
+    bump)]
+    pub rotation: Account<'info, Rotation>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreatePolicy<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+    init,
+    payer = owner,
+    space = 8 + Policy::INIT_SPACE,
+    seeds = [b"policy", owner.key().as_ref(), name.as_bytes()],
+    bump)]
+    pub policy: Account<'info, Policy>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePolicy<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+    mut,
+    has_one = owner @ SkelzError::Unauthorized,
+    seeds = [b"policy", owner.key().as_ref(), policy.name.as_bytes()],
+    bump = policy.bump)]
+    pub policy: Account<'info, Policy>,
+}
+
+#[derive(Accounts)]
+pub struct BindIdentity<'info> {
+    // Ephemeral key whose signatures are bound to the identity
+    pub signer: Signer<'info>,
+    // Verifier that checked the OIDC token; trust in it is decided at verification
+    pub attester: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+    init,
+    payer = payer,
+    space = 8 + IdentityBinding::INIT_SPACE,
+    seeds = [b"identity", signer.key().as_ref()],
+    bump)]
+    pub binding: Account<'info, IdentityBinding>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    // The key handing authority over its records to the members
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+    init,
+    payer = signer,
+    space = 8 + Multisig::INIT_SPACE,
+    seeds = [b"multisig", signer.key().as_ref()],
+    bump)]
+    pub multisig: Account<'info, Multisig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    // Member of the multisig; pays for the proposal
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(
+    mut,
+    seeds = [b"multisig", multisig.signer.as_ref()],
+    bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+    init,
+    payer = proposer,
+    space = 8 + Proposal::INIT_SPACE,
+    seeds = [b"proposal", multisig.key().as_ref(), &multisig.proposal_count.to_le_bytes()],
+    bump)]
+    pub proposal: Account<'info, Proposal>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Approve<'info> {
+    pub member: Signer<'info>,
+    #[account(
+    seeds = [b"multisig", multisig.signer.as_ref()],
+    bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+    mut,
+    has_one = multisig,
+    seeds = [b"proposal", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+    bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    // Pays to grow a legacy signature account being revoked
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    #[account(
+    mut,
+    seeds = [b"multisig", multisig.signer.as_ref()],
+    bump = multisig.bump)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+    mut,
+    has_one = multisig,
+    seeds = [b"proposal", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+    bump = proposal.bump)]
+    pub proposal: Account<'info, Proposal>,
+    // Only for RevokeSignature: the signature being revoked
+    #[account(
+    mut,
+    realloc = SIGNATURE_SPACE,
+    realloc::payer = executor,
+    realloc::zero = false)]
+    pub signature: Option<Account<'info, Signature>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Record of an image signature, at PDA `["signature", sha256(digest)]`.
+///
+/// Accounts created before `slot` were added are `LEGACY_SIGNATURE_SPACE` bytes
+/// and hold only `digest` and `signer`; the remaining fields decode from the
+/// zero padding when the digest leaves room, and revocation grows the account.
 #[account]
 pub struct Signature {
     pub digest: String,
     pub signer: Pubkey,
+    /// Slot at which the signature was recorded
+    pub slot: u64,
+    /// Cluster unix timestamp at which the signature was recorded
+    pub created_at: i64,
+    /// Optional unix timestamp after which the signature is expired
+    pub not_after: Option<i64>,
+    /// Unix timestamp at which the signer revoked the signature
+    pub revoked_at: Option<i64>,
+}
+
+/// Handover from a retired key to its successor.
+///
+/// Signatures made by `old_key` before `effective_slot` remain trusted wherever
+/// `new_key` (or one of its successors) is trusted.
+#[account]
+#[derive(InitSpace)]
+pub struct Rotation {
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    pub effective_slot: u64,
+    pub created_slot: u64,
+    pub bump: u8,
+}
+
+/// Human-readable identity mapping a repository namespace to its authorized signing keys.
+#[account]
+#[derive(InitSpace)]
+pub struct Publisher {
+    pub authority: Pubkey,
+    #[max_len(MAX_PUBLISHER_NAME_LEN)]
+    pub name: String,
+    #[max_len(MAX_NAMESPACE_LEN)]
+    pub namespace: String,
+    #[max_len(MAX_DISPLAY_NAME_LEN)]
+    pub display_name: String,
+    #[max_len(MAX_CONTACT_LEN)]
+    pub contact: String,
+    #[max_len(MAX_PUBLISHER_KEYS)]
+    pub keys: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+/// Admission rule shared by every cluster; immutable once frozen.
+#[account]
+#[derive(InitSpace)]
+pub struct Policy {
+    pub owner: Pubkey,
+    #[max_len(MAX_POLICY_NAME_LEN)]
+    pub name: String,
+    #[max_len(MAX_REPOSITORY_PATTERN_LEN)]
+    pub repository: String,
+    #[max_len(MAX_POLICY_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    /// Incremented on every update, starting at 1
+    pub version: u32,
+    pub frozen: bool,
+    pub updated_slot: u64,
+    pub bump: u8,
+}
+
+/// OIDC identity an attester vouched for when `signer` was created.
+#[account]
+#[derive(InitSpace)]
+pub struct IdentityBinding {
+    pub signer: Pubkey,
+    pub attester: Pubkey,
+    #[max_len(MAX_OIDC_ISSUER_LEN)]
+    pub issuer: String,
+    #[max_len(MAX_OIDC_SUBJECT_LEN)]
+    pub subject: String,
+    /// e.g. GitHub's `job_workflow_ref`; empty when the token has none
+    #[max_len(MAX_WORKFLOW_REF_LEN)]
+    pub workflow_ref: String,
+    pub slot: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// m-of-n authority over the records of `signer`.
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    pub signer: Pubkey,
+    #[max_len(MAX_MULTISIG_MEMBERS)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    /// Keys trusted to sign on behalf of `signer`
+    #[max_len(MAX_CO_SIGNERS)]
+    pub co_signers: Vec<CoSigner>,
+    /// Index of the next proposal
+    pub proposal_count: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, PartialEq, Eq)]
+pub struct CoSigner {
+    pub key: Pubkey,
+    /// Signatures made before this slot are not covered
+    pub added_slot: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, PartialEq, Eq)]
+pub enum MultisigAction {
+    RevokeSignature {
+        #[max_len(MAX_DIGEST_LEN)]
+        digest: String,
+    },
+    AddCoSigner { key: Pubkey },
+    RemoveCoSigner { key: Pubkey },
+}
+
+/// An action waiting for `threshold` member approvals.
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: MultisigAction,
+    #[max_len(MAX_MULTISIG_MEMBERS)]
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+    pub created_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct SignatureWritten {
+    pub digest: String,
+    pub signer: Pubkey,
+    pub slot: u64,
+    pub not_after: Option<i64>,
+}
+
+#[event]
+pub struct SignatureRevoked {
+    pub digest: String,
+    pub signer: Pubkey,
+    pub revoked_at: i64,
+}
+
+#[event]
+pub struct KeyRotated {
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    pub effective_slot: u64,
+}
+
+/// Emitted when a policy is created, updated or frozen
+#[event]
+pub struct PolicyChanged {
+    pub policy: Pubkey,
+    pub owner: Pubkey,
+    pub name: String,
+    pub repository: String,
+    pub version: u32,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct IdentityBound {
+    pub signer: Pubkey,
+    pub attester: Pubkey,
+    pub issuer: String,
+    pub subject: String,
+    pub workflow_ref: String,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub multisig: Pubkey,
+    pub signer: Pubkey,
+    pub index: u64,
+    /// Co-signing keys after execution
+    pub co_signers: Vec<Pubkey>,
+}
+
+#[error_code]
+pub enum SkelzError {
+    #[msg("Publisher name must be 1 to 32 bytes")]
+    InvalidPublisherName,
+    #[msg("Publisher namespace must be 1 to 128 bytes")]
+    InvalidNamespace,
+    #[msg("Publisher display name exceeds 64 bytes")]
+    DisplayNameTooLong,
+    #[msg("Publisher contact exceeds 128 bytes")]
+    ContactTooLong,
+    #[msg("Publisher must list between 1 and 8 keys")]
+    InvalidPublisherKeys,
+    #[msg("Signer is not the authority of this account")]
+    Unauthorized,
+    #[msg("A key cannot be rotated to itself")]
+    RotationToSelf,
+    #[msg("Rotation effective slot is in the past")]
+    RotationInThePast,
+    #[msg("Signature expiry is in the past")]
+    ExpiryInThePast,
+    #[msg("Signature is already revoked")]
+    AlreadyRevoked,
+    #[msg("Policy name must be 1 to 32 bytes")]
+    InvalidPolicyName,
+    #[msg("Policy repository pattern must be 1 to 128 bytes")]
+    InvalidRepositoryPattern,
+    #[msg("Policy must list between 1 and 16 signers")]
+    InvalidPolicySigners,
+    #[msg("Policy threshold must be between 1 and the number of signers")]
+    InvalidThreshold,
+    #[msg("Policy is frozen")]
+    PolicyFrozen,
+    #[msg("Policy version overflow")]
+    PolicyVersionOverflow,
+    #[msg("OIDC issuer must be 1 to 128 bytes")]
+    InvalidOidcIssuer,
+    #[msg("OIDC subject must be 1 to 256 bytes")]
+    InvalidOidcSubject,
+    #[msg("Workflow ref exceeds 256 bytes")]
+    WorkflowRefTooLong,
+    #[msg("Multisig must list between 1 and 10 distinct members")]
+    InvalidMultisigMembers,
+    #[msg("Signer is not a member of this multisig")]
+    NotMultisigMember,
+    #[msg("This signer is governed by a multisig; propose the action instead")]
+    MultisigRequired,
+    #[msg("Proposal count overflow")]
+    ProposalCountOverflow,
+    #[msg("Proposal was already executed")]
+    ProposalExecuted,
+    #[msg("Member already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal does not have enough approvals")]
+    ThresholdNotReached,
+    #[msg("Digest must be 1 to 96 bytes")]
+    InvalidDigest,
+    #[msg("Co-signer is the multisig signer, already trusted or not trusted")]
+    InvalidCoSigner,
+    #[msg("Multisig already trusts 8 co-signers")]
+    TooManyCoSigners,
+    #[msg("Signature account is missing or does not match the proposal")]
+    SignatureAccountMissing,
+    #[msg("Policy threshold must be 1 until several signers can sign one digest")]
+    UnsupportedPolicyThreshold,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn rotation_requires_the_new_key_signature() {
+        let (old_key, new_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rotation = Pubkey::find_program_address(&[b"rotation", old_key.as_ref()], &ID).0;
+        let (system, loader) = (anchor_lang::system_program::ID, Pubkey::new_unique());
+        let mut lamports = [1_000_000_000u64, 0, 0, 1];
+        let mut data: [Vec<u8>; 4] = Default::default();
+        let [old_lamports, new_lamports, rotation_lamports, system_lamports] = &mut lamports;
+        let [old_data, new_data, rotation_data, system_data] = &mut data;
+        let accounts = [
+            AccountInfo::new(&old_key, true, true, old_lamports, old_data, &system, false, 0),
+            // The successor's pubkey is passed without its signature
+            AccountInfo::new(&new_key, false, false, new_lamports, new_data, &system, false, 0),
+            AccountInfo::new(&rotation, false, true, rotation_lamports, rotation_data, &system, false, 0),
+            AccountInfo::new(&system, false, false, system_lamports, system_data, &loader, true, 0),
+        ];
+        let err = RotateKey::try_accounts(
+            &ID,
+            &mut &accounts[..],
+            &[],
+            &mut RotateKeyBumps::default(),
+            &mut BTreeSet::new(),
+        )
+        .err()
+        .unwrap();
+        match err {
+            Error::AnchorError(e) => {
+                assert_eq!(e.error_code_number, u32::from(ErrorCode::AccountNotSigner));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
 }
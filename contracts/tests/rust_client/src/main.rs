@@ -76,11 +76,13 @@ async fn main() -> anyhow::Result<()> {
         .request()
         .accounts(accounts::WriteSignature {
             signer: program.payer(),
+            payer: program.payer(),
             signature: signature_pda,
             system_program: system_program::ID,
         })
         .args(args::WriteSignature {
             digest: digest.to_string(),
+            not_after: None,
         })
         .instructions()?
         .remove(0);
@@ -107,11 +109,13 @@ async fn main() -> anyhow::Result<()> {
         .request()
         .accounts(accounts::WriteSignature {
             signer: program.payer(),
+            payer: program.payer(),
             signature: signature_pda,
             system_program: system_program::ID,
         })
         .args(args::WriteSignature {
             digest: digest.to_string(),
+            not_after: None,
         })
         .instructions()?
         .remove(0);
@@ -151,11 +155,13 @@ async fn main() -> anyhow::Result<()> {
         .request()
         .accounts(accounts::WriteSignature {
             signer: program.payer(),
+            payer: program.payer(),
             signature: signature_pda2,
             system_program: system_program::ID,
         })
         .args(args::WriteSignature {
             digest: digest2.to_string(),
+            not_after: None,
         })
         .instructions()?
         .remove(0);